@@ -65,9 +65,11 @@ blueprint! {
             resource_address: Address,
             auth: BucketRef,
         ) -> Bucket {
-            // FIXME: Check call depth
             // As we're statically checking transaction signers for authorization, we need to make sure
             // the call depth is `1` (not invoked by another component/blueprint).
+            if Context::call_depth() != 1 {
+                panic!("Not authorized! Withdrawal must be invoked directly by the transaction.")
+            }
 
             if !Context::transaction_signers().contains(&self.key) {
                 panic!("Not authorized! Make sure you sign transaction with the correct keys.",)
@@ -110,9 +112,11 @@ blueprint! {
             resource_address: Address,
             auth: BucketRef,
         ) -> Bucket {
-            // FIXME: Check call depth
             // As we're statically checking transaction signers for authorization, we need to make sure
             // the call depth is `1` (not invoked by another component/blueprint).
+            if Context::call_depth() != 1 {
+                panic!("Not authorized! Withdrawal must be invoked directly by the transaction.")
+            }
 
             if !Context::transaction_signers().contains(&self.key) {
                 panic!("Not authorized! Make sure you sign transaction with the correct keys.",)
@@ -7,17 +7,19 @@ fn test_hello() {
     // Set up environment.
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("${lib_name}"));
 
     // Test the `new` function.
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "Hello", "new", vec![], None)
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, false).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
@@ -25,11 +27,13 @@ fn test_hello() {
     // Test the `free_token` method.
     let component = receipt1.component(0).unwrap();
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(component, "free_token", vec![], Some(account))
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, false).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
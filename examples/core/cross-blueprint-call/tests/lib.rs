@@ -7,7 +7,7 @@ fn test_proxy_1() {
     // Set up environment.
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("cross_blueprint_call"));
 
@@ -19,9 +19,11 @@ fn test_proxy_1() {
 
     // Test the `new` function.
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "Proxy1", "new", vec![], None)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, true).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
@@ -29,11 +31,13 @@ fn test_proxy_1() {
     // Test the `get_gumball` method.
     let component = receipt1.component(0).unwrap();
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(component, "free_token", vec![], Some(account))
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, true).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
@@ -44,7 +48,7 @@ fn test_proxy_2() {
     // Set up environment.
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("cross_blueprint_call"));
 
@@ -56,9 +60,11 @@ fn test_proxy_2() {
 
     // Test the `new` function.
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "Proxy2", "new", vec![], None)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, true).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
@@ -66,11 +72,13 @@ fn test_proxy_2() {
     // Test the `get_gumball` method.
     let component = receipt1.component(0).unwrap();
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(component, "free_token", vec![], Some(account))
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, true).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
@@ -7,17 +7,19 @@ fn test_create_additional_admin() {
     // Set up environment.
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("flat_admin"));
 
     // Test the `new` function.
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "FlatAdmin", "new", vec!["test".to_string()], None)
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, false).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
@@ -26,6 +28,7 @@ fn test_create_additional_admin() {
     let flat_admin = receipt1.component(0).unwrap();
     let admin_badge = receipt1.resource_def(1).unwrap();
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(
             flat_admin,
             "create_additional_admin",
@@ -35,7 +38,8 @@ fn test_create_additional_admin() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, false).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
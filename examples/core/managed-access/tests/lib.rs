@@ -7,7 +7,7 @@ fn test_withdraw_all() {
     // Set up environment.
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("managed_access"));
 
@@ -21,11 +21,13 @@ fn test_withdraw_all() {
 
     // Test the `new` function.
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "ManagedAccess", "new", vec![], None)
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, false).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
@@ -34,6 +36,7 @@ fn test_withdraw_all() {
     let managed_access = receipt1.component(1).unwrap();
     let admin_badge = receipt1.resource_def(1).unwrap();
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(
             managed_access,
             "withdraw_all",
@@ -43,7 +46,8 @@ fn test_withdraw_all() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, false).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
@@ -7,6 +7,7 @@ use auto_lend::User;
 struct TestEnv<'a, L: Ledger> {
     executor: TransactionExecutor<'a, L>,
     key: Address,
+    sk: EcdsaPrivateKey,
     account: Address,
     usd: Address,
     lending_pool: Address,
@@ -14,17 +15,19 @@ struct TestEnv<'a, L: Ledger> {
 
 fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
     let mut executor = TransactionExecutor::new(ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("auto_lend"));
 
     let receipt = executor
         .run(
             TransactionBuilder::new(&executor)
+                .lock_fee(100.into(), account)
                 .new_token_fixed(HashMap::new(), 1_000_000.into())
                 .deposit_all_buckets(account)
                 .build(vec![key])
-                .unwrap(),
+                .unwrap()
+                .sign([&sk]),
             false,
         )
         .unwrap();
@@ -33,6 +36,7 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
     let receipt = executor
         .run(
             TransactionBuilder::new(&executor)
+                .lock_fee(100.into(), account)
                 .call_function(
                     package,
                     "AutoLend",
@@ -42,7 +46,8 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
                 )
                 .deposit_all_buckets(account)
                 .build(vec![key])
-                .unwrap(),
+                .unwrap()
+                .sign([&sk]),
             false,
         )
         .unwrap();
@@ -51,6 +56,7 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
     TestEnv {
         executor,
         key,
+        sk,
         account,
         usd,
         lending_pool,
@@ -62,10 +68,13 @@ fn create_user<'a, L: Ledger>(env: &mut TestEnv<'a, L>) -> Address {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(env.lending_pool, "new_user", args![], Some(env.account))
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -78,6 +87,8 @@ fn get_user_state<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address) ->
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "get_user",
@@ -86,12 +97,13 @@ fn get_user_state<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address) ->
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
     assert!(receipt.success);
-    let encoded = receipt.results.swap_remove(0).unwrap().unwrap().encoded;
+    let encoded = receipt.results.swap_remove(1).unwrap().unwrap().encoded;
     scrypto_decode(&encoded).unwrap()
 }
 
@@ -107,6 +119,8 @@ fn test_deposit_and_redeem() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "deposit",
@@ -115,7 +129,8 @@ fn test_deposit_and_redeem() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -138,6 +153,8 @@ fn test_deposit_and_redeem() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "set_deposit_interest_rate",
@@ -146,7 +163,8 @@ fn test_deposit_and_redeem() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -157,6 +175,8 @@ fn test_deposit_and_redeem() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "deposit",
@@ -165,7 +185,8 @@ fn test_deposit_and_redeem() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -188,6 +209,8 @@ fn test_deposit_and_redeem() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "redeem",
@@ -196,7 +219,8 @@ fn test_deposit_and_redeem() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -227,6 +251,8 @@ fn test_borrow_and_repay() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "deposit",
@@ -238,7 +264,8 @@ fn test_borrow_and_repay() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -261,6 +288,8 @@ fn test_borrow_and_repay() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "borrow",
@@ -269,7 +298,8 @@ fn test_borrow_and_repay() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -292,6 +322,8 @@ fn test_borrow_and_repay() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "set_borrow_interest_rate",
@@ -300,7 +332,8 @@ fn test_borrow_and_repay() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -311,6 +344,8 @@ fn test_borrow_and_repay() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "borrow",
@@ -319,7 +354,8 @@ fn test_borrow_and_repay() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -342,6 +378,8 @@ fn test_borrow_and_repay() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "repay",
@@ -350,7 +388,8 @@ fn test_borrow_and_repay() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -373,6 +412,8 @@ fn test_borrow_and_repay() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.lending_pool,
                     "repay",
@@ -384,7 +425,8 @@ fn test_borrow_and_repay() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -35,8 +35,8 @@ blueprint! {
                 .metadata("name", "Regulo")
                 .metadata("symbol", "REG")
                 .metadata("stage", "Stage 1 - Fixed supply, may be restricted transfer")
-                .flags(SHARED_METADATA_MUTABLE | RESTRICTED_TRANSFER)
-                .mutable_flags(MINTABLE | SHARED_METADATA_MUTABLE | RESTRICTED_TRANSFER)
+                .flags(SHARED_METADATA_MUTABLE | RESTRICTED_TRANSFER | RECALLABLE)
+                .mutable_flags(MINTABLE | SHARED_METADATA_MUTABLE | RESTRICTED_TRANSFER | RECALLABLE)
                 .badge(
                     general_admin.resource_def(),
                     ALL_PERMISSIONS
@@ -83,6 +83,13 @@ blueprint! {
             }     
         }
 
+        /// The general admin may seize tokens from any vault, e.g. to comply with a court order
+        /// Note that this operation will fail once the token has reached stage 3 and the RECALLABLE flag has been disabled
+        #[auth(admin_badge_def, keep_auth)]
+        pub fn recall_tokens(&self, vid: Vid, amount: Decimal) -> Bucket {
+            self.token_supply.resource_def().recall(vid, amount, auth)
+        }
+
         pub fn get_current_stage(&self) -> u8 {
             info!("Current stage is {}", self.current_stage);
             self.current_stage
@@ -126,7 +133,7 @@ blueprint! {
                 token_def.update_metadata(metadata, auth.clone());
 
                 // Set our flags appropriately now that the regulated period has ended
-                token_def.disable_flags(MINTABLE | RESTRICTED_TRANSFER | SHARED_METADATA_MUTABLE, auth.clone());
+                token_def.disable_flags(MINTABLE | RESTRICTED_TRANSFER | SHARED_METADATA_MUTABLE | RECALLABLE, auth.clone());
 
                 // Permanently prevent the flags from changing
                 token_def.lock_flags(ALL_FLAGS, auth.clone());
@@ -8,6 +8,7 @@ use x_perp_futures::PositionType;
 struct TestEnv<'a, L: Ledger> {
     executor: TransactionExecutor<'a, L>,
     key: Address,
+    sk: EcdsaPrivateKey,
     account: Address,
     usd: Address,
     clearing_house: Address,
@@ -15,17 +16,19 @@ struct TestEnv<'a, L: Ledger> {
 
 fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
     let mut executor = TransactionExecutor::new(ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("x_perp_futures"));
 
     let receipt = executor
         .run(
             TransactionBuilder::new(&executor)
+                .lock_fee(100.into(), account)
                 .new_token_fixed(HashMap::new(), 1_000_000.into())
                 .deposit_all_buckets(account)
                 .build(vec![key])
-                .unwrap(),
+                .unwrap()
+                .sign([&sk]),
             false,
         )
         .unwrap();
@@ -34,6 +37,7 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
     let receipt = executor
         .run(
             TransactionBuilder::new(&executor)
+                .lock_fee(100.into(), account)
                 .call_function(
                     package,
                     "ClearingHouse",
@@ -43,7 +47,8 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
                 )
                 .deposit_all_buckets(account)
                 .build(vec![key])
-                .unwrap(),
+                .unwrap()
+                .sign([&sk]),
             false,
         )
         .unwrap();
@@ -52,6 +57,7 @@ fn set_up_test_env<'a, L: Ledger>(ledger: &'a mut L) -> TestEnv<'a, L> {
     TestEnv {
         executor,
         key,
+        sk,
         account,
         usd,
         clearing_house,
@@ -63,10 +69,13 @@ fn create_user<'a, L: Ledger>(env: &mut TestEnv<'a, L>) -> Address {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(env.clearing_house, "new_user", args![], Some(env.account))
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -79,6 +88,8 @@ fn get_position<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address, nth:
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "get_position",
@@ -87,12 +98,13 @@ fn get_position<'a, L: Ledger>(env: &mut TestEnv<'a, L>, user_id: Address, nth:
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
     assert!(receipt.success);
-    let encoded = receipt.results.swap_remove(0).unwrap().unwrap().encoded;
+    let encoded = receipt.results.swap_remove(1).unwrap().unwrap().encoded;
     scrypto_decode(&encoded).unwrap()
 }
 
@@ -109,6 +121,8 @@ fn test_long() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "new_position",
@@ -122,7 +136,8 @@ fn test_long() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -143,6 +158,8 @@ fn test_long() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "new_position",
@@ -156,7 +173,8 @@ fn test_long() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -177,6 +195,8 @@ fn test_long() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "settle_position",
@@ -185,7 +205,8 @@ fn test_long() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -205,6 +226,8 @@ fn test_short() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "new_position",
@@ -218,7 +241,8 @@ fn test_short() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -239,6 +263,8 @@ fn test_short() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "new_position",
@@ -252,7 +278,8 @@ fn test_short() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -273,6 +300,8 @@ fn test_short() {
         .executor
         .run(
             TransactionBuilder::new(&env.executor)
+                .set_nonce(env.executor.nonce())
+                .lock_fee(100.into(), env.account)
                 .call_method(
                     env.clearing_house,
                     "settle_position",
@@ -281,7 +310,8 @@ fn test_short() {
                 )
                 .deposit_all_buckets(env.account)
                 .build(vec![env.key])
-                .unwrap(),
+                .unwrap()
+                .sign([&env.sk]),
             false,
         )
         .unwrap();
@@ -7,15 +7,17 @@ fn test_hello() {
     // Set up environment.
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(include_code!("magic_card"));
 
     // Test the `new` function.
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "HelloNft", "new", vec![], None)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, false).unwrap();
     println!("{:?}\n", receipt1);
     assert!(receipt1.success);
@@ -23,6 +25,7 @@ fn test_hello() {
     // Test the `buy_special_card` method.
     let component = receipt1.component(0).unwrap();
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(
             component,
             "buy_special_card",
@@ -31,7 +34,8 @@ fn test_hello() {
         )
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, false).unwrap();
     println!("{:?}\n", receipt2);
     assert!(receipt2.success);
@@ -39,6 +43,7 @@ fn test_hello() {
     // Test the `buy_special_card` method.
     let component = receipt1.component(0).unwrap();
     let transaction3 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_method(
             component,
             "buy_random_card",
@@ -47,7 +52,8 @@ fn test_hello() {
         )
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt3 = executor.run(transaction3, false).unwrap();
     println!("{:?}\n", receipt3);
     assert!(receipt3.success);
@@ -9,25 +9,28 @@ use scrypto::prelude::*;
 fn bench_transfer(b: &mut Bencher) {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key1 = executor.new_public_key();
+    let (key1, sk1) = executor.new_key_pair();
     let account1 = executor.new_account(key1);
-    let key2 = executor.new_public_key();
+    let (key2, _) = executor.new_key_pair();
     let account2 = executor.new_account(key2);
-    let transaction = TransactionBuilder::new(&executor)
-        .withdraw_from_account(
-            &ResourceAmount::Fungible {
-                amount: 1.into(),
-                resource_address: RADIX_TOKEN,
-            },
-            account1,
-        )
-        .drop_all_bucket_refs()
-        .deposit_all_buckets(account2)
-        .build(vec![key1])
-        .unwrap();
 
     b.iter(|| {
-        let receipt = executor.run(transaction.clone(), false).unwrap();
+        let transaction = TransactionBuilder::new(&executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), account1)
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: 1.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                account1,
+            )
+            .drop_all_bucket_refs()
+            .deposit_all_buckets(account2)
+            .build(vec![key1])
+            .unwrap()
+            .sign([&sk1]);
+        let receipt = executor.run(transaction, false).unwrap();
         assert!(receipt.success);
     });
 }
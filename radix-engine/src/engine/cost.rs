@@ -0,0 +1,82 @@
+use scrypto::types::*;
+
+use crate::engine::*;
+
+/// The default maximum number of cost units a transaction may consume.
+pub const DEFAULT_COST_UNIT_LIMIT: u32 = 100_000_000;
+
+/// The default price of a cost unit, in XRD (0.000001 XRD).
+pub const DEFAULT_COST_UNIT_PRICE: Decimal = Decimal(1_000_000_000_000);
+
+/// The cost of executing a single WASM instruction.
+pub const COST_PER_INSTRUCTION: u32 = 1;
+
+/// The cost of growing WASM memory by one page.
+pub const COST_PER_MEMORY_PAGE: u32 = 1_000;
+
+/// The base cost of a kernel call.
+pub const COST_PER_KERNEL_CALL: u32 = 1_000;
+
+/// The cost of every byte sent to the kernel.
+pub const COST_PER_KERNEL_CALL_BYTE: u32 = 1;
+
+/// The maximum number of cost units the refund of unused fee may consume, which are not charged.
+pub const MAX_FEE_REFUND_COST_UNITS: u32 = 10_000_000;
+
+/// Keeps track of the cost units consumed by a transaction.
+#[derive(Debug, Clone)]
+pub struct CostUnitCounter {
+    limit: u32,
+    consumed: u32,
+}
+
+impl CostUnitCounter {
+    /// Creates a counter with the given limit.
+    pub fn new(limit: u32) -> Self {
+        Self { limit, consumed: 0 }
+    }
+
+    /// Returns the maximum number of cost units.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Sets the maximum number of cost units, which is never below the number consumed so far.
+    pub fn set_limit(&mut self, limit: u32) {
+        self.limit = limit.max(self.consumed);
+    }
+
+    /// Returns the number of cost units consumed so far.
+    pub fn consumed(&self) -> u32 {
+        self.consumed
+    }
+
+    /// Returns the number of cost units left.
+    pub fn remaining(&self) -> u32 {
+        self.limit - self.consumed
+    }
+
+    /// Returns the number of cost units the given amount of XRD can pay for.
+    pub fn affordable(amount: Decimal, cost_unit_price: Decimal) -> u32 {
+        if cost_unit_price.is_positive() {
+            let units = (amount / cost_unit_price).0 / Decimal::one().0;
+            units.clamp(0, u32::MAX as i128) as u32
+        } else {
+            u32::MAX
+        }
+    }
+
+    /// Consumes some cost units, failing if the limit is exceeded.
+    pub fn consume(&mut self, n: u32) -> Result<(), RuntimeError> {
+        match self.consumed.checked_add(n) {
+            Some(total) if total <= self.limit => {
+                self.consumed = total;
+                Ok(())
+            }
+            _ => {
+                self.consumed = self.limit;
+                Err(RuntimeError::CostLimitExceeded)
+            }
+        }
+    }
+}
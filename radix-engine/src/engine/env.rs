@@ -5,6 +5,10 @@ use wasmi::*;
 pub const KERNEL_INDEX: usize = 0;
 /// Kernel entrance function name.
 pub const KERNEL_NAME: &str = "kernel";
+/// Cost metering function index.
+pub const GAS_INDEX: usize = 1;
+/// Cost metering function name.
+pub const GAS_NAME: &str = "gas";
 
 /// An `env` module resolver defines how symbols in `env` are resolved.
 pub struct EnvModuleResolver;
@@ -22,6 +26,14 @@ impl ModuleImportResolver for EnvModuleResolver {
                 }
                 Ok(FuncInstance::alloc_host(signature.clone(), KERNEL_INDEX))
             }
+            GAS_NAME => {
+                if signature.params() != [ValueType::I32] || signature.return_type().is_some() {
+                    return Err(Error::Instantiation(
+                        "Function signature does not match".into(),
+                    ));
+                }
+                Ok(FuncInstance::alloc_host(signature.clone(), GAS_INDEX))
+            }
             _ => Err(Error::Instantiation(format!(
                 "Export {} not found",
                 field_name
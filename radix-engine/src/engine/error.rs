@@ -1,5 +1,6 @@
 use sbor::*;
 use scrypto::rust::fmt;
+use scrypto::rust::string::String;
 use scrypto::types::*;
 use wasmi::*;
 
@@ -98,6 +99,12 @@ pub enum RuntimeError {
     /// The referenced bucket contains no resource.
     EmptyBucketRef,
 
+    /// An access rule which can never or always be satisfied.
+    InvalidAccessRule,
+
+    /// A withdraw rule for a resource without `RESTRICTED_TRANSFER`, which would never be checked.
+    WithdrawRuleNotEnforced,
+
     /// Bucket access error.
     BucketError(BucketError),
 
@@ -128,6 +135,12 @@ pub enum RuntimeError {
     /// Invalid log level.
     InvalidLogLevel,
 
+    /// The event is not declared in the ABI of the emitting blueprint.
+    EventNotDeclared(String),
+
+    /// The event data does not match the schema declared in the ABI.
+    InvalidEventData(String),
+
     /// The bucket id is not reserved.
     BucketNotReserved,
 
@@ -136,6 +149,21 @@ pub enum RuntimeError {
 
     /// Resource check failure.
     ResourceCheckFailure,
+
+    /// The transaction context does not hold the asserted resources.
+    ContextAssertionFailed(Address),
+
+    /// The transaction has used up all its cost units.
+    CostLimitExceeded,
+
+    /// The maximum call depth has been exceeded.
+    MaxCallDepthExceeded(usize),
+
+    /// A component method is called while another method of the same component is running.
+    ReentrantCallNotAllowed(Address, String),
+
+    /// The component state is locked by a method running in another call frame.
+    ComponentLocked(Address),
 }
 
 impl fmt::Display for RuntimeError {
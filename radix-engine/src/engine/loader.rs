@@ -1,3 +1,6 @@
+use scrypto::rust::string::ToString;
+use wasm_instrument::gas_metering::{self, ConstantCostRules};
+use wasm_instrument::parity_wasm::elements;
 use wasmi::*;
 
 use crate::engine::*;
@@ -7,6 +10,21 @@ pub fn parse_module(code: &[u8]) -> Result<Module, RuntimeError> {
     Module::from_buffer(code).map_err(RuntimeError::InvalidModule)
 }
 
+/// Parses a WASM module and injects cost metering into it.
+pub fn instrument_module(code: &[u8]) -> Result<Module, RuntimeError> {
+    let parsed =
+        elements::deserialize_buffer::<elements::Module>(code).map_err(|e: elements::Error| {
+            RuntimeError::InvalidModule(Error::Validation(e.to_string()))
+        })?;
+
+    let rules = ConstantCostRules::new(COST_PER_INSTRUCTION, COST_PER_MEMORY_PAGE);
+    let instrumented = gas_metering::inject(parsed, &rules, "env").map_err(|_| {
+        RuntimeError::InvalidModule(Error::Validation("Failed to inject cost metering".into()))
+    })?;
+
+    Module::from_parity_wasm_module(instrumented).map_err(RuntimeError::InvalidModule)
+}
+
 /// Instantiates a WASM module.
 pub fn instantiate_module(module: &Module) -> Result<(ModuleRef, MemoryRef), RuntimeError> {
     // Instantiate
@@ -35,6 +53,9 @@ pub fn validate_module(code: &[u8]) -> Result<(), RuntimeError> {
         .deny_floating_point()
         .map_err(|_| RuntimeError::FloatingPointNotAllowed)?;
 
+    // check cost metering
+    instrument_module(code)?;
+
     // Instantiate
     let instance = ModuleInstance::new(
         &parsed,
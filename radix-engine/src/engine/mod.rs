@@ -1,4 +1,5 @@
 mod allocator;
+mod cost;
 mod env;
 mod error;
 mod loader;
@@ -6,8 +7,9 @@ mod process;
 mod track;
 
 pub use allocator::IdAllocator;
-pub use env::{EnvModuleResolver, KERNEL_INDEX, KERNEL_NAME};
+pub use cost::*;
+pub use env::{EnvModuleResolver, GAS_INDEX, GAS_NAME, KERNEL_INDEX, KERNEL_NAME};
 pub use error::RuntimeError;
-pub use loader::{instantiate_module, parse_module, validate_module};
-pub use process::{Invocation, Process};
+pub use loader::{instantiate_module, instrument_module, parse_module, validate_module};
+pub use process::{Invocation, Process, DEFAULT_MAX_CALL_DEPTH};
 pub use track::Track;
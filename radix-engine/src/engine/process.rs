@@ -2,8 +2,12 @@ use colored::*;
 use sbor::any::*;
 use sbor::rust::boxed::Box;
 use sbor::*;
+use scrypto::abi;
+use scrypto::args;
 use scrypto::buffer::*;
 use scrypto::kernel::*;
+use scrypto::resource::resource_flags::*;
+use scrypto::resource::AccessRule;
 use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::collections::*;
 use scrypto::rust::convert::TryFrom;
@@ -11,6 +15,7 @@ use scrypto::rust::fmt;
 use scrypto::rust::format;
 use scrypto::rust::rc::Rc;
 use scrypto::rust::string::String;
+use scrypto::rust::string::ToString;
 use scrypto::rust::vec;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
@@ -19,6 +24,7 @@ use wasmi::*;
 use crate::engine::*;
 use crate::ledger::*;
 use crate::model::*;
+use crate::utils::*;
 
 macro_rules! re_trace {
     ($proc:expr, $($args: expr),+) => {
@@ -52,6 +58,9 @@ macro_rules! re_warn {
     };
 }
 
+/// The default maximum depth of nested calls.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 8;
+
 /// A process keeps track of resource movements and code execution.
 pub struct Process<'r, 'l, L: Ledger> {
     depth: usize,
@@ -60,12 +69,16 @@ pub struct Process<'r, 'l, L: Ledger> {
     buckets: HashMap<Bid, Bucket>,
     bucket_refs: HashMap<Rid, BucketRef>,
     locked_buckets: HashMap<Bid, BucketRef>,
+    signer_badges: HashSet<Bid>,
     moving_buckets: HashMap<Bid, Bucket>,
     moving_bucket_refs: HashMap<Rid, BucketRef>,
     temp_buckets: HashMap<Bid, Bucket>,
     temp_bucket_refs: HashMap<Rid, BucketRef>,
     reserved_bids: HashSet<Bid>,
     reserved_rids: HashSet<Rid>,
+    reentrant_component: Option<Address>,
+    call_traces: Option<Vec<CallTrace>>,
+    kernel_calls: Vec<u32>,
     vm: Option<Interpreter>,
 }
 
@@ -83,6 +96,7 @@ pub struct Invocation {
     export_name: String,
     function: String,
     args: Vec<Vec<u8>>,
+    component_address: Option<Address>,
 }
 
 impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
@@ -95,16 +109,36 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             buckets: HashMap::new(),
             bucket_refs: HashMap::new(),
             locked_buckets: HashMap::new(),
+            signer_badges: HashSet::new(),
             moving_buckets: HashMap::new(),
             moving_bucket_refs: HashMap::new(),
             temp_buckets: HashMap::new(),
             temp_bucket_refs: HashMap::new(),
             reserved_bids: HashSet::new(),
             reserved_rids: HashSet::new(),
+            reentrant_component: None,
+            call_traces: None,
+            kernel_calls: Vec::new(),
             vm: None,
         }
     }
 
+    /// Starts recording the calls made by this process and its children.
+    pub fn enable_call_trace(&mut self) {
+        self.call_traces = Some(Vec::new());
+    }
+
+    /// Takes the calls recorded by this process, if call trace is enabled.
+    pub fn take_call_traces(&mut self) -> Option<Vec<CallTrace>> {
+        self.call_traces.take()
+    }
+
+    /// Replaces the transaction signers, e.g. while running a partial transaction, and
+    /// returns the previous ones.
+    pub fn set_transaction_signers(&mut self, transaction_signers: Vec<Address>) -> Vec<Address> {
+        self.track.set_transaction_signers(transaction_signers)
+    }
+
     /// Reserves a BID.
     pub fn declare_bucket(&mut self) -> Bid {
         let bid = self.track.new_bid();
@@ -124,11 +158,6 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         amount: Decimal,
         resource_address: Address,
     ) -> Result<Bucket, RuntimeError> {
-        let definition = self
-            .track
-            .get_resource_def(resource_address)
-            .ok_or(RuntimeError::ResourceDefNotFound(resource_address))?;
-
         let candidates: BTreeSet<Bid> = self
             .buckets
             .iter()
@@ -136,16 +165,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .map(|(k, _)| *k)
             .collect();
 
-        let mut collector = Bucket::new(
-            resource_address,
-            definition.resource_type(),
-            match definition.resource_type() {
-                ResourceType::Fungible { .. } => Supply::Fungible { amount: 0.into() },
-                ResourceType::NonFungible { .. } => Supply::NonFungible {
-                    ids: BTreeSet::new(),
-                },
-            },
-        );
+        let mut collector = self.new_empty_bucket(resource_address)?;
         let mut needed = amount;
         for candidate in candidates {
             if needed.is_zero() {
@@ -180,6 +200,50 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         }
     }
 
+    fn withdraw_nfts(
+        &mut self,
+        ids: &BTreeSet<u128>,
+        resource_address: Address,
+    ) -> Result<Bucket, RuntimeError> {
+        let mut collector = self.new_empty_bucket(resource_address)?;
+        if let Supply::Fungible { .. } = collector.supply() {
+            return Err(RuntimeError::BucketError(BucketError::UnsupportedOperation));
+        }
+        for id in ids {
+            re_debug!(self, "Withdrawing NFT {:?}", id);
+            let bucket = self
+                .buckets
+                .values_mut()
+                .find(|b| {
+                    b.resource_address() == resource_address
+                        && matches!(b.get_nft_ids(), Ok(ids) if ids.contains(id))
+                })
+                .ok_or(RuntimeError::BucketError(BucketError::NftNotFound))?;
+            collector
+                .put(bucket.take_nft(*id).map_err(RuntimeError::BucketError)?)
+                .map_err(RuntimeError::BucketError)?;
+        }
+        Ok(collector)
+    }
+
+    fn new_empty_bucket(&mut self, resource_address: Address) -> Result<Bucket, RuntimeError> {
+        let definition = self
+            .track
+            .get_resource_def(resource_address)
+            .ok_or(RuntimeError::ResourceDefNotFound(resource_address))?;
+
+        Ok(Bucket::new(
+            resource_address,
+            definition.resource_type(),
+            match definition.resource_type() {
+                ResourceType::Fungible { .. } => Supply::Fungible { amount: 0.into() },
+                ResourceType::NonFungible => Supply::NonFungible {
+                    ids: BTreeSet::new(),
+                },
+            },
+        ))
+    }
+
     /// Takes resource from this context to a temporary bucket.
     pub fn take_from_context(
         &mut self,
@@ -203,6 +267,63 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(())
     }
 
+    /// Takes all of a resource from this context to a temporary bucket.
+    pub fn take_all_from_context(
+        &mut self,
+        resource_address: Address,
+        bid: Bid,
+    ) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Creating bucket: all of resource_address = {:?}, bid = {:?}",
+            resource_address,
+            bid
+        );
+        if !self.reserved_bids.remove(&bid) {
+            return Err(RuntimeError::BucketNotReserved);
+        }
+        let amount = self.context_amount(resource_address);
+        let bucket = self.withdraw_resource(amount, resource_address)?;
+        self.temp_buckets.insert(bid, bucket);
+
+        Ok(())
+    }
+
+    /// Takes the given NFTs from this context to a temporary bucket.
+    pub fn take_nfts_from_context(
+        &mut self,
+        ids: &BTreeSet<u128>,
+        resource_address: Address,
+        bid: Bid,
+    ) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Creating bucket: ids = {:?}, resource_address = {:?}, bid = {:?}",
+            ids,
+            resource_address,
+            bid
+        );
+        if !self.reserved_bids.remove(&bid) {
+            return Err(RuntimeError::BucketNotReserved);
+        }
+        let bucket = self.withdraw_nfts(ids, resource_address)?;
+        self.temp_buckets.insert(bid, bucket);
+
+        Ok(())
+    }
+
+    /// Returns a temporary bucket to this context.
+    pub fn return_to_context(&mut self, bid: Bid) -> Result<(), RuntimeError> {
+        re_debug!(self, "Returning bucket: bid = {:?}", bid);
+        let bucket = self
+            .temp_buckets
+            .remove(&bid)
+            .ok_or(RuntimeError::BucketNotFound(bid))?;
+        self.buckets.insert(bid, bucket);
+
+        Ok(())
+    }
+
     /// Borrows resource from this context to a temporary bucket ref.
     ///
     /// A bucket will be created to support the reference.
@@ -222,17 +343,128 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         if !self.reserved_rids.remove(&rid) {
             return Err(RuntimeError::BucketRefNotReserved);
         }
-        let bid = self.track.new_bid();
-        let bucket = BucketRef::new(LockedBucket::new(
-            bid,
-            self.withdraw_resource(amount, resource_address)?,
-        ));
-        self.locked_buckets.insert(bid, bucket.clone());
+        let bucket = if self.track.is_signer_badge(resource_address) {
+            // a signer badge borrowed again is backed by the same bucket, so that it's
+            // presented once no matter how many times it's borrowed
+            let badge = Self::new_signer_badge(amount, resource_address)?;
+            match self.locked_buckets.values().find(|b| {
+                self.signer_badges.contains(&b.bucket_id())
+                    && b.bucket().resource_address() == resource_address
+                    && b.bucket().amount() == badge.amount()
+            }) {
+                Some(bucket) => bucket.clone(),
+                None => {
+                    let bucket = self.lock_new_bucket(badge);
+                    self.signer_badges.insert(bucket.bucket_id());
+                    bucket
+                }
+            }
+        } else {
+            let bucket = self.withdraw_resource(amount, resource_address)?;
+            self.lock_new_bucket(bucket)
+        };
         self.temp_bucket_refs.insert(rid, bucket);
 
         Ok(())
     }
 
+    /// Locks a bucket under a new BID, and returns a reference to it.
+    fn lock_new_bucket(&mut self, bucket: Bucket) -> BucketRef {
+        let bid = self.track.new_bid();
+        let bucket_ref = BucketRef::new(LockedBucket::new(bid, bucket));
+        self.locked_buckets.insert(bid, bucket_ref.clone());
+        bucket_ref
+    }
+
+    /// Asserts that this context holds at least the given amount of a resource.
+    pub fn assert_context_contains(
+        &mut self,
+        amount: Decimal,
+        resource_address: Address,
+    ) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Asserting context contains: amount = {:?}, resource_address = {:?}",
+            amount,
+            resource_address
+        );
+        if self.context_amount(resource_address) >= amount {
+            Ok(())
+        } else {
+            Err(RuntimeError::ContextAssertionFailed(resource_address))
+        }
+    }
+
+    /// Asserts that this context holds all the given NFTs.
+    pub fn assert_context_contains_nfts(
+        &mut self,
+        ids: &BTreeSet<u128>,
+        resource_address: Address,
+    ) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Asserting context contains NFTs: ids = {:?}, resource_address = {:?}",
+            ids,
+            resource_address
+        );
+        let mut available = BTreeSet::new();
+        for bucket in self.context_buckets(resource_address) {
+            available.extend(bucket.get_nft_ids().map_err(RuntimeError::BucketError)?);
+        }
+        if ids.is_subset(&available) {
+            Ok(())
+        } else {
+            Err(RuntimeError::ContextAssertionFailed(resource_address))
+        }
+    }
+
+    /// Asserts that this context holds none of a resource.
+    pub fn assert_context_does_not_contain(
+        &mut self,
+        resource_address: Address,
+    ) -> Result<(), RuntimeError> {
+        re_debug!(
+            self,
+            "Asserting context does not contain: resource_address = {:?}",
+            resource_address
+        );
+        if self
+            .context_buckets(resource_address)
+            .all(|b| b.amount().is_zero())
+        {
+            Ok(())
+        } else {
+            Err(RuntimeError::ContextAssertionFailed(resource_address))
+        }
+    }
+
+    /// Returns the amount of a resource held by this context.
+    fn context_amount(&self, resource_address: Address) -> Decimal {
+        self.context_buckets(resource_address)
+            .fold(Decimal::zero(), |sum, b| sum + b.amount())
+    }
+
+    /// Returns the buckets of a resource held by this context.
+    fn context_buckets(&self, resource_address: Address) -> impl Iterator<Item = &Bucket> {
+        self.buckets
+            .values()
+            .filter(move |b| b.resource_address() == resource_address)
+    }
+
+    /// Creates a bucket of a signer's virtual badge, of which the supply is one.
+    fn new_signer_badge(
+        amount: Decimal,
+        resource_address: Address,
+    ) -> Result<Bucket, RuntimeError> {
+        Bucket::new(
+            resource_address,
+            ResourceType::Fungible { divisibility: 0 },
+            Supply::Fungible { amount: 1.into() },
+        )
+        .take(amount)
+        .map_err(RuntimeError::BucketError)
+    }
+
     /// Puts buckets and bucket refs into this process.
     pub fn put_resources(
         &mut self,
@@ -255,6 +487,68 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         self.buckets.keys().copied().collect()
     }
 
+    /// Locks fee by withdrawing XRD from an account, and then caps the cost units at the
+    /// given limit.
+    ///
+    /// A checkpoint of the track is created once the fee is locked, so that the rest of the
+    /// transaction can be rolled back while the fee stays locked.
+    pub fn lock_fee(
+        &mut self,
+        account: Address,
+        amount: Decimal,
+        cost_unit_limit: u32,
+    ) -> Result<Bucket, RuntimeError> {
+        re_debug!(
+            self,
+            "Locking fee: account = {:?}, amount = {:?}",
+            account,
+            amount
+        );
+        self.call_method(account, "withdraw", args!(amount, RADIX_TOKEN))?;
+        let reserve = self.withdraw_resource(amount, RADIX_TOKEN)?;
+        self.track.set_cost_unit_limit(cost_unit_limit);
+        self.track.checkpoint();
+        Ok(reserve)
+    }
+
+    /// Puts fee into the system XRD vault.
+    pub fn collect_fee(&mut self, fee: Bucket) -> Result<(), RuntimeError> {
+        re_debug!(self, "Collecting fee: amount = {:?}", fee.amount());
+        self.track
+            .get_vault_mut(XRD_VAULT_ID)
+            .ok_or(RuntimeError::VaultNotFound(XRD_VAULT_ID))?
+            .put(fee, Actor::SuperUser)
+            .map_err(RuntimeError::VaultError)
+    }
+
+    /// Returns unused fee to an account.
+    ///
+    /// If the account fails to take it, the deposit is rolled back and the unused fee is
+    /// returned instead.
+    pub fn refund_fee(&mut self, account: Address, bucket: Bucket) -> Result<(), Bucket> {
+        re_debug!(
+            self,
+            "Refunding fee: account = {:?}, amount = {:?}",
+            account,
+            bucket.amount()
+        );
+        let bid = self.track.new_bid();
+        self.buckets.insert(bid, bucket.clone());
+        self.track.checkpoint();
+        match self.call_method(account, "deposit", args!(bid)) {
+            Ok(_) => {
+                self.track.discard_checkpoint();
+                Ok(())
+            }
+            Err(e) => {
+                re_warn!(self, "Refunding fee failed: {:?}", e);
+                self.track.rollback();
+                self.buckets.remove(&bid);
+                Err(bucket)
+            }
+        }
+    }
+
     /// Returns all bucket ids.
     pub fn drop_bucket_refs(&mut self) {
         let rids: Vec<Rid> = self.bucket_refs.keys().copied().collect();
@@ -265,6 +559,22 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         }
     }
 
+    /// Returns the IDs of all bucket refs held by this process.
+    pub fn list_bucket_refs(&self) -> HashSet<Rid> {
+        self.bucket_refs
+            .keys()
+            .chain(self.temp_bucket_refs.keys())
+            .copied()
+            .collect()
+    }
+
+    /// Drops all bucket refs held by this process, except the given ones.
+    pub fn drop_bucket_refs_except(&mut self, rids: &HashSet<Rid>) {
+        self.bucket_refs.retain(|rid, _| rids.contains(rid));
+        self.temp_bucket_refs.retain(|rid, _| rids.contains(rid));
+        self.unlock_released_buckets();
+    }
+
     /// Runs the given export within this process.
     pub fn run(&mut self, invocation: Invocation) -> Result<Vec<u8>, RuntimeError> {
         #[cfg(not(feature = "alloc"))]
@@ -292,7 +602,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         let result = module.invoke_export(invocation.export_name.as_str(), &[], self);
         re_debug!(self, "Invoke result: {:?}", result);
         let rtn = result
-            .map_err(RuntimeError::InvokeError)?
+            .map_err(Self::unwrap_invoke_error)?
             .ok_or(RuntimeError::NoReturnData)?;
 
         // move resource based on return data
@@ -332,6 +642,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             export_name: format!("{}_main", blueprint_name),
             function: function.to_owned(),
             args,
+            component_address: None,
         })
     }
 
@@ -351,12 +662,14 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         let mut self_args = vec![scrypto_encode(&component_address)];
         self_args.extend(args);
 
-        self.prepare_call_function(
+        let mut invocation = self.prepare_call_function(
             component.package_address(),
             component.blueprint_name(),
             method,
             self_args,
-        )
+        )?;
+        invocation.component_address = Some(component_address);
+        Ok(invocation)
     }
 
     /// Prepares an ABI call.
@@ -370,28 +683,158 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             export_name: format!("{}_abi", blueprint_name),
             function: String::new(),
             args: Vec::new(),
+            component_address: None,
         })
     }
 
     /// Calls a function/method.
     pub fn call(&mut self, invocation: Invocation) -> Result<Vec<u8>, RuntimeError> {
+        if self.call_traces.is_none() {
+            return self.call_internal(invocation, None);
+        }
+
+        #[cfg(not(feature = "alloc"))]
+        let now = std::time::Instant::now();
+        let mut trace = CallTrace {
+            actor: self
+                .package()
+                .map(Actor::Package)
+                .unwrap_or(Actor::SuperUser),
+            package_address: invocation.package_address,
+            export_name: invocation.export_name.clone(),
+            function: invocation.function.clone(),
+            component_address: invocation.component_address,
+            args: invocation.args.iter().map(|arg| decode_any(arg)).collect(),
+            output: None,
+            error: None,
+            buckets_in: Vec::new(),
+            bucket_refs_in: Vec::new(),
+            buckets_out: Vec::new(),
+            bucket_refs_out: Vec::new(),
+            kernel_calls: Vec::new(),
+            time_elapsed: None,
+            children: Vec::new(),
+        };
+
+        let result = self.call_internal(invocation, Some(&mut trace));
+        match &result {
+            Ok(output) => trace.output = Some(decode_any(output)),
+            Err(e) => trace.error = Some(format!("{:?}", e)),
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            trace.time_elapsed = Some(now.elapsed().as_micros());
+        }
+        if let Some(traces) = self.call_traces.as_mut() {
+            traces.push(trace);
+        }
+
+        result
+    }
+
+    fn call_internal(
+        &mut self,
+        invocation: Invocation,
+        mut trace: Option<&mut CallTrace>,
+    ) -> Result<Vec<u8>, RuntimeError> {
+        // check call depth
+        if self.depth >= self.track.max_call_depth() {
+            return Err(RuntimeError::MaxCallDepthExceeded(
+                self.track.max_call_depth(),
+            ));
+        }
+
+        // check re-entrancy
+        let mut locked_component = None;
+        let mut reentrant_component = None;
+        if let Some(address) = invocation.component_address {
+            if self.track.component_lock(address).is_some() {
+                self.check_reentrant_call(address, &invocation.function)?;
+                reentrant_component = Some(address);
+            } else {
+                locked_component = Some(address);
+            }
+        }
+
         // move resource
         for arg in &invocation.args {
             self.process_data(arg, Self::move_buckets, Self::move_bucket_refs)?;
         }
         let (buckets_out, bucket_refs_out) = self.take_moving_resources();
+
+        // lock component, which is unlocked once the call returns
+        if let Some(address) = locked_component {
+            self.track.lock_component(address, self.depth + 1);
+        }
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.buckets_in = Self::summarize_buckets(&buckets_out);
+            trace.bucket_refs_in = Self::summarize_bucket_refs(&bucket_refs_out);
+        }
         let mut process = Process::new(self.depth + 1, self.trace, self.track);
         process.put_resources(buckets_out, bucket_refs_out);
+        process.reentrant_component = reentrant_component;
+        if trace.is_some() {
+            process.enable_call_trace();
+        }
 
         // run the function
-        let result = process.run(invocation)?;
+        let result = process.run(invocation);
+        if let Some(address) = locked_component {
+            process.track.unlock_component(address);
+        }
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.kernel_calls = process.kernel_calls.drain(..).collect();
+            trace.children = process.take_call_traces().unwrap_or_default();
+        }
+        let result = result?;
         process.check_resource()?;
 
         // move resource
         let (buckets_in, bucket_refs_in) = process.take_moving_resources();
+        if let Some(trace) = trace {
+            trace.buckets_out = Self::summarize_buckets(&buckets_in);
+            trace.bucket_refs_out = Self::summarize_bucket_refs(&bucket_refs_in);
+        }
         self.put_resources(buckets_in, bucket_refs_in);
+        self.unlock_released_buckets();
 
-        // scan locked buckets for some might have been unlocked by child processes
+        Ok(result)
+    }
+
+    /// Calls a function/method within a checkpoint of the track.
+    ///
+    /// If the call fails, its state updates are rolled back and the buckets and bucket refs
+    /// passed to it are returned to this process, so that the caller can recover. Running
+    /// out of cost units can't be recovered from.
+    pub fn call_try(&mut self, invocation: Invocation) -> Result<Vec<u8>, RuntimeError> {
+        // the callee can only take what is passed to it, so only that is kept for recovery
+        let moved = invocation.args.iter().try_for_each(|arg| {
+            self.process_data(arg, Self::move_buckets, Self::move_bucket_refs)
+                .map(|_| ())
+        });
+        let (buckets, bucket_refs) = self.take_moving_resources();
+        self.put_resources(buckets.clone(), bucket_refs.clone());
+        moved?;
+
+        self.track.checkpoint();
+        let result = self.call(invocation);
+        match &result {
+            Ok(_) | Err(RuntimeError::CostLimitExceeded) => {
+                self.track.discard_checkpoint();
+                drop(bucket_refs);
+            }
+            Err(_) => {
+                self.track.rollback();
+                self.put_resources(buckets, bucket_refs);
+            }
+        }
+        self.unlock_released_buckets();
+        result
+    }
+
+    /// Unlocks the buckets which are no longer borrowed, for some bucket refs might have
+    /// been dropped by child processes.
+    fn unlock_released_buckets(&mut self) {
         let bids: Vec<Bid> = self
             .locked_buckets
             .values()
@@ -402,10 +845,94 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             re_debug!(self, "Changing bucket {:?} to unlocked state", bid);
             let bucket_rc = self.locked_buckets.remove(&bid).unwrap();
             let bucket = Rc::try_unwrap(bucket_rc).unwrap();
-            self.buckets.insert(bid, bucket.into());
+            self.unlock_bucket(bid, bucket.into());
         }
+    }
 
-        Ok(result)
+    /// Returns a bucket which is no longer borrowed to this process.
+    fn unlock_bucket(&mut self, bid: Bid, bucket: Bucket) {
+        // signer badges are virtual and vanish once no longer borrowed, even if the
+        // transaction signers have changed since they were created
+        if !self.signer_badges.remove(&bid) {
+            self.buckets.insert(bid, bucket);
+        }
+    }
+
+    fn summarize_buckets(buckets: &HashMap<Bid, Bucket>) -> Vec<(Bid, Decimal, Address)> {
+        let mut summary: Vec<(Bid, Decimal, Address)> = buckets
+            .iter()
+            .map(|(bid, bucket)| (*bid, bucket.amount(), bucket.resource_address()))
+            .collect();
+        summary.sort_by_key(|(bid, _, _)| *bid);
+        summary
+    }
+
+    fn summarize_bucket_refs(
+        bucket_refs: &HashMap<Rid, BucketRef>,
+    ) -> Vec<(Rid, Decimal, Address)> {
+        let mut summary: Vec<(Rid, Decimal, Address)> = bucket_refs
+            .iter()
+            .map(|(rid, bucket_ref)| {
+                (
+                    *rid,
+                    bucket_ref.bucket().amount(),
+                    bucket_ref.bucket().resource_address(),
+                )
+            })
+            .collect();
+        summary.sort_by_key(|(rid, _, _)| *rid);
+        summary
+    }
+
+    /// Checks if a component method can be called while the component is locked.
+    ///
+    /// Only read-only methods of components which allow re-entrant reads can be re-entered.
+    fn check_reentrant_call(
+        &mut self,
+        component_address: Address,
+        method: &str,
+    ) -> Result<(), RuntimeError> {
+        let component = self
+            .track
+            .get_component(component_address)
+            .ok_or(RuntimeError::ComponentNotFound(component_address))?
+            .clone();
+
+        if component.reentrant_reads() {
+            let abi =
+                self.blueprint_abi(component.package_address(), component.blueprint_name())?;
+            if abi
+                .methods
+                .iter()
+                .any(|m| m.name == method && matches!(m.mutability, abi::Mutability::Immutable))
+            {
+                return Ok(());
+            }
+        }
+
+        Err(RuntimeError::ReentrantCallNotAllowed(
+            component_address,
+            method.to_owned(),
+        ))
+    }
+
+    /// Checks if this process can access the state of a component.
+    fn check_component_access(
+        &self,
+        component_address: Address,
+        write: bool,
+    ) -> Result<(), RuntimeError> {
+        let reentrant = self.reentrant_component == Some(component_address);
+        let locked_by_others = matches!(
+            self.track.component_lock(component_address),
+            Some(depth) if depth != self.depth
+        );
+
+        if (locked_by_others && !reentrant) || (write && reentrant) {
+            Err(RuntimeError::ComponentLocked(component_address))
+        } else {
+            Ok(())
+        }
     }
 
     /// Calls a function.
@@ -451,6 +978,64 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         result
     }
 
+    /// Calls the ABI generator of a blueprint and decodes its functions, methods and events.
+    pub fn export_abi(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        let rtn = self.call_abi(package_address, blueprint_name)?;
+        Self::decode_abi(package_address, blueprint_name, &rtn)
+    }
+
+    /// Returns the ABI of a blueprint, for the engine's own checks.
+    ///
+    /// The ABI generator is run in a process of its own rather than called, so that it's not
+    /// traced nor limited by the call depth, and only once per transaction, as the ABI is
+    /// cached by the track.
+    fn blueprint_abi(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+    ) -> Result<Rc<abi::Blueprint>, RuntimeError> {
+        if let Some(abi) = self.track.get_abi(package_address, blueprint_name) {
+            return Ok(abi);
+        }
+
+        let invocation = self.prepare_call_abi(package_address, blueprint_name)?;
+        let mut process = Process::new(self.depth + 1, self.trace, self.track);
+        let rtn = process.run(invocation)?;
+        process.check_resource()?;
+
+        let abi = Rc::new(Self::decode_abi(package_address, blueprint_name, &rtn)?);
+        self.track
+            .put_abi(package_address, blueprint_name, abi.clone());
+        Ok(abi)
+    }
+
+    /// Decodes the functions, methods and events returned by the ABI generator of a blueprint.
+    ///
+    /// Blueprints built before events were introduced export no events.
+    fn decode_abi(
+        package_address: Address,
+        blueprint_name: &str,
+        rtn: &[u8],
+    ) -> Result<abi::Blueprint, RuntimeError> {
+        let (functions, methods, events) = scrypto_decode(rtn).or_else(|e| {
+            scrypto_decode::<(Vec<abi::Function>, Vec<abi::Method>)>(rtn)
+                .map(|(functions, methods)| (functions, methods, Vec::new()))
+                .map_err(|_| RuntimeError::InvalidData(e))
+        })?;
+
+        Ok(abi::Blueprint {
+            package: package_address.to_string(),
+            name: blueprint_name.to_owned(),
+            functions,
+            methods,
+            events,
+        })
+    }
+
     /// Checks resource leak.
     pub fn check_resource(&self) -> Result<(), RuntimeError> {
         re_debug!(self, "Resource check started");
@@ -513,6 +1098,13 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .map(|vm| vm.invocation.package_address)
     }
 
+    /// Return the component address, if a method is running
+    fn component_address(&self) -> Option<Address> {
+        self.vm
+            .as_ref()
+            .and_then(|vm| vm.invocation.component_address)
+    }
+
     /// Return the function name
     fn function(&self) -> Result<String, RuntimeError> {
         self.vm
@@ -725,15 +1317,33 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Err(RuntimeError::BucketRefNotAllowed)
     }
 
+    /// Recovers the runtime error raised by a kernel call, if any.
+    fn unwrap_invoke_error(error: Error) -> RuntimeError {
+        match error.try_into_host_error() {
+            Ok(host_error) => match host_error.downcast::<RuntimeError>() {
+                Ok(e) => *e,
+                Err(e) => RuntimeError::InvokeError(Error::Host(e)),
+            },
+            Err(e) => RuntimeError::InvokeError(e),
+        }
+    }
+
     /// Send a byte array to wasm instance.
     fn send_bytes(&mut self, bytes: &[u8]) -> Result<i32, RuntimeError> {
-        let result = self.module()?.invoke_export(
-            "scrypto_alloc",
-            &[RuntimeValue::I32((bytes.len()) as i32)],
-            &mut NopExternals,
-        );
+        // errors raised by the kernel, e.g. running out of cost units, are kept
+        let result = self
+            .module()?
+            .invoke_export(
+                "scrypto_alloc",
+                &[RuntimeValue::I32((bytes.len()) as i32)],
+                self,
+            )
+            .map_err(|e| match Self::unwrap_invoke_error(e) {
+                RuntimeError::InvokeError(_) => RuntimeError::MemoryAllocError,
+                e => e,
+            })?;
 
-        if let Ok(Some(RuntimeValue::I32(ptr))) = result {
+        if let Some(RuntimeValue::I32(ptr)) = result {
             if self.memory()?.set((ptr + 4) as u32, bytes).is_ok() {
                 return Ok(ptr);
             }
@@ -759,12 +1369,11 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
 
         // free the buffer
         self.module()?
-            .invoke_export(
-                "scrypto_free",
-                &[RuntimeValue::I32(ptr as i32)],
-                &mut NopExternals,
-            )
-            .map_err(RuntimeError::MemoryAccessError)?;
+            .invoke_export("scrypto_free", &[RuntimeValue::I32(ptr)], self)
+            .map_err(|e| match Self::unwrap_invoke_error(e) {
+                RuntimeError::InvokeError(e) => RuntimeError::MemoryAccessError(e),
+                e => e,
+            })?;
 
         Ok(data)
     }
@@ -839,7 +1448,19 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         &mut self,
         optional_rid: Option<Rid>,
     ) -> Result<Actor, RuntimeError> {
-        if let Some(rid) = optional_rid {
+        self.authenticate_with_badges(optional_rid.into_iter().collect())
+            .map(|(actor, _)| actor)
+    }
+
+    /// Authenticates with all the bucket refs presented, and returns the total amount
+    /// presented per resource along with the actor.
+    fn authenticate_with_badges(
+        &mut self,
+        rids: Vec<Rid>,
+    ) -> Result<(Actor, HashMap<Address, Decimal>), RuntimeError> {
+        let mut proofs = HashMap::<Address, Decimal>::new();
+        let mut presented = HashSet::<Bid>::new();
+        for rid in rids {
             // retrieve bucket reference
             let bucket_ref = self
                 .bucket_refs
@@ -847,19 +1468,45 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
                 .ok_or(RuntimeError::BucketRefNotFound(rid))?;
 
             // read amount & address
-            if bucket_ref.bucket().amount().is_zero() {
+            let amount = bucket_ref.bucket().amount();
+            if amount.is_zero() {
                 return Err(RuntimeError::EmptyBucketRef);
             }
             let resource_address = bucket_ref.bucket().resource_address();
+            let bid = bucket_ref.bucket_id();
 
             // drop bucket reference after use
             self.handle_drop_bucket_ref(DropBucketRefInput { rid })?;
 
-            let mut set = HashSet::new();
-            set.insert(resource_address);
-            Ok(Actor::PackageWithBadges(self.package()?, set))
-        } else {
-            Ok(Actor::PackageWithBadges(self.package()?, HashSet::new()))
+            // references to the same bucket are counted once
+            if presented.insert(bid) {
+                *proofs.entry(resource_address).or_insert_with(Decimal::zero) += amount;
+            }
+        }
+
+        let badges = proofs.keys().cloned().collect();
+        Ok((Actor::PackageWithBadges(self.package()?, badges), proofs))
+    }
+
+    fn expect_valid_access_rule(rule: &AccessRule) -> Result<(), RuntimeError> {
+        match rule {
+            AccessRule::Require {
+                resource_address, ..
+            } => Self::expect_resource_address(*resource_address),
+            AccessRule::AllOf(rules) | AccessRule::CountOf(_, rules) => {
+                // requiring none of the rules, or more rules than there are, is rejected
+                let required = match rule {
+                    AccessRule::CountOf(count, _) => *count as usize,
+                    _ => rules.len(),
+                };
+                if required == 0 || required > rules.len() {
+                    return Err(RuntimeError::InvalidAccessRule);
+                }
+                for r in rules {
+                    Self::expect_valid_access_rule(r)?;
+                }
+                Ok(())
+            }
         }
     }
 
@@ -934,6 +1581,33 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(CallMethodOutput { rtn: result? })
     }
 
+    fn handle_call_method_try(
+        &mut self,
+        input: CallMethodTryInput,
+    ) -> Result<CallMethodTryOutput, RuntimeError> {
+        Self::expect_component_address(input.component_address)?;
+
+        re_debug!(
+            self,
+            "CALL started: component = {:?}, method = {:?}, args = {:?}",
+            input.component_address,
+            input.method,
+            input.args
+        );
+
+        let result = self
+            .prepare_call_method(input.component_address, input.method.as_str(), input.args)
+            .and_then(|invocation| self.call_try(invocation));
+
+        re_debug!(self, "CALL finished");
+        match result {
+            Err(RuntimeError::CostLimitExceeded) => Err(RuntimeError::CostLimitExceeded),
+            result => Ok(CallMethodTryOutput {
+                rtn: result.map_err(|e| format!("{:?}", e)),
+            }),
+        }
+    }
+
     fn handle_create_component(
         &mut self,
         input: CreateComponentInput,
@@ -953,6 +1627,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             new_state
         );
 
+        self.track.claim_vaults(component_address, &new_state);
         let component = Component::new(self.package()?, input.blueprint_name, new_state);
         self.track.put_component(component_address, component);
 
@@ -982,6 +1657,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<GetComponentStateOutput, RuntimeError> {
         Self::expect_component_address(input.component_address)?;
         let actor = self.authenticate()?;
+        self.check_component_access(input.component_address, false)?;
 
         let component = self
             .track
@@ -1003,10 +1679,12 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
     ) -> Result<PutComponentStateOutput, RuntimeError> {
         Self::expect_component_address(input.component_address)?;
         let actor = self.authenticate()?;
+        self.check_component_access(input.component_address, true)?;
 
         let new_state =
             self.process_data(&input.state, Self::reject_buckets, Self::reject_bucket_refs)?;
         re_debug!(self, "Transformed state: {:?}", new_state);
+        self.track.claim_vaults(input.component_address, &new_state);
 
         let component = self
             .track
@@ -1020,6 +1698,25 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(PutComponentStateOutput {})
     }
 
+    fn handle_allow_reentrant_reads(
+        &mut self,
+        input: AllowReentrantReadsInput,
+    ) -> Result<AllowReentrantReadsOutput, RuntimeError> {
+        Self::expect_component_address(input.component_address)?;
+        let actor = self.authenticate()?;
+
+        let component = self
+            .track
+            .get_component_mut(input.component_address)
+            .ok_or(RuntimeError::ComponentNotFound(input.component_address))?;
+
+        component
+            .allow_reentrant_reads(actor)
+            .map_err(RuntimeError::ComponentError)?;
+
+        Ok(AllowReentrantReadsOutput {})
+    }
+
     fn handle_create_lazy_map(
         &mut self,
         _input: CreateLazyMapInput,
@@ -1031,6 +1728,10 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         }
 
         self.track.put_lazy_map(mid, LazyMap::new(self.package()?));
+        if let Some(component_address) = self.component_address() {
+            self.track
+                .claim_vaults(component_address, &scrypto_encode(&mid));
+        }
 
         Ok(CreateLazyMapOutput { mid })
     }
@@ -1074,8 +1775,12 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .ok_or(RuntimeError::LazyMapNotFound(input.mid))?;
 
         lazy_map
-            .set_entry(new_key, new_value, actor)
+            .set_entry(new_key.clone(), new_value.clone(), actor)
             .map_err(RuntimeError::LazyMapError)?;
+        if let Some(owner) = lazy_map.owner() {
+            self.track.claim_vaults(owner, &new_key);
+            self.track.claim_vaults(owner, &new_value);
+        }
 
         Ok(PutLazyMapEntryOutput {})
     }
@@ -1155,6 +1860,24 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         })
     }
 
+    fn handle_create_resource_with_withdraw_rule(
+        &mut self,
+        input: CreateResourceWithWithdrawRuleInput,
+    ) -> Result<CreateResourceOutput, RuntimeError> {
+        Self::expect_valid_access_rule(&input.withdraw_rule)?;
+        if input.resource.flags & RESTRICTED_TRANSFER != RESTRICTED_TRANSFER {
+            return Err(RuntimeError::WithdrawRuleNotEnforced);
+        }
+
+        let output = self.handle_create_resource(input.resource)?;
+        self.track
+            .get_resource_def_mut(output.resource_address)
+            .ok_or(RuntimeError::ResourceDefNotFound(output.resource_address))?
+            .set_withdraw_rule(input.withdraw_rule);
+
+        Ok(output)
+    }
+
     fn handle_get_resource_metadata(
         &mut self,
         input: GetResourceMetadataInput,
@@ -1407,6 +2130,10 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         );
         let vid = self.track.new_vid();
         self.track.put_vault(vid, new_vault);
+        if let Some(component_address) = self.component_address() {
+            self.track
+                .claim_vaults(component_address, &scrypto_encode(&vid));
+        }
 
         Ok(CreateEmptyVaultOutput { vid })
     }
@@ -1431,7 +2158,12 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(PutIntoVaultOutput {})
     }
 
-    fn check_take_from_vault_auth(&mut self, vid: Vid, actor: Actor) -> Result<(), RuntimeError> {
+    fn check_take_from_vault_auth(
+        &mut self,
+        vid: Vid,
+        actor: Actor,
+        proofs: &HashMap<Address, Decimal>,
+    ) -> Result<(), RuntimeError> {
         let resource_address = self
             .track
             .get_vault(vid)
@@ -1443,7 +2175,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
             .get_resource_def(resource_address)
             .ok_or(RuntimeError::ResourceDefNotFound(resource_address))?;
         resource_def
-            .check_take_from_vault_auth(actor)
+            .check_take_from_vault_auth(actor, proofs)
             .map_err(RuntimeError::ResourceDefError)
     }
 
@@ -1451,8 +2183,8 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         &mut self,
         input: TakeFromVaultInput,
     ) -> Result<TakeFromVaultOutput, RuntimeError> {
-        let actor = self.authenticate_with_badge(input.auth)?;
-        self.check_take_from_vault_auth(input.vid, actor.clone())?;
+        let (actor, proofs) = self.authenticate_with_badges(input.auth.into_iter().collect())?;
+        self.check_take_from_vault_auth(input.vid, actor.clone(), &proofs)?;
 
         let new_bucket = self
             .track
@@ -1467,12 +2199,32 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(TakeFromVaultOutput { bid })
     }
 
+    fn handle_take_from_vault_with_auths(
+        &mut self,
+        input: TakeFromVaultWithAuthsInput,
+    ) -> Result<TakeFromVaultWithAuthsOutput, RuntimeError> {
+        let (actor, proofs) = self.authenticate_with_badges(input.auths)?;
+        self.check_take_from_vault_auth(input.vid, actor.clone(), &proofs)?;
+
+        let new_bucket = self
+            .track
+            .get_vault_mut(input.vid)
+            .ok_or(RuntimeError::VaultNotFound(input.vid))?
+            .take(input.amount, actor)
+            .map_err(RuntimeError::VaultError)?;
+
+        let bid = self.track.new_bid();
+        self.buckets.insert(bid, new_bucket);
+
+        Ok(TakeFromVaultWithAuthsOutput { bid })
+    }
+
     fn handle_take_nft_from_vault(
         &mut self,
         input: TakeNftFromVaultInput,
     ) -> Result<TakeNftFromVaultOutput, RuntimeError> {
-        let actor = self.authenticate_with_badge(input.auth)?;
-        self.check_take_from_vault_auth(input.vid, actor.clone())?;
+        let (actor, proofs) = self.authenticate_with_badges(input.auth.into_iter().collect())?;
+        self.check_take_from_vault_auth(input.vid, actor.clone(), &proofs)?;
 
         let new_bucket = self
             .track
@@ -1503,6 +2255,90 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         })
     }
 
+    fn handle_recall_from_vault(
+        &mut self,
+        input: RecallFromVaultInput,
+    ) -> Result<RecallFromVaultOutput, RuntimeError> {
+        let actor = self.authenticate_with_badge(Some(input.auth))?;
+
+        // obtain authorization from resource definition
+        let resource_def = self
+            .track
+            .get_resource_def(input.resource_address)
+            .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
+        resource_def
+            .check_recall_auth(actor)
+            .map_err(RuntimeError::ResourceDefError)?;
+
+        // bypass the vault authority, which is the owner package
+        let new_bucket = self
+            .get_vault_of_resource_mut(input.vid, input.resource_address)?
+            .recall(input.amount)
+            .map_err(RuntimeError::VaultError)?;
+
+        let bid = self.track.new_bid();
+        self.buckets.insert(bid, new_bucket);
+
+        Ok(RecallFromVaultOutput { bid })
+    }
+
+    fn handle_get_vault_freeze_flags(
+        &mut self,
+        input: GetVaultFreezeFlagsInput,
+    ) -> Result<GetVaultFreezeFlagsOutput, RuntimeError> {
+        let actor = self.authenticate()?;
+
+        let vault = self
+            .track
+            .get_vault(input.vid)
+            .ok_or(RuntimeError::VaultNotFound(input.vid))?;
+
+        Ok(GetVaultFreezeFlagsOutput {
+            freeze_flags: vault
+                .freeze_flags(actor)
+                .map_err(RuntimeError::VaultError)?,
+        })
+    }
+
+    fn handle_update_vault_freeze_flags(
+        &mut self,
+        input: UpdateVaultFreezeFlagsInput,
+    ) -> Result<UpdateVaultFreezeFlagsOutput, RuntimeError> {
+        let actor = self.authenticate_with_badge(Some(input.auth))?;
+
+        // obtain authorization from resource definition
+        let resource_def = self
+            .track
+            .get_resource_def(input.resource_address)
+            .ok_or(RuntimeError::ResourceDefNotFound(input.resource_address))?;
+        resource_def
+            .check_freeze_auth(actor)
+            .map_err(RuntimeError::ResourceDefError)?;
+
+        self.get_vault_of_resource_mut(input.vid, input.resource_address)?
+            .set_freeze_flags(input.new_freeze_flags);
+
+        Ok(UpdateVaultFreezeFlagsOutput {})
+    }
+
+    /// Returns a vault for updates by the resource authorities.
+    fn get_vault_of_resource_mut(
+        &mut self,
+        vid: Vid,
+        resource_address: Address,
+    ) -> Result<&mut Vault, RuntimeError> {
+        let vault = self
+            .track
+            .get_vault_mut(vid)
+            .ok_or(RuntimeError::VaultNotFound(vid))?;
+        if vault.resource_address(Actor::SuperUser).unwrap() != resource_address {
+            return Err(RuntimeError::VaultError(VaultError::AccountingError(
+                BucketError::ResourceNotMatching,
+            )));
+        }
+        Ok(vault)
+    }
+
     fn handle_get_vault_amount(
         &mut self,
         input: GetVaultDecimalInput,
@@ -1553,7 +2389,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
                 ResourceType::Fungible { .. } => Supply::Fungible {
                     amount: Decimal::zero(),
                 },
-                ResourceType::NonFungible { .. } => Supply::NonFungible {
+                ResourceType::NonFungible => Supply::NonFungible {
                     ids: BTreeSet::new(),
                 },
             },
@@ -1710,7 +2546,7 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
 
         if count == 1 {
             if let Some(b) = self.locked_buckets.remove(&bid) {
-                self.buckets.insert(bid, Rc::try_unwrap(b).unwrap().into());
+                self.unlock_bucket(bid, Rc::try_unwrap(b).unwrap().into());
             }
         }
 
@@ -1790,6 +2626,48 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         Ok(EmitLogOutput {})
     }
 
+    fn handle_emit_event(
+        &mut self,
+        input: EmitEventInput,
+    ) -> Result<EmitEventOutput, RuntimeError> {
+        let data =
+            self.process_data(&input.data, Self::reject_buckets, Self::reject_bucket_refs)?;
+        let invocation = &self
+            .vm
+            .as_ref()
+            .ok_or(RuntimeError::InterpreterNotStarted)?
+            .invocation;
+        let package_address = invocation.package_address;
+        let component_address = invocation.component_address;
+        let blueprint_name = invocation
+            .export_name
+            .strip_suffix("_main")
+            .unwrap_or(&invocation.export_name)
+            .to_owned();
+        re_debug!(self, "Event emitted: name = {:?}", input.name);
+
+        // the event must be declared by the blueprint, and match the declared schema
+        let abi = self.blueprint_abi(package_address, &blueprint_name)?;
+        let event = abi
+            .events
+            .iter()
+            .find(|e| e.name == input.name)
+            .ok_or_else(|| RuntimeError::EventNotDeclared(input.name.clone()))?;
+        let value = decode_any(&data).map_err(RuntimeError::InvalidData)?;
+        if !value_matches_type(&value, &event.schema) {
+            return Err(RuntimeError::InvalidEventData(input.name));
+        }
+
+        self.track.add_event(Event::new(
+            package_address,
+            component_address,
+            input.name,
+            data,
+        ));
+
+        Ok(EmitEventOutput {})
+    }
+
     fn handle_get_package_address(
         &mut self,
         _input: GetPackageAddressInput,
@@ -1836,6 +2714,15 @@ impl<'r, 'l, L: Ledger> Process<'r, 'l, L> {
         })
     }
 
+    fn handle_get_call_depth(
+        &mut self,
+        _input: GetCallDepthInput,
+    ) -> Result<GetCallDepthOutput, RuntimeError> {
+        Ok(GetCallDepthOutput {
+            call_depth: self.depth as u32,
+        })
+    }
+
     fn handle_generate_uuid(
         &mut self,
         _input: GenerateUuidInput,
@@ -1859,15 +2746,28 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
         match index {
             KERNEL_INDEX => {
                 let operation: u32 = args.nth_checked(0)?;
+                let input_len: u32 = args.nth_checked(2)?;
+                self.track
+                    .consume_cost_units(
+                        COST_PER_KERNEL_CALL
+                            .saturating_add(input_len.saturating_mul(COST_PER_KERNEL_CALL_BYTE)),
+                    )
+                    .map_err(Trap::from)?;
+                if self.call_traces.is_some() {
+                    self.kernel_calls.push(operation);
+                }
+
                 match operation {
                     PUBLISH_PACKAGE => self.handle(args, Self::handle_publish),
                     CALL_FUNCTION => self.handle(args, Self::handle_call_function),
                     CALL_METHOD => self.handle(args, Self::handle_call_method),
+                    CALL_METHOD_TRY => self.handle(args, Self::handle_call_method_try),
 
                     CREATE_COMPONENT => self.handle(args, Self::handle_create_component),
                     GET_COMPONENT_INFO => self.handle(args, Self::handle_get_component_info),
                     GET_COMPONENT_STATE => self.handle(args, Self::handle_get_component_state),
                     PUT_COMPONENT_STATE => self.handle(args, Self::handle_put_component_state),
+                    ALLOW_REENTRANT_READS => self.handle(args, Self::handle_allow_reentrant_reads),
 
                     CREATE_LAZY_MAP => self.handle(args, Self::handle_create_lazy_map),
                     GET_LAZY_MAP_ENTRY => self.handle(args, Self::handle_get_lazy_map_entry),
@@ -1896,6 +2796,9 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                     UPDATE_RESOURCE_METADATA => {
                         self.handle(args, Self::handle_update_resource_metadata)
                     }
+                    CREATE_RESOURCE_WITH_WITHDRAW_RULE => {
+                        self.handle(args, Self::handle_create_resource_with_withdraw_rule)
+                    }
 
                     CREATE_EMPTY_VAULT => self.handle(args, Self::handle_create_vault),
                     PUT_INTO_VAULT => self.handle(args, Self::handle_put_into_vault),
@@ -1906,6 +2809,16 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                     }
                     TAKE_NFT_FROM_VAULT => self.handle(args, Self::handle_take_nft_from_vault),
                     GET_NFT_IDS_IN_VAULT => self.handle(args, Self::handle_get_nft_ids_in_vault),
+                    RECALL_FROM_VAULT => self.handle(args, Self::handle_recall_from_vault),
+                    GET_VAULT_FREEZE_FLAGS => {
+                        self.handle(args, Self::handle_get_vault_freeze_flags)
+                    }
+                    UPDATE_VAULT_FREEZE_FLAGS => {
+                        self.handle(args, Self::handle_update_vault_freeze_flags)
+                    }
+                    TAKE_FROM_VAULT_WITH_AUTHS => {
+                        self.handle(args, Self::handle_take_from_vault_with_auths)
+                    }
 
                     CREATE_EMPTY_BUCKET => self.handle(args, Self::handle_create_bucket),
                     PUT_INTO_BUCKET => self.handle(args, Self::handle_put_into_bucket),
@@ -1929,6 +2842,7 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                     CLONE_BUCKET_REF => self.handle(args, Self::handle_clone_bucket_ref),
 
                     EMIT_LOG => self.handle(args, Self::handle_emit_log),
+                    EMIT_EVENT => self.handle(args, Self::handle_emit_event),
                     GET_PACKAGE_ADDRESS => self.handle(args, Self::handle_get_package_address),
                     GET_CALL_DATA => self.handle(args, Self::handle_get_call_data),
                     GET_TRANSACTION_HASH => self.handle(args, Self::handle_get_transaction_hash),
@@ -1937,10 +2851,16 @@ impl<'r, 'l, L: Ledger> Externals for Process<'r, 'l, L> {
                         self.handle(args, Self::handle_get_transaction_signers)
                     }
                     GENERATE_UUID => self.handle(args, Self::handle_generate_uuid),
+                    GET_CALL_DEPTH => self.handle(args, Self::handle_get_call_depth),
 
                     _ => Err(RuntimeError::InvalidRequestCode(operation).into()),
                 }
             }
+            GAS_INDEX => {
+                let amount: u32 = args.nth_checked(0)?;
+                self.track.consume_cost_units(amount).map_err(Trap::from)?;
+                Ok(None)
+            }
             _ => Err(RuntimeError::HostFunctionNotFound(index).into()),
         }
     }
@@ -1,7 +1,17 @@
+use core::hash::Hash;
 use lru::LruCache;
+use sbor::any::*;
+use scrypto::abi;
+use scrypto::buffer::*;
 use scrypto::kernel::*;
+use scrypto::rust::borrow::Borrow;
+use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::collections::*;
+use scrypto::rust::convert::TryFrom;
+use scrypto::rust::mem;
+use scrypto::rust::rc::Rc;
 use scrypto::rust::string::String;
+use scrypto::rust::vec;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
 use wasmi::*;
@@ -23,7 +33,11 @@ pub struct Track<'l, L: Ledger> {
     transaction_hash: H256,
     transaction_signers: Vec<Address>,
     id_alloc: IdAllocator,
+    cost_unit_counter: CostUnitCounter,
+    max_call_depth: usize,
+    component_locks: HashMap<Address, usize>,
     logs: Vec<(LogLevel, String)>,
+    events: Vec<Event>,
     packages: HashMap<Address, Package>,
     components: HashMap<Address, Component>,
     resource_defs: HashMap<Address, ResourceDef>,
@@ -37,7 +51,29 @@ pub struct Track<'l, L: Ledger> {
     updated_vaults: HashSet<Vid>,
     updated_nfts: HashSet<(Address, u128)>,
     new_entities: Vec<Address>,
+    checkpoints: Vec<Checkpoint>,
     code_cache: LruCache<Address, Module>, // TODO: move to ledger level
+    abi_cache: HashMap<(Address, String), Rc<abi::Blueprint>>,
+}
+
+/// The state of the substates updated since a checkpoint, before their first update.
+///
+/// Each entry keeps the previous value, if any, and whether the substate had already been
+/// updated within the transaction.
+type UndoLog<K, V> = HashMap<K, (Option<V>, bool)>;
+
+/// A point of a track's state updates, which can be rolled back to.
+///
+/// Only the substates updated since are recorded, so creating a checkpoint is cheap.
+struct Checkpoint {
+    events: usize,
+    new_entities: usize,
+    packages: UndoLog<Address, Package>,
+    components: UndoLog<Address, Component>,
+    resource_defs: UndoLog<Address, ResourceDef>,
+    lazy_maps: UndoLog<Mid, LazyMap>,
+    vaults: UndoLog<Vid, Vault>,
+    nfts: UndoLog<(Address, u128), Nft>,
 }
 
 impl<'l, L: Ledger> Track<'l, L> {
@@ -46,6 +82,8 @@ impl<'l, L: Ledger> Track<'l, L> {
         current_epoch: u64,
         transaction_hash: H256,
         transaction_signers: Vec<Address>,
+        cost_unit_limit: u32,
+        max_call_depth: usize,
     ) -> Self {
         Self {
             ledger,
@@ -53,7 +91,11 @@ impl<'l, L: Ledger> Track<'l, L> {
             transaction_hash,
             transaction_signers,
             id_alloc: IdAllocator::new(),
+            cost_unit_counter: CostUnitCounter::new(cost_unit_limit),
+            max_call_depth,
+            component_locks: HashMap::new(),
             logs: Vec::new(),
+            events: Vec::new(),
             packages: HashMap::new(),
             components: HashMap::new(),
             resource_defs: HashMap::new(),
@@ -67,7 +109,9 @@ impl<'l, L: Ledger> Track<'l, L> {
             updated_vaults: HashSet::new(),
             updated_nfts: HashSet::new(),
             new_entities: Vec::new(),
+            checkpoints: Vec::new(),
             code_cache: LruCache::new(1024),
+            abi_cache: HashMap::new(),
         }
     }
 
@@ -86,16 +130,69 @@ impl<'l, L: Ledger> Track<'l, L> {
         self.transaction_signers.clone()
     }
 
+    /// Replaces the transaction signers, e.g. while running a partial transaction, and
+    /// returns the previous ones.
+    pub fn set_transaction_signers(&mut self, transaction_signers: Vec<Address>) -> Vec<Address> {
+        mem::replace(&mut self.transaction_signers, transaction_signers)
+    }
+
+    /// Checks if an address is the virtual badge of any transaction signer.
+    pub fn is_signer_badge(&self, address: Address) -> bool {
+        self.transaction_signers
+            .iter()
+            .any(|signer| signer.signer_badge() == Some(address))
+    }
+
     /// Returns the current epoch.
     pub fn current_epoch(&self) -> u64 {
         self.current_epoch
     }
 
+    /// Returns the cost unit counter.
+    pub fn cost_unit_counter(&self) -> &CostUnitCounter {
+        &self.cost_unit_counter
+    }
+
+    /// Sets the maximum number of cost units the transaction may consume.
+    pub fn set_cost_unit_limit(&mut self, limit: u32) {
+        self.cost_unit_counter.set_limit(limit);
+    }
+
+    /// Consumes some cost units.
+    pub fn consume_cost_units(&mut self, n: u32) -> Result<(), RuntimeError> {
+        self.cost_unit_counter.consume(n)
+    }
+
+    /// Returns the maximum call depth.
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// Returns the depth of the call frame which holds the lock of a component, if any.
+    pub fn component_lock(&self, address: Address) -> Option<usize> {
+        self.component_locks.get(&address).cloned()
+    }
+
+    /// Locks a component to the call frame at the given depth.
+    pub fn lock_component(&mut self, address: Address, depth: usize) {
+        self.component_locks.insert(address, depth);
+    }
+
+    /// Unlocks a component.
+    pub fn unlock_component(&mut self, address: Address) {
+        self.component_locks.remove(&address);
+    }
+
     /// Returns the logs collected so far.
     pub fn logs(&self) -> &Vec<(LogLevel, String)> {
         &self.logs
     }
 
+    /// Returns the events emitted so far.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
     /// Returns new entities created so far.
     pub fn new_entities(&self) -> &[Address] {
         &self.new_entities
@@ -106,6 +203,81 @@ impl<'l, L: Ledger> Track<'l, L> {
         self.logs.push((level, message));
     }
 
+    /// Adds an event.
+    pub fn add_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Creates a checkpoint of the state updates made so far.
+    ///
+    /// Checkpoints can be nested, and each must be either rolled back or discarded.
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(Checkpoint {
+            events: self.events.len(),
+            new_entities: self.new_entities.len(),
+            packages: HashMap::new(),
+            components: HashMap::new(),
+            resource_defs: HashMap::new(),
+            lazy_maps: HashMap::new(),
+            vaults: HashMap::new(),
+            nfts: HashMap::new(),
+        });
+    }
+
+    /// Reverts all state updates, events and new entities since the last checkpoint,
+    /// and removes the checkpoint.
+    ///
+    /// Logs, consumed cost units and allocated IDs are kept.
+    pub fn rollback(&mut self) {
+        let checkpoint = self
+            .checkpoints
+            .pop()
+            .expect("No checkpoint to roll back to");
+        self.events.truncate(checkpoint.events);
+        self.new_entities.truncate(checkpoint.new_entities);
+        undo(
+            checkpoint.packages,
+            &mut self.packages,
+            &mut self.updated_packages,
+        );
+        undo(
+            checkpoint.components,
+            &mut self.components,
+            &mut self.updated_components,
+        );
+        undo(
+            checkpoint.resource_defs,
+            &mut self.resource_defs,
+            &mut self.updated_resource_defs,
+        );
+        undo(
+            checkpoint.lazy_maps,
+            &mut self.lazy_maps,
+            &mut self.updated_lazy_maps,
+        );
+        undo(
+            checkpoint.vaults,
+            &mut self.vaults,
+            &mut self.updated_vaults,
+        );
+        undo(checkpoint.nfts, &mut self.nfts, &mut self.updated_nfts);
+    }
+
+    /// Removes the last checkpoint, keeping all state updates since.
+    pub fn discard_checkpoint(&mut self) {
+        let checkpoint = self.checkpoints.pop().expect("No checkpoint to discard");
+
+        // the updates since become updates since the enclosing checkpoint, if any
+        if let Some(outer) = self.checkpoints.last_mut() {
+            merge(&mut outer.packages, checkpoint.packages);
+            merge(&mut outer.components, checkpoint.components);
+            merge(&mut outer.resource_defs, checkpoint.resource_defs);
+            merge(&mut outer.lazy_maps, checkpoint.lazy_maps);
+            merge(&mut outer.vaults, checkpoint.vaults);
+            merge(&mut outer.nfts, checkpoint.nfts);
+        }
+    }
+
     /// Loads a module.
     pub fn load_module(&mut self, address: Address) -> Option<(ModuleRef, MemoryRef)> {
         match self.get_package(address).map(Clone::clone) {
@@ -113,7 +285,7 @@ impl<'l, L: Ledger> Track<'l, L> {
                 if let Some(m) = self.code_cache.get(&address) {
                     Some(instantiate_module(m).unwrap())
                 } else {
-                    let module = parse_module(p.code()).unwrap();
+                    let module = instrument_module(p.code()).unwrap();
                     let inst = instantiate_module(&module).unwrap();
                     self.code_cache.put(address, module);
                     Some(inst)
@@ -123,6 +295,28 @@ impl<'l, L: Ledger> Track<'l, L> {
         }
     }
 
+    /// Returns the ABI of a blueprint, if it has been exported within this transaction.
+    pub fn get_abi(
+        &self,
+        package_address: Address,
+        blueprint_name: &str,
+    ) -> Option<Rc<abi::Blueprint>> {
+        self.abi_cache
+            .get(&(package_address, blueprint_name.to_owned()))
+            .cloned()
+    }
+
+    /// Caches the ABI of a blueprint, which can't change as packages are immutable.
+    pub fn put_abi(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+        abi: Rc<abi::Blueprint>,
+    ) {
+        self.abi_cache
+            .insert((package_address, blueprint_name.to_owned()), abi);
+    }
+
     /// Returns an immutable reference to a package, if exists.
     pub fn get_package(&mut self, address: Address) -> Option<&Package> {
         if self.packages.contains_key(&address) {
@@ -140,6 +334,14 @@ impl<'l, L: Ledger> Track<'l, L> {
     /// Returns a mutable reference to a package, if exists.
     #[allow(dead_code)]
     pub fn get_package_mut(&mut self, address: Address) -> Option<&mut Package> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.packages,
+                &self.packages,
+                &self.updated_packages,
+                address,
+            );
+        }
         self.updated_packages.insert(address);
 
         if self.packages.contains_key(&address) {
@@ -156,6 +358,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new package.
     pub fn put_package(&mut self, address: Address, package: Package) {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.packages,
+                &self.packages,
+                &self.updated_packages,
+                address,
+            );
+        }
         self.updated_packages.insert(address);
 
         self.packages.insert(address, package);
@@ -176,6 +386,14 @@ impl<'l, L: Ledger> Track<'l, L> {
     }
     /// Returns a mutable reference to a component, if exists.
     pub fn get_component_mut(&mut self, address: Address) -> Option<&mut Component> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.components,
+                &self.components,
+                &self.updated_components,
+                address,
+            );
+        }
         self.updated_components.insert(address);
 
         if self.components.contains_key(&address) {
@@ -192,6 +410,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new component.
     pub fn put_component(&mut self, address: Address, component: Component) {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.components,
+                &self.components,
+                &self.updated_components,
+                address,
+            );
+        }
         self.updated_components.insert(address);
 
         self.components.insert(address, component);
@@ -213,6 +439,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns a mutable reference to a nft, if exists.
     pub fn get_nft_mut(&mut self, resource_address: Address, id: u128) -> Option<&mut Nft> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.nfts,
+                &self.nfts,
+                &self.updated_nfts,
+                (resource_address, id),
+            );
+        }
         self.updated_nfts.insert((resource_address, id));
 
         if self.nfts.contains_key(&(resource_address, id)) {
@@ -229,6 +463,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new nft.
     pub fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft) {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.nfts,
+                &self.nfts,
+                &self.updated_nfts,
+                (resource_address, id),
+            );
+        }
         self.updated_nfts.insert((resource_address, id));
 
         self.nfts.insert((resource_address, id), nft);
@@ -250,6 +492,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns a mutable reference to a lazy map, if exists.
     pub fn get_lazy_map_mut(&mut self, mid: Mid) -> Option<&mut LazyMap> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.lazy_maps,
+                &self.lazy_maps,
+                &self.updated_lazy_maps,
+                mid,
+            );
+        }
         self.updated_lazy_maps.insert(mid);
 
         if self.lazy_maps.contains_key(&mid) {
@@ -266,6 +516,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new lazy map.
     pub fn put_lazy_map(&mut self, mid: Mid, lazy_map: LazyMap) {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.lazy_maps,
+                &self.lazy_maps,
+                &self.updated_lazy_maps,
+                mid,
+            );
+        }
         self.updated_lazy_maps.insert(mid);
 
         self.lazy_maps.insert(mid, lazy_map);
@@ -288,6 +546,14 @@ impl<'l, L: Ledger> Track<'l, L> {
     /// Returns a mutable reference to a resource definition, if exists.
     #[allow(dead_code)]
     pub fn get_resource_def_mut(&mut self, address: Address) -> Option<&mut ResourceDef> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.resource_defs,
+                &self.resource_defs,
+                &self.updated_resource_defs,
+                address,
+            );
+        }
         self.updated_resource_defs.insert(address);
 
         if self.resource_defs.contains_key(&address) {
@@ -304,6 +570,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new resource definition.
     pub fn put_resource_def(&mut self, address: Address, resource_def: ResourceDef) {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.resource_defs,
+                &self.resource_defs,
+                &self.updated_resource_defs,
+                address,
+            );
+        }
         self.updated_resource_defs.insert(address);
 
         self.resource_defs.insert(address, resource_def);
@@ -326,6 +600,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Returns a mutable reference to a vault, if exists.
     pub fn get_vault_mut(&mut self, vid: Vid) -> Option<&mut Vault> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.vaults,
+                &self.vaults,
+                &self.updated_vaults,
+                vid,
+            );
+        }
         self.updated_vaults.insert(vid);
 
         if self.vaults.contains_key(&vid) {
@@ -342,6 +624,14 @@ impl<'l, L: Ledger> Track<'l, L> {
 
     /// Inserts a new vault.
     pub fn put_vault(&mut self, vid: Vid, vault: Vault) {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            save(
+                &mut checkpoint.vaults,
+                &self.vaults,
+                &self.updated_vaults,
+                vid,
+            );
+        }
         self.updated_vaults.insert(vid);
 
         self.vaults.insert(vid, vault);
@@ -393,6 +683,160 @@ impl<'l, L: Ledger> Track<'l, L> {
         self.id_alloc.new_mid(self.transaction_hash())
     }
 
+    /// Makes a component the owner of the vaults and lazy maps referenced by some data, along
+    /// with those nested in the lazy maps, replacing any previous owner.
+    pub fn claim_vaults(&mut self, owner: Address, data: &[u8]) {
+        let mut vids = Vec::new();
+        let mut mids = Vec::new();
+        collect_ids(data, &mut vids, &mut mids);
+
+        while let Some(mid) = mids.pop() {
+            match self.get_lazy_map(mid) {
+                Some(lazy_map) if lazy_map.owner() != Some(owner) => {}
+                _ => continue,
+            }
+            let lazy_map = self.get_lazy_map_mut(mid).unwrap();
+            lazy_map.set_owner(owner);
+            for (key, value) in lazy_map.map() {
+                collect_ids(key, &mut vids, &mut mids);
+                collect_ids(value, &mut vids, &mut mids);
+            }
+        }
+
+        for vid in vids {
+            if matches!(self.get_vault(vid), Some(vault) if vault.owner() != Some(owner)) {
+                self.get_vault_mut(vid).unwrap().set_owner(owner);
+            }
+        }
+    }
+
+    /// Returns the balance changes of the vaults updated so far, grouped by owning component.
+    ///
+    /// Vaults with no net change are omitted.
+    pub fn balance_changes(&mut self) -> Vec<ComponentBalanceChanges> {
+        // compare with ledger state
+        let mut vids: Vec<Vid> = self.updated_vaults.iter().copied().collect();
+        vids.sort_by_key(Vid::to_vec);
+        let mut changes: Vec<(Option<Address>, BalanceChange)> = Vec::new();
+        for vid in vids {
+            let vault = self.vaults.get(&vid).unwrap();
+            let (amount_after, nfts_after) = Self::vault_balance(vault);
+            let (amount_before, nfts_before) = match self.ledger.get_vault(vid) {
+                Some(v) => Self::vault_balance(&v),
+                None => (Decimal::zero(), BTreeSet::new()),
+            };
+            let change = BalanceChange {
+                vid,
+                resource_address: vault.resource_address(Actor::SuperUser).unwrap(),
+                amount: amount_after - amount_before,
+                nfts_added: nfts_after.difference(&nfts_before).copied().collect(),
+                nfts_removed: nfts_before.difference(&nfts_after).copied().collect(),
+            };
+            if !change.amount.is_zero()
+                || !change.nfts_added.is_empty()
+                || !change.nfts_removed.is_empty()
+            {
+                changes.push((vault.owner(), change));
+            }
+        }
+
+        // group by owner
+        changes.sort_by_key(|(owner, _)| owner.map(|a| a.to_vec()).unwrap_or_default());
+        let mut result: Vec<ComponentBalanceChanges> = Vec::new();
+        for (owner, change) in changes {
+            match result.last_mut() {
+                Some(last) if last.component_address == owner => last.changes.push(change),
+                _ => result.push(ComponentBalanceChanges {
+                    component_address: owner,
+                    changes: vec![change],
+                }),
+            }
+        }
+        result
+    }
+
+    fn vault_balance(vault: &Vault) -> (Decimal, BTreeSet<u128>) {
+        match vault.total_supply(Actor::SuperUser).unwrap() {
+            Supply::Fungible { amount } => (amount, BTreeSet::new()),
+            Supply::NonFungible { ids } => (ids.len().into(), ids),
+        }
+    }
+
+    /// Returns the changes of all substates updated so far, compared to the ledger state.
+    pub fn state_diff(&self) -> Vec<SubstateChange> {
+        let mut changes = Vec::new();
+
+        let mut addresses: Vec<Address> = self.updated_packages.iter().copied().collect();
+        addresses.sort_by_key(Address::to_vec);
+        for address in addresses {
+            changes.push(SubstateChange {
+                id: SubstateId::Package(address),
+                before: self.ledger.get_package(address).map(|v| scrypto_encode(&v)),
+                after: scrypto_encode(self.packages.get(&address).unwrap()),
+            });
+        }
+
+        let mut addresses: Vec<Address> = self.updated_components.iter().copied().collect();
+        addresses.sort_by_key(Address::to_vec);
+        for address in addresses {
+            changes.push(SubstateChange {
+                id: SubstateId::Component(address),
+                before: self
+                    .ledger
+                    .get_component(address)
+                    .map(|v| scrypto_encode(&v)),
+                after: scrypto_encode(self.components.get(&address).unwrap()),
+            });
+        }
+
+        let mut mids: Vec<Mid> = self.updated_lazy_maps.iter().copied().collect();
+        mids.sort_by_key(Mid::to_vec);
+        for mid in mids {
+            changes.push(SubstateChange {
+                id: SubstateId::LazyMap(mid),
+                before: self.ledger.get_lazy_map(mid).map(|v| scrypto_encode(&v)),
+                after: scrypto_encode(self.lazy_maps.get(&mid).unwrap()),
+            });
+        }
+
+        let mut addresses: Vec<Address> = self.updated_resource_defs.iter().copied().collect();
+        addresses.sort_by_key(Address::to_vec);
+        for address in addresses {
+            changes.push(SubstateChange {
+                id: SubstateId::ResourceDef(address),
+                before: self
+                    .ledger
+                    .get_resource_def(address)
+                    .map(|v| scrypto_encode(&v)),
+                after: scrypto_encode(self.resource_defs.get(&address).unwrap()),
+            });
+        }
+
+        let mut vids: Vec<Vid> = self.updated_vaults.iter().copied().collect();
+        vids.sort_by_key(Vid::to_vec);
+        for vid in vids {
+            changes.push(SubstateChange {
+                id: SubstateId::Vault(vid),
+                before: self.ledger.get_vault(vid).map(|v| scrypto_encode(&v)),
+                after: scrypto_encode(self.vaults.get(&vid).unwrap()),
+            });
+        }
+
+        let mut nfts: Vec<(Address, u128)> = self.updated_nfts.iter().copied().collect();
+        nfts.sort_by_key(|(address, id)| (address.to_vec(), *id));
+        for (address, id) in nfts {
+            changes.push(SubstateChange {
+                id: SubstateId::Nft(address, id),
+                before: self.ledger.get_nft(address, id).map(|v| scrypto_encode(&v)),
+                after: scrypto_encode(self.nfts.get(&(address, id)).unwrap()),
+            });
+        }
+
+        // substates which are only borrowed mutably may be unchanged
+        changes.retain(|c| c.before.as_ref() != Some(&c.after));
+        changes
+    }
+
     /// Commits changes to the underlying ledger.
     pub fn commit(&mut self) {
         for address in self.updated_packages.clone() {
@@ -429,3 +873,94 @@ impl<'l, L: Ledger> Track<'l, L> {
         }
     }
 }
+
+/// Records the state of a substate before its first update since a checkpoint.
+fn save<K: Copy + Eq + Hash, V: Clone>(
+    log: &mut UndoLog<K, V>,
+    substates: &HashMap<K, V>,
+    updated: &HashSet<K>,
+    key: K,
+) {
+    log.entry(key)
+        .or_insert_with(|| (substates.get(&key).cloned(), updated.contains(&key)));
+}
+
+/// Restores the substates recorded by an undo log.
+fn undo<K: Copy + Eq + Hash, V>(
+    log: UndoLog<K, V>,
+    substates: &mut HashMap<K, V>,
+    updated: &mut HashSet<K>,
+) {
+    for (key, (value, was_updated)) in log {
+        match value {
+            Some(value) => substates.insert(key, value),
+            None => substates.remove(&key),
+        };
+        if !was_updated {
+            updated.remove(&key);
+        }
+    }
+}
+
+/// Merges the undo log of a checkpoint into the one of its enclosing checkpoint, which keeps
+/// the earlier state of any substate recorded by both.
+fn merge<K: Copy + Eq + Hash, V>(log: &mut UndoLog<K, V>, nested: UndoLog<K, V>) {
+    for (key, entry) in nested {
+        log.entry(key).or_insert(entry);
+    }
+}
+
+/// Collects the vault and lazy map ids referenced by some SBOR data.
+fn collect_ids(data: &[u8], vids: &mut Vec<Vid>, mids: &mut Vec<Mid>) {
+    if let Ok(value) = decode_any(data) {
+        collect_ids_in_value(&value, vids, mids);
+    }
+}
+
+fn collect_ids_in_value(value: &Value, vids: &mut Vec<Vid>, mids: &mut Vec<Mid>) {
+    match value {
+        Value::Struct(fields) | Value::Enum(_, fields) => match fields {
+            Fields::Named(values) | Fields::Unnamed(values) => {
+                values
+                    .iter()
+                    .for_each(|v| collect_ids_in_value(v, vids, mids));
+            }
+            Fields::Unit => {}
+        },
+        Value::Option(v) => {
+            if let Some(v) = v.borrow() {
+                collect_ids_in_value(v, vids, mids);
+            }
+        }
+        Value::Box(v) => collect_ids_in_value(v, vids, mids),
+        Value::Result(v) => match v.borrow() {
+            Ok(v) | Err(v) => collect_ids_in_value(v, vids, mids),
+        },
+        Value::Array(_, values)
+        | Value::Tuple(values)
+        | Value::Vec(_, values)
+        | Value::TreeSet(_, values)
+        | Value::HashSet(_, values) => {
+            values
+                .iter()
+                .for_each(|v| collect_ids_in_value(v, vids, mids));
+        }
+        Value::TreeMap(_, _, entries) | Value::HashMap(_, _, entries) => {
+            for (k, v) in entries {
+                collect_ids_in_value(k, vids, mids);
+                collect_ids_in_value(v, vids, mids);
+            }
+        }
+        Value::Custom(SCRYPTO_TYPE_VID, data) => {
+            if let Ok(vid) = Vid::try_from(data.as_slice()) {
+                vids.push(vid);
+            }
+        }
+        Value::Custom(SCRYPTO_TYPE_MID, data) => {
+            if let Ok(mid) = Mid::try_from(data.as_slice()) {
+                mids.push(mid);
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,4 +1,5 @@
 use scrypto::rust::collections::HashMap;
+use scrypto::rust::collections::HashSet;
 use scrypto::types::*;
 
 use crate::ledger::*;
@@ -13,6 +14,7 @@ pub struct InMemoryLedger {
     resource_defs: HashMap<Address, ResourceDef>,
     vaults: HashMap<Vid, Vault>,
     nfts: HashMap<(Address, u128), Nft>,
+    transactions: HashSet<H256>,
 }
 
 impl InMemoryLedger {
@@ -24,6 +26,7 @@ impl InMemoryLedger {
             resource_defs: HashMap::new(),
             vaults: HashMap::new(),
             nfts: HashMap::new(),
+            transactions: HashSet::new(),
         }
     }
 
@@ -88,4 +91,12 @@ impl Ledger for InMemoryLedger {
     fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft) {
         self.nfts.insert((resource_address, id), nft);
     }
+
+    fn has_transaction(&self, transaction_hash: H256) -> bool {
+        self.transactions.contains(&transaction_hash)
+    }
+
+    fn put_transaction(&mut self, transaction_hash: H256) {
+        self.transactions.insert(transaction_hash);
+    }
 }
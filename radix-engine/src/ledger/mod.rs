@@ -1,5 +1,7 @@
 mod memory;
+mod overlay;
 mod traits;
 
 pub use memory::InMemoryLedger;
-pub use traits::Ledger;
+pub use overlay::OverlayLedger;
+pub use traits::{Ledger, XRD_VAULT_ID};
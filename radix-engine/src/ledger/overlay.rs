@@ -0,0 +1,137 @@
+use scrypto::rust::collections::HashMap;
+use scrypto::rust::collections::HashSet;
+use scrypto::types::*;
+
+use crate::ledger::*;
+use crate::model::*;
+
+/// A ledger which buffers all updates on top of another ledger, until they're committed.
+///
+/// Reads see the buffered updates first, then fall back to the underlying ledger.
+pub struct OverlayLedger<'l, L: Ledger> {
+    parent: &'l mut L,
+    packages: HashMap<Address, Package>,
+    components: HashMap<Address, Component>,
+    lazy_maps: HashMap<Mid, LazyMap>,
+    resource_defs: HashMap<Address, ResourceDef>,
+    vaults: HashMap<Vid, Vault>,
+    nfts: HashMap<(Address, u128), Nft>,
+    transactions: HashSet<H256>,
+}
+
+impl<'l, L: Ledger> OverlayLedger<'l, L> {
+    pub fn new(parent: &'l mut L) -> Self {
+        Self {
+            parent,
+            packages: HashMap::new(),
+            components: HashMap::new(),
+            lazy_maps: HashMap::new(),
+            resource_defs: HashMap::new(),
+            vaults: HashMap::new(),
+            nfts: HashMap::new(),
+            transactions: HashSet::new(),
+        }
+    }
+
+    /// Writes the buffered updates into the underlying ledger.
+    pub fn commit(self) {
+        for (address, package) in self.packages {
+            self.parent.put_package(address, package);
+        }
+        for (address, component) in self.components {
+            self.parent.put_component(address, component);
+        }
+        for (mid, lazy_map) in self.lazy_maps {
+            self.parent.put_lazy_map(mid, lazy_map);
+        }
+        for (address, resource_def) in self.resource_defs {
+            self.parent.put_resource_def(address, resource_def);
+        }
+        for (vid, vault) in self.vaults {
+            self.parent.put_vault(vid, vault);
+        }
+        for ((resource_address, id), nft) in self.nfts {
+            self.parent.put_nft(resource_address, id, nft);
+        }
+        for transaction_hash in self.transactions {
+            self.parent.put_transaction(transaction_hash);
+        }
+    }
+}
+
+impl<'l, L: Ledger> Ledger for OverlayLedger<'l, L> {
+    fn get_resource_def(&self, address: Address) -> Option<ResourceDef> {
+        self.resource_defs
+            .get(&address)
+            .cloned()
+            .or_else(|| self.parent.get_resource_def(address))
+    }
+
+    fn put_resource_def(&mut self, address: Address, resource_def: ResourceDef) {
+        self.resource_defs.insert(address, resource_def);
+    }
+
+    fn get_package(&self, address: Address) -> Option<Package> {
+        self.packages
+            .get(&address)
+            .cloned()
+            .or_else(|| self.parent.get_package(address))
+    }
+
+    fn put_package(&mut self, address: Address, package: Package) {
+        self.packages.insert(address, package);
+    }
+
+    fn get_component(&self, address: Address) -> Option<Component> {
+        self.components
+            .get(&address)
+            .cloned()
+            .or_else(|| self.parent.get_component(address))
+    }
+
+    fn put_component(&mut self, address: Address, component: Component) {
+        self.components.insert(address, component);
+    }
+
+    fn get_lazy_map(&self, mid: Mid) -> Option<LazyMap> {
+        self.lazy_maps
+            .get(&mid)
+            .cloned()
+            .or_else(|| self.parent.get_lazy_map(mid))
+    }
+
+    fn put_lazy_map(&mut self, mid: Mid, lazy_map: LazyMap) {
+        self.lazy_maps.insert(mid, lazy_map);
+    }
+
+    fn get_vault(&self, vid: Vid) -> Option<Vault> {
+        self.vaults
+            .get(&vid)
+            .cloned()
+            .or_else(|| self.parent.get_vault(vid))
+    }
+
+    fn put_vault(&mut self, vid: Vid, vault: Vault) {
+        self.vaults.insert(vid, vault);
+    }
+
+    fn get_nft(&self, resource_address: Address, id: u128) -> Option<Nft> {
+        self.nfts
+            .get(&(resource_address, id))
+            .cloned()
+            .or_else(|| self.parent.get_nft(resource_address, id))
+    }
+
+    fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft) {
+        self.nfts.insert((resource_address, id), nft);
+    }
+
+    fn has_transaction(&self, transaction_hash: H256) -> bool {
+        self.transactions.contains(&transaction_hash)
+            || self.parent.has_transaction(transaction_hash)
+    }
+
+    fn put_transaction(&mut self, transaction_hash: H256) {
+        self.transactions.insert(transaction_hash);
+    }
+}
@@ -12,7 +12,8 @@ const XRD_NAME: &str = "Radix";
 const XRD_DESCRIPTION: &str = "The Radix Public Network's native token, used to pay the network's required transaction fees and to secure the network through staking to its validator nodes.";
 const XRD_URL: &str = "https://tokens.radixdlt.com";
 const XRD_MAX_SUPPLY: i128 = 24_000_000_000_000i128;
-const XRD_VAULT_ID: Vid = Vid(H256([0u8; 32]), 0);
+/// The vault which holds the XRD of the system component, including collected fees.
+pub const XRD_VAULT_ID: Vid = Vid(H256([0u8; 32]), 0);
 
 const SYSTEM_COMPONENT_NAME: &str = "System";
 
@@ -47,6 +48,10 @@ pub trait Ledger {
 
     fn put_nft(&mut self, resource_address: Address, id: u128, nft: Nft);
 
+    fn has_transaction(&self, transaction_hash: H256) -> bool;
+
+    fn put_transaction(&mut self, transaction_hash: H256);
+
     fn bootstrap(&mut self) {
         if self.get_package(SYSTEM_PACKAGE).is_none() {
             // System package
@@ -83,19 +88,18 @@ pub trait Ledger {
             );
 
             // Instantiate system component
-            self.put_vault(
-                XRD_VAULT_ID,
-                Vault::new(
-                    Bucket::new(
-                        RADIX_TOKEN,
-                        ResourceType::Fungible { divisibility: 18 },
-                        Supply::Fungible {
-                            amount: XRD_MAX_SUPPLY.into(),
-                        },
-                    ),
-                    SYSTEM_PACKAGE,
+            let mut xrd_vault = Vault::new(
+                Bucket::new(
+                    RADIX_TOKEN,
+                    ResourceType::Fungible { divisibility: 18 },
+                    Supply::Fungible {
+                        amount: XRD_MAX_SUPPLY.into(),
+                    },
                 ),
+                SYSTEM_PACKAGE,
             );
+            xrd_vault.set_owner(SYSTEM_COMPONENT);
+            self.put_vault(XRD_VAULT_ID, xrd_vault);
             self.put_component(
                 SYSTEM_COMPONENT,
                 Component::new(
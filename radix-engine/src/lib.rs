@@ -9,6 +9,8 @@ compile_error!("Feature `std` and `alloc` can't be enabled at the same time.");
 pub mod engine;
 /// Radix ledger abstraction.
 pub mod ledger;
+/// Transaction manifest language.
+pub mod manifest;
 /// Radix ledger data types.
 pub mod model;
 /// Radix Engine transaction model.
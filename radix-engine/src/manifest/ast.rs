@@ -0,0 +1,175 @@
+use sbor::rust::boxed::Box;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+
+/// Represents a parsed manifest instruction.
+///
+/// Operands are kept as values and only checked against their expected kinds by the compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    LockFee {
+        account: Value,
+        amount: Value,
+    },
+
+    TakeFromContext {
+        amount: Value,
+        resource_address: Value,
+        new_bucket: Value,
+    },
+
+    TakeAllFromContext {
+        resource_address: Value,
+        new_bucket: Value,
+    },
+
+    TakeNftsFromContext {
+        ids: Value,
+        resource_address: Value,
+        new_bucket: Value,
+    },
+
+    ReturnToContext {
+        bucket: Value,
+    },
+
+    BorrowFromContext {
+        amount: Value,
+        resource_address: Value,
+        new_bucket_ref: Value,
+    },
+
+    AssertContextContains {
+        amount: Value,
+        resource_address: Value,
+    },
+
+    AssertContextContainsNfts {
+        ids: Value,
+        resource_address: Value,
+    },
+
+    AssertContextDoesNotContain {
+        resource_address: Value,
+    },
+
+    CallFunction {
+        package_address: Value,
+        blueprint_name: Value,
+        function: Value,
+        args: Vec<Value>,
+    },
+
+    CallMethod {
+        component_address: Value,
+        method: Value,
+        args: Vec<Value>,
+    },
+
+    DropAllBucketRefs,
+
+    DepositAllBuckets {
+        account: Value,
+    },
+
+    End {
+        signers: Vec<Value>,
+    },
+}
+
+/// Represents the type of a manifest value, as used by collection literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Unit,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    String,
+
+    Struct,
+    Enum,
+
+    Option,
+    Box,
+    Array,
+    Tuple,
+    Result,
+
+    Vec,
+    TreeSet,
+    TreeMap,
+    HashSet,
+    HashMap,
+
+    Decimal,
+    BigDecimal,
+    Address,
+    H256,
+    Bucket,
+    BucketRef,
+    LazyMap,
+    Vault,
+}
+
+/// Represents a manifest value.
+///
+/// Scrypto types keep their textual representation, and buckets and bucket refs are
+/// referred to by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    String(String),
+
+    Struct(Fields),
+    Enum(u8, Fields),
+
+    Option(Box<Option<Value>>),
+    Box(Box<Value>),
+    Array(Type, Vec<Value>),
+    Tuple(Vec<Value>),
+    Result(Box<Result<Value, Value>>),
+
+    Vec(Type, Vec<Value>),
+    TreeSet(Type, Vec<Value>),
+    TreeMap(Type, Type, Vec<(Value, Value)>),
+    HashSet(Type, Vec<Value>),
+    HashMap(Type, Type, Vec<(Value, Value)>),
+
+    Decimal(String),
+    BigDecimal(String),
+    Address(String),
+    H256(String),
+    Bucket(String),
+    BucketRef(String),
+    LazyMap(String, u32),
+    Vault(String, u32),
+}
+
+/// Represents the fields of a struct or enum variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fields {
+    Named(Vec<Value>),
+
+    Unnamed(Vec<Value>),
+
+    Unit,
+}
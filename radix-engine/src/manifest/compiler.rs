@@ -0,0 +1,660 @@
+use sbor::any::{Fields, Value};
+use sbor::rust::boxed::Box;
+use sbor::type_id::*;
+use scrypto::buffer::*;
+use scrypto::rust::borrow::ToOwned;
+use scrypto::rust::collections::{BTreeSet, HashMap};
+use scrypto::rust::str::FromStr;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+use crate::engine::*;
+use crate::manifest::ast;
+use crate::manifest::lexer::{Lexer, LexerError};
+use crate::manifest::parser::{Parser, ParserError};
+use crate::transaction::*;
+
+/// Represents an error when compiling a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileError {
+    LexerError(LexerError),
+    ParserError(ParserError),
+    UnexpectedValue {
+        expected: ast::Type,
+        actual: ast::Value,
+    },
+    MismatchedElementType {
+        expected: ast::Type,
+        actual: ast::Type,
+    },
+    InvalidAddress(String),
+    InvalidDecimal(String),
+    InvalidBigDecimal(String),
+    InvalidH256(String),
+    BucketAlreadyDefined(String),
+    BucketRefAlreadyDefined(String),
+    UndefinedBucket(String),
+    UndefinedBucketRef(String),
+}
+
+/// Compiles a manifest into a transaction with the default header.
+///
+/// Named buckets and bucket refs are declared in the order they are first defined.
+pub fn compile(text: &str) -> Result<Transaction, CompileError> {
+    let tokens = Lexer::new(text)
+        .tokenize()
+        .map_err(CompileError::LexerError)?;
+    let instructions = Parser::new(tokens)
+        .parse_manifest()
+        .map_err(CompileError::ParserError)?;
+    Compiler::new().compile(&instructions)
+}
+
+/// Compiles a single manifest value, e.g. `Struct { 1u8, "a" }`.
+///
+/// Buckets and bucket refs can't be referred to by name outside a manifest.
+pub fn compile_value(text: &str) -> Result<Value, CompileError> {
+    let tokens = Lexer::new(text)
+        .tokenize()
+        .map_err(CompileError::LexerError)?;
+    let mut parser = Parser::new(tokens);
+    let value = parser.parse_value().map_err(CompileError::ParserError)?;
+    parser.check_eof().map_err(CompileError::ParserError)?;
+    Compiler::new().compile_value(&value)
+}
+
+/// Turns parsed manifest instructions into a transaction.
+pub struct Compiler {
+    /// The ID allocator for calculating reserved bucket and bucket ref ids.
+    allocator: IdAllocator,
+    /// Named buckets defined so far.
+    buckets: HashMap<String, Bid>,
+    /// Named bucket refs defined so far.
+    bucket_refs: HashMap<String, Rid>,
+    /// Bucket and bucket ref declarations.
+    reservations: Vec<Instruction>,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            allocator: IdAllocator::new(),
+            buckets: HashMap::new(),
+            bucket_refs: HashMap::new(),
+            reservations: Vec::new(),
+        }
+    }
+
+    /// Compiles the instructions of a manifest.
+    ///
+    /// Declarations are placed before all other instructions, including the fee lock, the
+    /// way the transaction builder does.
+    pub fn compile(
+        mut self,
+        instructions: &[ast::Instruction],
+    ) -> Result<Transaction, CompileError> {
+        let mut compiled = Vec::new();
+        for inst in instructions {
+            compiled.push(self.compile_instruction(inst)?);
+        }
+
+        let mut reservations = self.reservations;
+        reservations.extend(compiled);
+
+        Ok(Transaction {
+            header: TransactionHeader::default(),
+            instructions: reservations,
+        })
+    }
+
+    fn compile_instruction(
+        &mut self,
+        inst: &ast::Instruction,
+    ) -> Result<Instruction, CompileError> {
+        let compiled = match inst {
+            ast::Instruction::LockFee { account, amount } => Instruction::LockFee {
+                account: Self::compile_address(account)?,
+                amount: Self::compile_decimal(amount)?,
+            },
+            ast::Instruction::TakeFromContext {
+                amount,
+                resource_address,
+                new_bucket,
+            } => Instruction::TakeFromContext {
+                amount: Self::compile_decimal(amount)?,
+                resource_address: Self::compile_address(resource_address)?,
+                to: self.declare_bucket(new_bucket)?,
+            },
+            ast::Instruction::TakeAllFromContext {
+                resource_address,
+                new_bucket,
+            } => Instruction::TakeAllFromContext {
+                resource_address: Self::compile_address(resource_address)?,
+                to: self.declare_bucket(new_bucket)?,
+            },
+            ast::Instruction::TakeNftsFromContext {
+                ids,
+                resource_address,
+                new_bucket,
+            } => Instruction::TakeNftsFromContext {
+                ids: Self::compile_nft_ids(ids)?,
+                resource_address: Self::compile_address(resource_address)?,
+                to: self.declare_bucket(new_bucket)?,
+            },
+            ast::Instruction::ReturnToContext { bucket } => Instruction::ReturnToContext {
+                bid: self.compile_bucket(bucket)?,
+            },
+            ast::Instruction::BorrowFromContext {
+                amount,
+                resource_address,
+                new_bucket_ref,
+            } => Instruction::BorrowFromContext {
+                amount: Self::compile_decimal(amount)?,
+                resource_address: Self::compile_address(resource_address)?,
+                to: self.declare_bucket_ref(new_bucket_ref)?,
+            },
+            ast::Instruction::AssertContextContains {
+                amount,
+                resource_address,
+            } => Instruction::AssertContextContains {
+                amount: Self::compile_decimal(amount)?,
+                resource_address: Self::compile_address(resource_address)?,
+            },
+            ast::Instruction::AssertContextContainsNfts {
+                ids,
+                resource_address,
+            } => Instruction::AssertContextContainsNfts {
+                ids: Self::compile_nft_ids(ids)?,
+                resource_address: Self::compile_address(resource_address)?,
+            },
+            ast::Instruction::AssertContextDoesNotContain { resource_address } => {
+                Instruction::AssertContextDoesNotContain {
+                    resource_address: Self::compile_address(resource_address)?,
+                }
+            }
+            ast::Instruction::CallFunction {
+                package_address,
+                blueprint_name,
+                function,
+                args,
+            } => Instruction::CallFunction {
+                package_address: Self::compile_address(package_address)?,
+                blueprint_name: Self::compile_string(blueprint_name)?,
+                function: Self::compile_string(function)?,
+                args: self.compile_args(args)?,
+            },
+            ast::Instruction::CallMethod {
+                component_address,
+                method,
+                args,
+            } => Instruction::CallMethod {
+                component_address: Self::compile_address(component_address)?,
+                method: Self::compile_string(method)?,
+                args: self.compile_args(args)?,
+            },
+            ast::Instruction::DropAllBucketRefs => Instruction::DropAllBucketRefs,
+            ast::Instruction::DepositAllBuckets { account } => Instruction::DepositAllBuckets {
+                account: Self::compile_address(account)?,
+            },
+            ast::Instruction::End { signers } => Instruction::End {
+                signers: signers
+                    .iter()
+                    .map(Self::compile_address)
+                    .collect::<Result<Vec<Address>, CompileError>>()?,
+            },
+        };
+        Ok(compiled)
+    }
+
+    fn declare_bucket(&mut self, value: &ast::Value) -> Result<Bid, CompileError> {
+        let name = match value {
+            ast::Value::Bucket(name) => name,
+            _ => return Err(Self::unexpected(ast::Type::Bucket, value)),
+        };
+        if self.buckets.contains_key(name) {
+            return Err(CompileError::BucketAlreadyDefined(name.clone()));
+        }
+
+        let bid = self.allocator.new_bid();
+        self.reservations.push(Instruction::DeclareTempBucket);
+        self.buckets.insert(name.clone(), bid);
+        Ok(bid)
+    }
+
+    fn declare_bucket_ref(&mut self, value: &ast::Value) -> Result<Rid, CompileError> {
+        let name = match value {
+            ast::Value::BucketRef(name) => name,
+            _ => return Err(Self::unexpected(ast::Type::BucketRef, value)),
+        };
+        if self.bucket_refs.contains_key(name) {
+            return Err(CompileError::BucketRefAlreadyDefined(name.clone()));
+        }
+
+        let rid = self.allocator.new_rid();
+        self.reservations.push(Instruction::DeclareTempBucketRef);
+        self.bucket_refs.insert(name.clone(), rid);
+        Ok(rid)
+    }
+
+    fn compile_bucket(&self, value: &ast::Value) -> Result<Bid, CompileError> {
+        match value {
+            ast::Value::Bucket(name) => self
+                .buckets
+                .get(name)
+                .copied()
+                .ok_or_else(|| CompileError::UndefinedBucket(name.clone())),
+            _ => Err(Self::unexpected(ast::Type::Bucket, value)),
+        }
+    }
+
+    fn compile_args(&self, args: &[ast::Value]) -> Result<Vec<SmartValue>, CompileError> {
+        let mut result = Vec::new();
+        for arg in args {
+            result.push(SmartValue::from_value(&self.compile_value(arg)?));
+        }
+        Ok(result)
+    }
+
+    fn compile_address(value: &ast::Value) -> Result<Address, CompileError> {
+        match value {
+            ast::Value::Address(s) => {
+                Address::from_str(s).map_err(|_| CompileError::InvalidAddress(s.clone()))
+            }
+            _ => Err(Self::unexpected(ast::Type::Address, value)),
+        }
+    }
+
+    fn compile_decimal(value: &ast::Value) -> Result<Decimal, CompileError> {
+        match value {
+            ast::Value::Decimal(s) => {
+                Decimal::from_str(s).map_err(|_| CompileError::InvalidDecimal(s.clone()))
+            }
+            _ => Err(Self::unexpected(ast::Type::Decimal, value)),
+        }
+    }
+
+    fn compile_nft_ids(value: &ast::Value) -> Result<BTreeSet<u128>, CompileError> {
+        match value {
+            ast::Value::TreeSet(ast::Type::U128, elements) => elements
+                .iter()
+                .map(|e| match e {
+                    ast::Value::U128(id) => Ok(*id),
+                    _ => Err(CompileError::MismatchedElementType {
+                        expected: ast::Type::U128,
+                        actual: type_of(e),
+                    }),
+                })
+                .collect(),
+            _ => Err(Self::unexpected(ast::Type::TreeSet, value)),
+        }
+    }
+
+    fn compile_string(value: &ast::Value) -> Result<String, CompileError> {
+        match value {
+            ast::Value::String(s) => Ok(s.clone()),
+            _ => Err(Self::unexpected(ast::Type::String, value)),
+        }
+    }
+
+    fn compile_h256(s: &str) -> Result<H256, CompileError> {
+        H256::from_str(s).map_err(|_| CompileError::InvalidH256(s.to_owned()))
+    }
+
+    /// Converts a manifest value into a SBOR value, resolving bucket and bucket ref names.
+    pub fn compile_value(&self, value: &ast::Value) -> Result<Value, CompileError> {
+        let compiled = match value {
+            ast::Value::Unit => Value::Unit,
+            ast::Value::Bool(v) => Value::Bool(*v),
+            ast::Value::I8(v) => Value::I8(*v),
+            ast::Value::I16(v) => Value::I16(*v),
+            ast::Value::I32(v) => Value::I32(*v),
+            ast::Value::I64(v) => Value::I64(*v),
+            ast::Value::I128(v) => Value::I128(*v),
+            ast::Value::U8(v) => Value::U8(*v),
+            ast::Value::U16(v) => Value::U16(*v),
+            ast::Value::U32(v) => Value::U32(*v),
+            ast::Value::U64(v) => Value::U64(*v),
+            ast::Value::U128(v) => Value::U128(*v),
+            ast::Value::String(v) => Value::String(v.clone()),
+            ast::Value::Struct(fields) => Value::Struct(self.compile_fields(fields)?),
+            ast::Value::Enum(index, fields) => Value::Enum(*index, self.compile_fields(fields)?),
+            ast::Value::Option(v) => Value::Option(Box::new(match v.as_ref() {
+                Some(x) => Some(self.compile_value(x)?),
+                None => None,
+            })),
+            ast::Value::Box(v) => Value::Box(Box::new(self.compile_value(v)?)),
+            ast::Value::Array(ty, elements) => {
+                Value::Array(type_id(*ty), self.compile_elements(*ty, elements)?)
+            }
+            ast::Value::Tuple(elements) => Value::Tuple(self.compile_values(elements)?),
+            ast::Value::Result(v) => Value::Result(Box::new(match v.as_ref() {
+                Ok(x) => Ok(self.compile_value(x)?),
+                Err(x) => Err(self.compile_value(x)?),
+            })),
+            ast::Value::Vec(ty, elements) => {
+                Value::Vec(type_id(*ty), self.compile_elements(*ty, elements)?)
+            }
+            ast::Value::TreeSet(ty, elements) => {
+                Value::TreeSet(type_id(*ty), self.compile_elements(*ty, elements)?)
+            }
+            ast::Value::HashSet(ty, elements) => {
+                Value::HashSet(type_id(*ty), self.compile_elements(*ty, elements)?)
+            }
+            ast::Value::TreeMap(key, value, entries) => Value::TreeMap(
+                type_id(*key),
+                type_id(*value),
+                self.compile_entries(*key, *value, entries)?,
+            ),
+            ast::Value::HashMap(key, value, entries) => Value::HashMap(
+                type_id(*key),
+                type_id(*value),
+                self.compile_entries(*key, *value, entries)?,
+            ),
+            ast::Value::Decimal(_) => {
+                Value::Custom(SCRYPTO_TYPE_DECIMAL, Self::compile_decimal(value)?.to_vec())
+            }
+            ast::Value::BigDecimal(s) => Value::Custom(
+                SCRYPTO_TYPE_BIG_DECIMAL,
+                BigDecimal::from_str(s)
+                    .map_err(|_| CompileError::InvalidBigDecimal(s.clone()))?
+                    .to_vec(),
+            ),
+            ast::Value::Address(_) => {
+                Value::Custom(SCRYPTO_TYPE_ADDRESS, Self::compile_address(value)?.to_vec())
+            }
+            ast::Value::H256(s) => {
+                Value::Custom(SCRYPTO_TYPE_H256, Self::compile_h256(s)?.to_vec())
+            }
+            ast::Value::Bucket(_) => {
+                Value::Custom(SCRYPTO_TYPE_BID, self.compile_bucket(value)?.to_vec())
+            }
+            ast::Value::BucketRef(name) => Value::Custom(
+                SCRYPTO_TYPE_RID,
+                self.bucket_refs
+                    .get(name)
+                    .ok_or_else(|| CompileError::UndefinedBucketRef(name.clone()))?
+                    .to_vec(),
+            ),
+            ast::Value::LazyMap(hash, index) => Value::Custom(
+                SCRYPTO_TYPE_MID,
+                Mid(Self::compile_h256(hash)?, *index).to_vec(),
+            ),
+            ast::Value::Vault(hash, index) => Value::Custom(
+                SCRYPTO_TYPE_VID,
+                Vid(Self::compile_h256(hash)?, *index).to_vec(),
+            ),
+        };
+        Ok(compiled)
+    }
+
+    fn compile_fields(&self, fields: &ast::Fields) -> Result<Fields, CompileError> {
+        match fields {
+            ast::Fields::Named(named) => Ok(Fields::Named(self.compile_values(named)?)),
+            ast::Fields::Unnamed(unnamed) => Ok(Fields::Unnamed(self.compile_values(unnamed)?)),
+            ast::Fields::Unit => Ok(Fields::Unit),
+        }
+    }
+
+    fn compile_values(&self, values: &[ast::Value]) -> Result<Vec<Value>, CompileError> {
+        values.iter().map(|v| self.compile_value(v)).collect()
+    }
+
+    fn compile_elements(
+        &self,
+        ty: ast::Type,
+        elements: &[ast::Value],
+    ) -> Result<Vec<Value>, CompileError> {
+        elements
+            .iter()
+            .map(|e| {
+                Self::check_element_type(ty, e)?;
+                self.compile_value(e)
+            })
+            .collect()
+    }
+
+    fn compile_entries(
+        &self,
+        key_ty: ast::Type,
+        value_ty: ast::Type,
+        entries: &[(ast::Value, ast::Value)],
+    ) -> Result<Vec<(Value, Value)>, CompileError> {
+        entries
+            .iter()
+            .map(|(k, v)| {
+                Self::check_element_type(key_ty, k)?;
+                Self::check_element_type(value_ty, v)?;
+                Ok((self.compile_value(k)?, self.compile_value(v)?))
+            })
+            .collect()
+    }
+
+    /// Elements of a collection are encoded without their type, so they must all match
+    /// the declared element type.
+    fn check_element_type(expected: ast::Type, element: &ast::Value) -> Result<(), CompileError> {
+        let actual = type_of(element);
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(CompileError::MismatchedElementType { expected, actual })
+        }
+    }
+
+    fn unexpected(expected: ast::Type, actual: &ast::Value) -> CompileError {
+        CompileError::UnexpectedValue {
+            expected,
+            actual: actual.clone(),
+        }
+    }
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the SBOR type id of a manifest type.
+pub fn type_id(ty: ast::Type) -> u8 {
+    match ty {
+        ast::Type::Unit => TYPE_UNIT,
+        ast::Type::Bool => TYPE_BOOL,
+        ast::Type::I8 => TYPE_I8,
+        ast::Type::I16 => TYPE_I16,
+        ast::Type::I32 => TYPE_I32,
+        ast::Type::I64 => TYPE_I64,
+        ast::Type::I128 => TYPE_I128,
+        ast::Type::U8 => TYPE_U8,
+        ast::Type::U16 => TYPE_U16,
+        ast::Type::U32 => TYPE_U32,
+        ast::Type::U64 => TYPE_U64,
+        ast::Type::U128 => TYPE_U128,
+        ast::Type::String => TYPE_STRING,
+        ast::Type::Struct => TYPE_STRUCT,
+        ast::Type::Enum => TYPE_ENUM,
+        ast::Type::Option => TYPE_OPTION,
+        ast::Type::Box => TYPE_BOX,
+        ast::Type::Array => TYPE_ARRAY,
+        ast::Type::Tuple => TYPE_TUPLE,
+        ast::Type::Result => TYPE_RESULT,
+        ast::Type::Vec => TYPE_VEC,
+        ast::Type::TreeSet => TYPE_TREE_SET,
+        ast::Type::TreeMap => TYPE_TREE_MAP,
+        ast::Type::HashSet => TYPE_HASH_SET,
+        ast::Type::HashMap => TYPE_HASH_MAP,
+        ast::Type::Decimal => SCRYPTO_TYPE_DECIMAL,
+        ast::Type::BigDecimal => SCRYPTO_TYPE_BIG_DECIMAL,
+        ast::Type::Address => SCRYPTO_TYPE_ADDRESS,
+        ast::Type::H256 => SCRYPTO_TYPE_H256,
+        ast::Type::Bucket => SCRYPTO_TYPE_BID,
+        ast::Type::BucketRef => SCRYPTO_TYPE_RID,
+        ast::Type::LazyMap => SCRYPTO_TYPE_MID,
+        ast::Type::Vault => SCRYPTO_TYPE_VID,
+    }
+}
+
+/// Returns the type of a manifest value.
+pub fn type_of(value: &ast::Value) -> ast::Type {
+    match value {
+        ast::Value::Unit => ast::Type::Unit,
+        ast::Value::Bool(_) => ast::Type::Bool,
+        ast::Value::I8(_) => ast::Type::I8,
+        ast::Value::I16(_) => ast::Type::I16,
+        ast::Value::I32(_) => ast::Type::I32,
+        ast::Value::I64(_) => ast::Type::I64,
+        ast::Value::I128(_) => ast::Type::I128,
+        ast::Value::U8(_) => ast::Type::U8,
+        ast::Value::U16(_) => ast::Type::U16,
+        ast::Value::U32(_) => ast::Type::U32,
+        ast::Value::U64(_) => ast::Type::U64,
+        ast::Value::U128(_) => ast::Type::U128,
+        ast::Value::String(_) => ast::Type::String,
+        ast::Value::Struct(_) => ast::Type::Struct,
+        ast::Value::Enum(_, _) => ast::Type::Enum,
+        ast::Value::Option(_) => ast::Type::Option,
+        ast::Value::Box(_) => ast::Type::Box,
+        ast::Value::Array(_, _) => ast::Type::Array,
+        ast::Value::Tuple(_) => ast::Type::Tuple,
+        ast::Value::Result(_) => ast::Type::Result,
+        ast::Value::Vec(_, _) => ast::Type::Vec,
+        ast::Value::TreeSet(_, _) => ast::Type::TreeSet,
+        ast::Value::TreeMap(_, _, _) => ast::Type::TreeMap,
+        ast::Value::HashSet(_, _) => ast::Type::HashSet,
+        ast::Value::HashMap(_, _, _) => ast::Type::HashMap,
+        ast::Value::Decimal(_) => ast::Type::Decimal,
+        ast::Value::BigDecimal(_) => ast::Type::BigDecimal,
+        ast::Value::Address(_) => ast::Type::Address,
+        ast::Value::H256(_) => ast::Type::H256,
+        ast::Value::Bucket(_) => ast::Type::Bucket,
+        ast::Value::BucketRef(_) => ast::Type::BucketRef,
+        ast::Value::LazyMap(_, _) => ast::Type::LazyMap,
+        ast::Value::Vault(_, _) => ast::Type::Vault,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scrypto::rust::string::ToString;
+
+    const ACCOUNT: &str = "02d43f479e9b2beb9df98bc3888344fc25eda181e8f710ce1bf1de";
+
+    #[test]
+    fn test_compile_buckets() {
+        let transaction = compile(&format!(
+            r#"
+                CALL_METHOD Address("{0}") "withdraw" Decimal("1") Address("{1}");
+                TAKE_FROM_CONTEXT Decimal("1") Address("{1}") Bucket("xrd");
+                BORROW_FROM_CONTEXT Decimal("1") Address("{1}") BucketRef("proof");
+                CALL_METHOD Address("{0}") "deposit" Bucket("xrd") BucketRef("proof");
+                END;
+            "#,
+            ACCOUNT, RADIX_TOKEN
+        ))
+        .unwrap();
+
+        let instructions = transaction.instructions;
+        assert_eq!(instructions.len(), 7);
+        assert!(matches!(instructions[0], Instruction::DeclareTempBucket));
+        assert!(matches!(instructions[1], Instruction::DeclareTempBucketRef));
+        assert!(matches!(
+            instructions[3],
+            Instruction::TakeFromContext { to: Bid(0), .. }
+        ));
+        assert!(matches!(
+            instructions[4],
+            Instruction::BorrowFromContext { to: Rid(1), .. }
+        ));
+        match &instructions[5] {
+            Instruction::CallMethod { args, .. } => {
+                assert_eq!(args[0].encoded, scrypto_encode(&Bid(0)));
+                assert_eq!(args[1].encoded, scrypto_encode(&Rid(1)));
+            }
+            _ => panic!("Unexpected instruction"),
+        }
+    }
+
+    #[test]
+    fn test_compile_fee_lock() {
+        let transaction = compile(&format!(
+            r#"
+                LOCK_FEE Address("{0}") Decimal("10");
+                CALL_METHOD Address("{0}") "withdraw" Decimal("1") Address("{1}");
+                TAKE_FROM_CONTEXT Decimal("1") Address("{1}") Bucket("xrd");
+                CALL_METHOD Address("{0}") "deposit" Bucket("xrd");
+                END;
+            "#,
+            ACCOUNT, RADIX_TOKEN
+        ))
+        .unwrap();
+
+        // the bucket is reserved before the fee is locked
+        let instructions = transaction.instructions;
+        assert!(matches!(instructions[0], Instruction::DeclareTempBucket));
+        assert!(matches!(instructions[1], Instruction::LockFee { .. }));
+        assert!(matches!(
+            instructions[3],
+            Instruction::TakeFromContext { to: Bid(0), .. }
+        ));
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert_eq!(
+            compile("CALL_METHOD Address(\"00\") \"a\";").err(),
+            Some(CompileError::InvalidAddress("00".to_string()))
+        );
+        assert_eq!(
+            compile(&format!(
+                "DEPOSIT_ALL_BUCKETS Address(\"{}\") Decimal(\"1\");",
+                ACCOUNT
+            ))
+            .err(),
+            Some(CompileError::ParserError(ParserError::UnexpectedToken(
+                crate::manifest::lexer::Token {
+                    kind: crate::manifest::lexer::TokenKind::Ident("Decimal".to_string()),
+                    position: crate::manifest::lexer::Position {
+                        line: 1,
+                        column: 87
+                    },
+                }
+            )))
+        );
+        assert_eq!(
+            compile(&format!(
+                "CALL_METHOD Address(\"{}\") \"deposit\" Bucket(\"xrd\");",
+                ACCOUNT
+            ))
+            .err(),
+            Some(CompileError::UndefinedBucket("xrd".to_string()))
+        );
+        assert_eq!(
+            compile(&format!(
+                "TAKE_FROM_CONTEXT Decimal(\"1\") Address(\"{0}\") Bucket(\"a\"); \
+                 TAKE_FROM_CONTEXT Decimal(\"1\") Address(\"{0}\") Bucket(\"a\");",
+                RADIX_TOKEN
+            ))
+            .err(),
+            Some(CompileError::BucketAlreadyDefined("a".to_string()))
+        );
+        assert_eq!(
+            compile(&format!(
+                "CALL_METHOD Address(\"{}\") \"a\" Vec<u8>(1u8, 2u16);",
+                ACCOUNT
+            ))
+            .err(),
+            Some(CompileError::MismatchedElementType {
+                expected: ast::Type::U8,
+                actual: ast::Type::U16
+            })
+        );
+        assert_eq!(
+            compile("LOCK_FEE Decimal(\"1\") Decimal(\"1\");").err(),
+            Some(CompileError::UnexpectedValue {
+                expected: ast::Type::Address,
+                actual: ast::Value::Decimal("1".to_string())
+            })
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use sbor::any::{decode_any, Fields, Value};
+use sbor::type_id::*;
+use sbor::DecodeError;
+use scrypto::buffer::*;
+use scrypto::rust::borrow::Borrow;
+use scrypto::rust::collections::{BTreeSet, HashMap};
+use scrypto::rust::convert::TryFrom;
+use scrypto::rust::format;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+use crate::engine::*;
+use crate::transaction::*;
+
+/// Represents an error when decompiling a transaction.
+#[derive(Debug, Clone)]
+pub enum DecompileError {
+    DecodeError(DecodeError),
+    UnknownBucket(Bid),
+    UnknownBucketRef(Rid),
+    UnsupportedPartialTransaction,
+}
+
+/// Turns a transaction back into a manifest, with one instruction per line.
+///
+/// Declared buckets and bucket refs are named `bucket1`, `bucket_ref1` and so on. The
+/// declarations themselves are implied by the instructions that fill them, and the header
+/// is not part of the manifest. Partial transactions can't be decompiled, as signatures
+/// can't be expressed in a manifest.
+pub fn decompile(transaction: &Transaction) -> Result<String, DecompileError> {
+    let mut decompiler = Decompiler::new();
+    let mut buf = String::new();
+    for inst in &transaction.instructions {
+        if let Some(line) = decompiler.decompile_instruction(inst)? {
+            buf.push_str(&line);
+            buf.push('\n');
+        }
+    }
+    Ok(buf)
+}
+
+struct Decompiler {
+    /// The ID allocator for replaying bucket and bucket ref reservations.
+    allocator: IdAllocator,
+    buckets: HashMap<Bid, String>,
+    bucket_refs: HashMap<Rid, String>,
+}
+
+impl Decompiler {
+    fn new() -> Self {
+        Self {
+            allocator: IdAllocator::new(),
+            buckets: HashMap::new(),
+            bucket_refs: HashMap::new(),
+        }
+    }
+
+    fn decompile_instruction(
+        &mut self,
+        inst: &Instruction,
+    ) -> Result<Option<String>, DecompileError> {
+        let line = match inst {
+            Instruction::LockFee { account, amount } => format!(
+                "LOCK_FEE {} {};",
+                format_address(*account),
+                format_decimal(*amount)
+            ),
+            Instruction::DeclareTempBucket => {
+                let bid = self.allocator.new_bid();
+                let name = format!("bucket{}", self.buckets.len() + 1);
+                self.buckets.insert(bid, name);
+                return Ok(None);
+            }
+            Instruction::DeclareTempBucketRef => {
+                let rid = self.allocator.new_rid();
+                let name = format!("bucket_ref{}", self.bucket_refs.len() + 1);
+                self.bucket_refs.insert(rid, name);
+                return Ok(None);
+            }
+            Instruction::TakeFromContext {
+                amount,
+                resource_address,
+                to,
+            } => format!(
+                "TAKE_FROM_CONTEXT {} {} {};",
+                format_decimal(*amount),
+                format_address(*resource_address),
+                self.format_bucket(*to)?
+            ),
+            Instruction::TakeAllFromContext {
+                resource_address,
+                to,
+            } => format!(
+                "TAKE_ALL_FROM_CONTEXT {} {};",
+                format_address(*resource_address),
+                self.format_bucket(*to)?
+            ),
+            Instruction::TakeNftsFromContext {
+                ids,
+                resource_address,
+                to,
+            } => format!(
+                "TAKE_NFTS_FROM_CONTEXT {} {} {};",
+                format_nft_ids(ids),
+                format_address(*resource_address),
+                self.format_bucket(*to)?
+            ),
+            Instruction::ReturnToContext { bid } => {
+                format!("RETURN_TO_CONTEXT {};", self.format_bucket(*bid)?)
+            }
+            Instruction::BorrowFromContext {
+                amount,
+                resource_address,
+                to,
+            } => format!(
+                "BORROW_FROM_CONTEXT {} {} {};",
+                format_decimal(*amount),
+                format_address(*resource_address),
+                self.format_bucket_ref(*to)?
+            ),
+            Instruction::AssertContextContains {
+                amount,
+                resource_address,
+            } => format!(
+                "ASSERT_CONTEXT_CONTAINS {} {};",
+                format_decimal(*amount),
+                format_address(*resource_address)
+            ),
+            Instruction::AssertContextContainsNfts {
+                ids,
+                resource_address,
+            } => format!(
+                "ASSERT_CONTEXT_CONTAINS_NFTS {} {};",
+                format_nft_ids(ids),
+                format_address(*resource_address)
+            ),
+            Instruction::AssertContextDoesNotContain { resource_address } => format!(
+                "ASSERT_CONTEXT_DOES_NOT_CONTAIN {};",
+                format_address(*resource_address)
+            ),
+            Instruction::CallFunction {
+                package_address,
+                blueprint_name,
+                function,
+                args,
+            } => format!(
+                "CALL_FUNCTION {} {} {}{};",
+                format_address(*package_address),
+                format_string(blueprint_name),
+                format_string(function),
+                self.format_args(args)?
+            ),
+            Instruction::CallMethod {
+                component_address,
+                method,
+                args,
+            } => format!(
+                "CALL_METHOD {} {}{};",
+                format_address(*component_address),
+                format_string(method),
+                self.format_args(args)?
+            ),
+            Instruction::DropAllBucketRefs => String::from("DROP_ALL_BUCKET_REFS;"),
+            Instruction::DepositAllBuckets { account } => {
+                format!("DEPOSIT_ALL_BUCKETS {};", format_address(*account))
+            }
+            Instruction::PartialTransaction { .. } => {
+                return Err(DecompileError::UnsupportedPartialTransaction);
+            }
+            Instruction::End { signers } => {
+                let mut buf = String::from("END");
+                for signer in signers {
+                    buf.push(' ');
+                    buf.push_str(&format_address(*signer));
+                }
+                buf.push(';');
+                buf
+            }
+        };
+        Ok(Some(line))
+    }
+
+    fn format_bucket(&self, bid: Bid) -> Result<String, DecompileError> {
+        self.buckets
+            .get(&bid)
+            .map(|name| format!("Bucket({})", format_string(name)))
+            .ok_or(DecompileError::UnknownBucket(bid))
+    }
+
+    fn format_bucket_ref(&self, rid: Rid) -> Result<String, DecompileError> {
+        self.bucket_refs
+            .get(&rid)
+            .map(|name| format!("BucketRef({})", format_string(name)))
+            .ok_or(DecompileError::UnknownBucketRef(rid))
+    }
+
+    fn format_args(&self, args: &[SmartValue]) -> Result<String, DecompileError> {
+        let mut buf = String::new();
+        for arg in args {
+            let value = decode_any(&arg.encoded).map_err(DecompileError::DecodeError)?;
+            buf.push(' ');
+            buf.push_str(&self.format_value(&value)?);
+        }
+        Ok(buf)
+    }
+
+    fn format_value(&self, value: &Value) -> Result<String, DecompileError> {
+        let formatted = match value {
+            Value::Unit => String::from("()"),
+            Value::Bool(v) => format!("{}", v),
+            Value::I8(v) => format!("{}i8", v),
+            Value::I16(v) => format!("{}i16", v),
+            Value::I32(v) => format!("{}i32", v),
+            Value::I64(v) => format!("{}i64", v),
+            Value::I128(v) => format!("{}i128", v),
+            Value::U8(v) => format!("{}u8", v),
+            Value::U16(v) => format!("{}u16", v),
+            Value::U32(v) => format!("{}u32", v),
+            Value::U64(v) => format!("{}u64", v),
+            Value::U128(v) => format!("{}u128", v),
+            Value::String(v) => format_string(v),
+            Value::Struct(fields) => format!("Struct{}", self.format_fields(fields)?),
+            Value::Enum(index, fields) => format!("Enum::{}{}", index, self.format_fields(fields)?),
+            Value::Option(v) => match v.borrow() {
+                Some(x) => format!("Some({})", self.format_value(x)?),
+                None => String::from("None"),
+            },
+            Value::Box(v) => format!("Box({})", self.format_value(v)?),
+            Value::Array(ty, elements) => {
+                format!(
+                    "Array<{}>({})",
+                    type_name(*ty)?,
+                    self.format_values(elements)?
+                )
+            }
+            Value::Tuple(elements) => format!("Tuple({})", self.format_values(elements)?),
+            Value::Result(v) => match v.borrow() {
+                Ok(x) => format!("Ok({})", self.format_value(x)?),
+                Err(x) => format!("Err({})", self.format_value(x)?),
+            },
+            Value::Vec(ty, elements) => {
+                format!(
+                    "Vec<{}>({})",
+                    type_name(*ty)?,
+                    self.format_values(elements)?
+                )
+            }
+            Value::TreeSet(ty, elements) => {
+                format!(
+                    "TreeSet<{}>({})",
+                    type_name(*ty)?,
+                    self.format_values(elements)?
+                )
+            }
+            Value::HashSet(ty, elements) => {
+                format!(
+                    "HashSet<{}>({})",
+                    type_name(*ty)?,
+                    self.format_values(elements)?
+                )
+            }
+            Value::TreeMap(key, value, entries) => format!(
+                "TreeMap<{}, {}>({})",
+                type_name(*key)?,
+                type_name(*value)?,
+                self.format_entries(entries)?
+            ),
+            Value::HashMap(key, value, entries) => format!(
+                "HashMap<{}, {}>({})",
+                type_name(*key)?,
+                type_name(*value)?,
+                self.format_entries(entries)?
+            ),
+            Value::Custom(ty, data) => self.format_custom(*ty, data)?,
+        };
+        Ok(formatted)
+    }
+
+    fn format_custom(&self, ty: u8, data: &[u8]) -> Result<String, DecompileError> {
+        let invalid = || DecompileError::DecodeError(DecodeError::InvalidCustomData(ty));
+        let formatted = match ty {
+            SCRYPTO_TYPE_DECIMAL => format_decimal(Decimal::try_from(data).map_err(|_| invalid())?),
+            SCRYPTO_TYPE_BIG_DECIMAL => format!(
+                "BigDecimal(\"{}\")",
+                BigDecimal::try_from(data).map_err(|_| invalid())?
+            ),
+            SCRYPTO_TYPE_ADDRESS => format_address(Address::try_from(data).map_err(|_| invalid())?),
+            SCRYPTO_TYPE_H256 => {
+                format!("H256(\"{}\")", H256::try_from(data).map_err(|_| invalid())?)
+            }
+            SCRYPTO_TYPE_BID => self.format_bucket(Bid::try_from(data).map_err(|_| invalid())?)?,
+            SCRYPTO_TYPE_RID => {
+                self.format_bucket_ref(Rid::try_from(data).map_err(|_| invalid())?)?
+            }
+            SCRYPTO_TYPE_MID => {
+                let mid = Mid::try_from(data).map_err(|_| invalid())?;
+                format!("LazyMap(\"{}\", {}u32)", mid.0, mid.1)
+            }
+            SCRYPTO_TYPE_VID => {
+                let vid = Vid::try_from(data).map_err(|_| invalid())?;
+                format!("Vault(\"{}\", {}u32)", vid.0, vid.1)
+            }
+            _ => {
+                return Err(DecompileError::DecodeError(DecodeError::InvalidType {
+                    expected: None,
+                    actual: ty,
+                }))
+            }
+        };
+        Ok(formatted)
+    }
+
+    fn format_fields(&self, fields: &Fields) -> Result<String, DecompileError> {
+        match fields {
+            Fields::Named(named) => Ok(format!(" {{ {} }}", self.format_values(named)?)),
+            Fields::Unnamed(unnamed) => Ok(format!(" ( {} )", self.format_values(unnamed)?)),
+            Fields::Unit => Ok(String::new()),
+        }
+    }
+
+    fn format_values(&self, values: &[Value]) -> Result<String, DecompileError> {
+        let formatted = values
+            .iter()
+            .map(|v| self.format_value(v))
+            .collect::<Result<Vec<String>, DecompileError>>()?;
+        Ok(formatted.join(", "))
+    }
+
+    fn format_entries(&self, entries: &[(Value, Value)]) -> Result<String, DecompileError> {
+        let formatted = entries
+            .iter()
+            .map(|(k, v)| {
+                Ok(format!(
+                    "{} => {}",
+                    self.format_value(k)?,
+                    self.format_value(v)?
+                ))
+            })
+            .collect::<Result<Vec<String>, DecompileError>>()?;
+        Ok(formatted.join(", "))
+    }
+}
+
+fn format_string(s: &str) -> String {
+    let mut buf = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            _ => buf.push(c),
+        }
+    }
+    buf.push('"');
+    buf
+}
+
+fn format_nft_ids(ids: &BTreeSet<u128>) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| format!("{}u128", id)).collect();
+    format!("TreeSet<u128>({})", ids.join(", "))
+}
+
+fn format_address(address: Address) -> String {
+    format!("Address(\"{}\")", address)
+}
+
+fn format_decimal(amount: Decimal) -> String {
+    format!("Decimal(\"{}\")", amount)
+}
+
+/// Returns the manifest name of a SBOR type.
+fn type_name(ty: u8) -> Result<&'static str, DecompileError> {
+    let name = match ty {
+        TYPE_UNIT => "Unit",
+        TYPE_BOOL => "bool",
+        TYPE_I8 => "i8",
+        TYPE_I16 => "i16",
+        TYPE_I32 => "i32",
+        TYPE_I64 => "i64",
+        TYPE_I128 => "i128",
+        TYPE_U8 => "u8",
+        TYPE_U16 => "u16",
+        TYPE_U32 => "u32",
+        TYPE_U64 => "u64",
+        TYPE_U128 => "u128",
+        TYPE_STRING => "String",
+        TYPE_STRUCT => "Struct",
+        TYPE_ENUM => "Enum",
+        TYPE_OPTION => "Option",
+        TYPE_BOX => "Box",
+        TYPE_ARRAY => "Array",
+        TYPE_TUPLE => "Tuple",
+        TYPE_RESULT => "Result",
+        TYPE_VEC => "Vec",
+        TYPE_TREE_SET => "TreeSet",
+        TYPE_TREE_MAP => "TreeMap",
+        TYPE_HASH_SET => "HashSet",
+        TYPE_HASH_MAP => "HashMap",
+        SCRYPTO_TYPE_DECIMAL => "Decimal",
+        SCRYPTO_TYPE_BIG_DECIMAL => "BigDecimal",
+        SCRYPTO_TYPE_ADDRESS => "Address",
+        SCRYPTO_TYPE_H256 => "H256",
+        SCRYPTO_TYPE_BID => "Bucket",
+        SCRYPTO_TYPE_RID => "BucketRef",
+        SCRYPTO_TYPE_MID => "LazyMap",
+        SCRYPTO_TYPE_VID => "Vault",
+        _ => {
+            return Err(DecompileError::DecodeError(DecodeError::InvalidType {
+                expected: None,
+                actual: ty,
+            }))
+        }
+    };
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::compile;
+
+    #[test]
+    fn test_round_trip() {
+        let manifest = format!(
+            r#"LOCK_FEE Address("{0}") Decimal("10");
+CALL_METHOD Address("{0}") "withdraw" Decimal("1.5") Address("{1}");
+TAKE_FROM_CONTEXT Decimal("1.5") Address("{1}") Bucket("bucket1");
+TAKE_ALL_FROM_CONTEXT Address("{1}") Bucket("bucket2");
+TAKE_NFTS_FROM_CONTEXT TreeSet<u128>(3u128) Address("{1}") Bucket("bucket3");
+RETURN_TO_CONTEXT Bucket("bucket3");
+BORROW_FROM_CONTEXT Decimal("1") Address("{1}") BucketRef("bucket_ref1");
+CALL_FUNCTION Address("{2}") "Blueprint" "f" () true -1i8 2u128 "a\"b\n" Struct Struct {{ 1u8 }} Enum::1 ( None, Some(Box(1u32)) );
+CALL_METHOD Address("{0}") "g" Array<u8>(1u8, 2u8) Tuple(Ok(1u8), Err("e")) Vec<Bucket>(Bucket("bucket1")) TreeSet<i32>() HashSet<String>("x") TreeMap<String, BucketRef>("r" => BucketRef("bucket_ref1")) HashMap<u8, Unit>(1u8 => ());
+CALL_METHOD Address("{0}") "h" BigDecimal("-1.25") H256("{3}") LazyMap("{3}", 5u32) Vault("{3}", 6u32);
+ASSERT_CONTEXT_CONTAINS Decimal("1") Address("{1}");
+ASSERT_CONTEXT_CONTAINS_NFTS TreeSet<u128>(1u128, 2u128) Address("{1}");
+ASSERT_CONTEXT_DOES_NOT_CONTAIN Address("{1}");
+DROP_ALL_BUCKET_REFS;
+DEPOSIT_ALL_BUCKETS Address("{0}");
+END Address("{4}");
+"#,
+            "02d43f479e9b2beb9df98bc3888344fc25eda181e8f710ce1bf1de",
+            RADIX_TOKEN,
+            SYSTEM_PACKAGE,
+            H256([7u8; 32]),
+            "04005feceb66ffc86f38d952786c6d696c79c2dbc239dd4e91b46729d73a27fb57e9"
+        );
+
+        let transaction = compile(&manifest).unwrap();
+        assert_eq!(decompile(&transaction).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_unknown_bucket() {
+        let transaction = Transaction {
+            header: TransactionHeader::default(),
+            instructions: vec![Instruction::TakeFromContext {
+                amount: 1.into(),
+                resource_address: RADIX_TOKEN,
+                to: Bid(0),
+            }],
+        };
+        assert!(matches!(
+            decompile(&transaction),
+            Err(DecompileError::UnknownBucket(Bid(0)))
+        ));
+    }
+}
@@ -0,0 +1,279 @@
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+
+/// The position of a character within a manifest, both 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Represents a manifest token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// An identifier, e.g. `CALL_METHOD`, `Decimal` or `true`.
+    Ident(String),
+    /// A number, with an optional type suffix, e.g. `5`, `-12i32` or `100u128`.
+    Number(String),
+    /// A string literal, with escapes resolved.
+    String(String),
+
+    OpenParenthesis,
+    CloseParenthesis,
+    OpenCurlyBrace,
+    CloseCurlyBrace,
+    LessThan,
+    GreaterThan,
+    Comma,
+    Semicolon,
+    DoubleColon,
+    FatArrow,
+}
+
+/// A token, along with where it starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub position: Position,
+}
+
+/// Represents an error when tokenizing a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexerError {
+    UnexpectedEof,
+    UnexpectedChar(char, Position),
+    InvalidEscape(char, Position),
+}
+
+/// Splits a manifest into tokens.
+///
+/// Whitespace and `#` comments, which run until the end of the line, are skipped.
+pub struct Lexer {
+    chars: Vec<char>,
+    current: usize,
+    position: Position,
+}
+
+impl Lexer {
+    pub fn new(text: &str) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            current: 0,
+            position: Position { line: 1, column: 1 },
+        }
+    }
+
+    /// Returns all remaining tokens.
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, LexerError> {
+        let mut tokens = Vec::new();
+        while let Some(token) = self.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    /// Returns the next token, or `None` at the end of the manifest.
+    pub fn next_token(&mut self) -> Result<Option<Token>, LexerError> {
+        self.skip_whitespace_and_comments();
+
+        let position = self.position;
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let kind = match c {
+            '"' => self.tokenize_string()?,
+            '-' | '0'..='9' => self.tokenize_number()?,
+            'a'..='z' | 'A'..='Z' | '_' => self.tokenize_ident(),
+            _ => self.tokenize_punctuation()?,
+        };
+
+        Ok(Some(Token { kind, position }))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) -> Result<char, LexerError> {
+        let c = self.peek().ok_or(LexerError::UnexpectedEof)?;
+        self.current += 1;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Ok(c)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        let mut in_comment = false;
+        while let Some(c) = self.peek() {
+            if in_comment {
+                in_comment = c != '\n';
+            } else if c == '#' {
+                in_comment = true;
+            } else if !c.is_whitespace() {
+                break;
+            }
+            self.advance().unwrap();
+        }
+    }
+
+    fn tokenize_string(&mut self) -> Result<TokenKind, LexerError> {
+        self.advance()?;
+
+        let mut s = String::new();
+        loop {
+            let position = self.position;
+            match self.advance()? {
+                '"' => break,
+                '\\' => {
+                    let position = self.position;
+                    match self.advance()? {
+                        '"' => s.push('"'),
+                        '\\' => s.push('\\'),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        c => return Err(LexerError::InvalidEscape(c, position)),
+                    }
+                }
+                '\n' => return Err(LexerError::UnexpectedChar('\n', position)),
+                c => s.push(c),
+            }
+        }
+        Ok(TokenKind::String(s))
+    }
+
+    fn tokenize_number(&mut self) -> Result<TokenKind, LexerError> {
+        let mut s = String::new();
+        if self.peek() == Some('-') {
+            s.push(self.advance()?);
+        }
+
+        let position = self.position;
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => {}
+            Some(c) => return Err(LexerError::UnexpectedChar(c, position)),
+            None => return Err(LexerError::UnexpectedEof),
+        }
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_alphanumeric() {
+                break;
+            }
+            s.push(self.advance()?);
+        }
+        Ok(TokenKind::Number(s))
+    }
+
+    fn tokenize_ident(&mut self) -> TokenKind {
+        let mut s = String::new();
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_') {
+                break;
+            }
+            s.push(self.advance().unwrap());
+        }
+        TokenKind::Ident(s)
+    }
+
+    fn tokenize_punctuation(&mut self) -> Result<TokenKind, LexerError> {
+        let position = self.position;
+        let kind = match self.advance()? {
+            '(' => TokenKind::OpenParenthesis,
+            ')' => TokenKind::CloseParenthesis,
+            '{' => TokenKind::OpenCurlyBrace,
+            '}' => TokenKind::CloseCurlyBrace,
+            '<' => TokenKind::LessThan,
+            '>' => TokenKind::GreaterThan,
+            ',' => TokenKind::Comma,
+            ';' => TokenKind::Semicolon,
+            ':' if self.peek() == Some(':') => {
+                self.advance()?;
+                TokenKind::DoubleColon
+            }
+            '=' if self.peek() == Some('>') => {
+                self.advance()?;
+                TokenKind::FatArrow
+            }
+            c => return Err(LexerError::UnexpectedChar(c, position)),
+        };
+        Ok(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scrypto::rust::vec;
+
+    fn kinds(text: &str) -> Vec<TokenKind> {
+        Lexer::new(text)
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds("CALL_METHOD Address(\"02\") \"a\\\"b\" -5i32 Vec<u8>(); # comment"),
+            vec![
+                TokenKind::Ident("CALL_METHOD".into()),
+                TokenKind::Ident("Address".into()),
+                TokenKind::OpenParenthesis,
+                TokenKind::String("02".into()),
+                TokenKind::CloseParenthesis,
+                TokenKind::String("a\"b".into()),
+                TokenKind::Number("-5i32".into()),
+                TokenKind::Ident("Vec".into()),
+                TokenKind::LessThan,
+                TokenKind::Ident("u8".into()),
+                TokenKind::GreaterThan,
+                TokenKind::OpenParenthesis,
+                TokenKind::CloseParenthesis,
+                TokenKind::Semicolon,
+            ]
+        );
+        assert_eq!(
+            kinds("Enum::1 { 1u8 => 2u8 }"),
+            vec![
+                TokenKind::Ident("Enum".into()),
+                TokenKind::DoubleColon,
+                TokenKind::Number("1".into()),
+                TokenKind::OpenCurlyBrace,
+                TokenKind::Number("1u8".into()),
+                TokenKind::FatArrow,
+                TokenKind::Number("2u8".into()),
+                TokenKind::CloseCurlyBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_error() {
+        assert_eq!(
+            Lexer::new("DROP_ALL_BUCKET_REFS;\n  @").tokenize(),
+            Err(LexerError::UnexpectedChar(
+                '@',
+                Position { line: 2, column: 3 }
+            ))
+        );
+        assert_eq!(
+            Lexer::new("\"abc").tokenize(),
+            Err(LexerError::UnexpectedEof)
+        );
+        assert_eq!(
+            Lexer::new("\"\\x\"").tokenize(),
+            Err(LexerError::InvalidEscape(
+                'x',
+                Position { line: 1, column: 3 }
+            ))
+        );
+    }
+}
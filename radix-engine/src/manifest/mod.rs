@@ -0,0 +1,11 @@
+mod ast;
+mod compiler;
+mod decompiler;
+mod lexer;
+mod parser;
+
+pub use ast::{Fields, Instruction, Type, Value};
+pub use compiler::{compile, compile_value, CompileError, Compiler};
+pub use decompiler::{decompile, DecompileError};
+pub use lexer::{Lexer, LexerError, Position, Token, TokenKind};
+pub use parser::{Parser, ParserError};
@@ -0,0 +1,514 @@
+use sbor::rust::boxed::Box;
+use scrypto::rust::borrow::ToOwned;
+use scrypto::rust::str::FromStr;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+
+use crate::manifest::ast::{Fields, Instruction, Type, Value};
+use crate::manifest::lexer::{Position, Token, TokenKind};
+
+/// Represents an error when parsing a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParserError {
+    UnexpectedEof,
+    UnexpectedToken(Token),
+    UnknownInstruction(String, Position),
+    UnknownType(String, Position),
+    UnknownValue(String, Position),
+    InvalidNumber(String, Position),
+}
+
+/// Parses manifest tokens into instructions.
+pub struct Parser {
+    tokens: Vec<Token>,
+    current: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, current: 0 }
+    }
+
+    /// Returns whether all tokens have been consumed.
+    pub fn is_eof(&self) -> bool {
+        self.current == self.tokens.len()
+    }
+
+    /// Fails with the next token if not all tokens have been consumed.
+    pub fn check_eof(&self) -> Result<(), ParserError> {
+        match self.tokens.get(self.current) {
+            Some(token) => Err(ParserError::UnexpectedToken(token.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses all remaining instructions.
+    pub fn parse_manifest(&mut self) -> Result<Vec<Instruction>, ParserError> {
+        let mut instructions = Vec::new();
+        while !self.is_eof() {
+            instructions.push(self.parse_instruction()?);
+        }
+        Ok(instructions)
+    }
+
+    /// Parses one instruction, including its trailing semicolon.
+    pub fn parse_instruction(&mut self) -> Result<Instruction, ParserError> {
+        let token = self.advance()?;
+        let name = match &token.kind {
+            TokenKind::Ident(name) => name.as_str(),
+            _ => return Err(ParserError::UnexpectedToken(token)),
+        };
+
+        let instruction = match name {
+            "LOCK_FEE" => Instruction::LockFee {
+                account: self.parse_value()?,
+                amount: self.parse_value()?,
+            },
+            "TAKE_FROM_CONTEXT" => Instruction::TakeFromContext {
+                amount: self.parse_value()?,
+                resource_address: self.parse_value()?,
+                new_bucket: self.parse_value()?,
+            },
+            "TAKE_ALL_FROM_CONTEXT" => Instruction::TakeAllFromContext {
+                resource_address: self.parse_value()?,
+                new_bucket: self.parse_value()?,
+            },
+            "TAKE_NFTS_FROM_CONTEXT" => Instruction::TakeNftsFromContext {
+                ids: self.parse_value()?,
+                resource_address: self.parse_value()?,
+                new_bucket: self.parse_value()?,
+            },
+            "RETURN_TO_CONTEXT" => Instruction::ReturnToContext {
+                bucket: self.parse_value()?,
+            },
+            "BORROW_FROM_CONTEXT" => Instruction::BorrowFromContext {
+                amount: self.parse_value()?,
+                resource_address: self.parse_value()?,
+                new_bucket_ref: self.parse_value()?,
+            },
+            "ASSERT_CONTEXT_CONTAINS" => Instruction::AssertContextContains {
+                amount: self.parse_value()?,
+                resource_address: self.parse_value()?,
+            },
+            "ASSERT_CONTEXT_CONTAINS_NFTS" => Instruction::AssertContextContainsNfts {
+                ids: self.parse_value()?,
+                resource_address: self.parse_value()?,
+            },
+            "ASSERT_CONTEXT_DOES_NOT_CONTAIN" => Instruction::AssertContextDoesNotContain {
+                resource_address: self.parse_value()?,
+            },
+            "CALL_FUNCTION" => Instruction::CallFunction {
+                package_address: self.parse_value()?,
+                blueprint_name: self.parse_value()?,
+                function: self.parse_value()?,
+                args: self.parse_values_until_semicolon()?,
+            },
+            "CALL_METHOD" => Instruction::CallMethod {
+                component_address: self.parse_value()?,
+                method: self.parse_value()?,
+                args: self.parse_values_until_semicolon()?,
+            },
+            "DROP_ALL_BUCKET_REFS" => Instruction::DropAllBucketRefs,
+            "DEPOSIT_ALL_BUCKETS" => Instruction::DepositAllBuckets {
+                account: self.parse_value()?,
+            },
+            "END" => Instruction::End {
+                signers: self.parse_values_until_semicolon()?,
+            },
+            _ => {
+                return Err(ParserError::UnknownInstruction(
+                    name.to_owned(),
+                    token.position,
+                ))
+            }
+        };
+        self.expect(TokenKind::Semicolon)?;
+
+        Ok(instruction)
+    }
+
+    /// Parses one value.
+    pub fn parse_value(&mut self) -> Result<Value, ParserError> {
+        let token = self.advance()?;
+        match &token.kind {
+            TokenKind::OpenParenthesis => {
+                self.expect(TokenKind::CloseParenthesis)?;
+                Ok(Value::Unit)
+            }
+            TokenKind::String(s) => Ok(Value::String(s.clone())),
+            TokenKind::Number(n) => Self::parse_number(n, token.position),
+            TokenKind::Ident(ident) => match ident.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "Struct" => Ok(Value::Struct(self.parse_fields()?)),
+                "Enum" => {
+                    self.expect(TokenKind::DoubleColon)?;
+                    let token = self.advance()?;
+                    let index = match &token.kind {
+                        TokenKind::Number(n) => u8::from_str(n)
+                            .map_err(|_| ParserError::InvalidNumber(n.clone(), token.position))?,
+                        _ => return Err(ParserError::UnexpectedToken(token)),
+                    };
+                    Ok(Value::Enum(index, self.parse_fields()?))
+                }
+                "None" => Ok(Value::Option(Box::new(None))),
+                "Some" => Ok(Value::Option(Box::new(Some(self.parse_wrapped_value()?)))),
+                "Box" => Ok(Value::Box(Box::new(self.parse_wrapped_value()?))),
+                "Ok" => Ok(Value::Result(Box::new(Ok(self.parse_wrapped_value()?)))),
+                "Err" => Ok(Value::Result(Box::new(Err(self.parse_wrapped_value()?)))),
+                "Tuple" => Ok(Value::Tuple(self.parse_values(
+                    TokenKind::OpenParenthesis,
+                    TokenKind::CloseParenthesis,
+                )?)),
+                "Array" => {
+                    let ty = self.parse_type_args_1()?;
+                    Ok(Value::Array(ty, self.parse_elements()?))
+                }
+                "Vec" => {
+                    let ty = self.parse_type_args_1()?;
+                    Ok(Value::Vec(ty, self.parse_elements()?))
+                }
+                "TreeSet" => {
+                    let ty = self.parse_type_args_1()?;
+                    Ok(Value::TreeSet(ty, self.parse_elements()?))
+                }
+                "HashSet" => {
+                    let ty = self.parse_type_args_1()?;
+                    Ok(Value::HashSet(ty, self.parse_elements()?))
+                }
+                "TreeMap" => {
+                    let (key, value) = self.parse_type_args_2()?;
+                    Ok(Value::TreeMap(key, value, self.parse_entries()?))
+                }
+                "HashMap" => {
+                    let (key, value) = self.parse_type_args_2()?;
+                    Ok(Value::HashMap(key, value, self.parse_entries()?))
+                }
+                "Decimal" => Ok(Value::Decimal(self.parse_wrapped_string()?)),
+                "BigDecimal" => Ok(Value::BigDecimal(self.parse_wrapped_string()?)),
+                "Address" => Ok(Value::Address(self.parse_wrapped_string()?)),
+                "H256" => Ok(Value::H256(self.parse_wrapped_string()?)),
+                "Bucket" => Ok(Value::Bucket(self.parse_wrapped_string()?)),
+                "BucketRef" => Ok(Value::BucketRef(self.parse_wrapped_string()?)),
+                "LazyMap" => {
+                    let (hash, index) = self.parse_wrapped_id()?;
+                    Ok(Value::LazyMap(hash, index))
+                }
+                "Vault" => {
+                    let (hash, index) = self.parse_wrapped_id()?;
+                    Ok(Value::Vault(hash, index))
+                }
+                _ => Err(ParserError::UnknownValue(ident.clone(), token.position)),
+            },
+            _ => Err(ParserError::UnexpectedToken(token)),
+        }
+    }
+
+    /// Parses a type name.
+    pub fn parse_type(&mut self) -> Result<Type, ParserError> {
+        let token = self.advance()?;
+        let name = match &token.kind {
+            TokenKind::Ident(name) => name.as_str(),
+            _ => return Err(ParserError::UnexpectedToken(token)),
+        };
+
+        let ty = match name {
+            "Unit" => Type::Unit,
+            "bool" => Type::Bool,
+            "i8" => Type::I8,
+            "i16" => Type::I16,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "i128" => Type::I128,
+            "u8" => Type::U8,
+            "u16" => Type::U16,
+            "u32" => Type::U32,
+            "u64" => Type::U64,
+            "u128" => Type::U128,
+            "String" => Type::String,
+            "Struct" => Type::Struct,
+            "Enum" => Type::Enum,
+            "Option" => Type::Option,
+            "Box" => Type::Box,
+            "Array" => Type::Array,
+            "Tuple" => Type::Tuple,
+            "Result" => Type::Result,
+            "Vec" => Type::Vec,
+            "TreeSet" => Type::TreeSet,
+            "TreeMap" => Type::TreeMap,
+            "HashSet" => Type::HashSet,
+            "HashMap" => Type::HashMap,
+            "Decimal" => Type::Decimal,
+            "BigDecimal" => Type::BigDecimal,
+            "Address" => Type::Address,
+            "H256" => Type::H256,
+            "Bucket" => Type::Bucket,
+            "BucketRef" => Type::BucketRef,
+            "LazyMap" => Type::LazyMap,
+            "Vault" => Type::Vault,
+            _ => return Err(ParserError::UnknownType(name.to_owned(), token.position)),
+        };
+        Ok(ty)
+    }
+
+    fn parse_number(n: &str, position: Position) -> Result<Value, ParserError> {
+        let err = || ParserError::InvalidNumber(n.to_owned(), position);
+        let split = n.find(['i', 'u']).ok_or_else(err)?;
+        let (digits, suffix) = n.split_at(split);
+
+        let value = match suffix {
+            "i8" => i8::from_str(digits).map(Value::I8),
+            "i16" => i16::from_str(digits).map(Value::I16),
+            "i32" => i32::from_str(digits).map(Value::I32),
+            "i64" => i64::from_str(digits).map(Value::I64),
+            "i128" => i128::from_str(digits).map(Value::I128),
+            "u8" => u8::from_str(digits).map(Value::U8),
+            "u16" => u16::from_str(digits).map(Value::U16),
+            "u32" => u32::from_str(digits).map(Value::U32),
+            "u64" => u64::from_str(digits).map(Value::U64),
+            "u128" => u128::from_str(digits).map(Value::U128),
+            _ => return Err(err()),
+        };
+        value.map_err(|_| err())
+    }
+
+    fn parse_fields(&mut self) -> Result<Fields, ParserError> {
+        match self.peek_kind() {
+            Some(TokenKind::OpenCurlyBrace) => Ok(Fields::Named(
+                self.parse_values(TokenKind::OpenCurlyBrace, TokenKind::CloseCurlyBrace)?,
+            )),
+            Some(TokenKind::OpenParenthesis) => Ok(Fields::Unnamed(
+                self.parse_values(TokenKind::OpenParenthesis, TokenKind::CloseParenthesis)?,
+            )),
+            _ => Ok(Fields::Unit),
+        }
+    }
+
+    fn parse_wrapped_value(&mut self) -> Result<Value, ParserError> {
+        self.expect(TokenKind::OpenParenthesis)?;
+        let value = self.parse_value()?;
+        self.expect(TokenKind::CloseParenthesis)?;
+        Ok(value)
+    }
+
+    fn parse_wrapped_string(&mut self) -> Result<String, ParserError> {
+        self.expect(TokenKind::OpenParenthesis)?;
+        let s = self.parse_string()?;
+        self.expect(TokenKind::CloseParenthesis)?;
+        Ok(s)
+    }
+
+    fn parse_wrapped_id(&mut self) -> Result<(String, u32), ParserError> {
+        self.expect(TokenKind::OpenParenthesis)?;
+        let hash = self.parse_string()?;
+        self.expect(TokenKind::Comma)?;
+        let token = self.advance()?;
+        let index = match Self::parse_number_token(&token)? {
+            Value::U32(index) => index,
+            _ => return Err(ParserError::UnexpectedToken(token)),
+        };
+        self.expect(TokenKind::CloseParenthesis)?;
+        Ok((hash, index))
+    }
+
+    fn parse_number_token(token: &Token) -> Result<Value, ParserError> {
+        match &token.kind {
+            TokenKind::Number(n) => Self::parse_number(n, token.position),
+            _ => Err(ParserError::UnexpectedToken(token.clone())),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParserError> {
+        let token = self.advance()?;
+        match token.kind {
+            TokenKind::String(s) => Ok(s),
+            _ => Err(ParserError::UnexpectedToken(token)),
+        }
+    }
+
+    fn parse_type_args_1(&mut self) -> Result<Type, ParserError> {
+        self.expect(TokenKind::LessThan)?;
+        let ty = self.parse_type()?;
+        self.expect(TokenKind::GreaterThan)?;
+        Ok(ty)
+    }
+
+    fn parse_type_args_2(&mut self) -> Result<(Type, Type), ParserError> {
+        self.expect(TokenKind::LessThan)?;
+        let key = self.parse_type()?;
+        self.expect(TokenKind::Comma)?;
+        let value = self.parse_type()?;
+        self.expect(TokenKind::GreaterThan)?;
+        Ok((key, value))
+    }
+
+    fn parse_elements(&mut self) -> Result<Vec<Value>, ParserError> {
+        self.parse_values(TokenKind::OpenParenthesis, TokenKind::CloseParenthesis)
+    }
+
+    fn parse_entries(&mut self) -> Result<Vec<(Value, Value)>, ParserError> {
+        self.expect(TokenKind::OpenParenthesis)?;
+        let mut entries = Vec::new();
+        while self.peek_kind() != Some(&TokenKind::CloseParenthesis) {
+            let key = self.parse_value()?;
+            self.expect(TokenKind::FatArrow)?;
+            let value = self.parse_value()?;
+            entries.push((key, value));
+            if self.peek_kind() != Some(&TokenKind::CloseParenthesis) {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        self.expect(TokenKind::CloseParenthesis)?;
+        Ok(entries)
+    }
+
+    /// Parses comma-separated values between the given delimiters.
+    fn parse_values(
+        &mut self,
+        open: TokenKind,
+        close: TokenKind,
+    ) -> Result<Vec<Value>, ParserError> {
+        self.expect(open)?;
+        let mut values = Vec::new();
+        while self.peek_kind() != Some(&close) {
+            values.push(self.parse_value()?);
+            if self.peek_kind() != Some(&close) {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        self.expect(close)?;
+        Ok(values)
+    }
+
+    /// Parses whitespace-separated values up to, but excluding, the next semicolon.
+    fn parse_values_until_semicolon(&mut self) -> Result<Vec<Value>, ParserError> {
+        let mut values = Vec::new();
+        while self.peek_kind() != Some(&TokenKind::Semicolon) {
+            values.push(self.parse_value()?);
+        }
+        Ok(values)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.current).map(|t| &t.kind)
+    }
+
+    fn advance(&mut self) -> Result<Token, ParserError> {
+        let token = self
+            .tokens
+            .get(self.current)
+            .cloned()
+            .ok_or(ParserError::UnexpectedEof)?;
+        self.current += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), ParserError> {
+        let token = self.advance()?;
+        if token.kind == kind {
+            Ok(())
+        } else {
+            Err(ParserError::UnexpectedToken(token))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::lexer::Lexer;
+    use scrypto::rust::vec;
+
+    fn parse_value(text: &str) -> Result<Value, ParserError> {
+        let mut parser = Parser::new(Lexer::new(text).tokenize().unwrap());
+        let value = parser.parse_value()?;
+        parser.check_eof()?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_parse_primitives() {
+        assert_eq!(parse_value("()"), Ok(Value::Unit));
+        assert_eq!(parse_value("true"), Ok(Value::Bool(true)));
+        assert_eq!(parse_value("-5i8"), Ok(Value::I8(-5)));
+        assert_eq!(parse_value("300u16"), Ok(Value::U16(300)));
+        assert_eq!(parse_value("\"abc\""), Ok(Value::String("abc".into())));
+        assert_eq!(
+            parse_value("300u8"),
+            Err(ParserError::InvalidNumber(
+                "300u8".into(),
+                Position { line: 1, column: 1 }
+            ))
+        );
+        assert_eq!(
+            parse_value("5"),
+            Err(ParserError::InvalidNumber(
+                "5".into(),
+                Position { line: 1, column: 1 }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_composites() {
+        assert_eq!(
+            parse_value("Struct { 1u8, Struct ( \"a\" ), Struct }"),
+            Ok(Value::Struct(Fields::Named(vec![
+                Value::U8(1),
+                Value::Struct(Fields::Unnamed(vec![Value::String("a".into())])),
+                Value::Struct(Fields::Unit),
+            ])))
+        );
+        assert_eq!(
+            parse_value("Enum::2 ( Some(Box(1u32)), None )"),
+            Ok(Value::Enum(
+                2,
+                Fields::Unnamed(vec![
+                    Value::Option(Box::new(Some(Value::Box(Box::new(Value::U32(1)))))),
+                    Value::Option(Box::new(None)),
+                ])
+            ))
+        );
+        assert_eq!(
+            parse_value("TreeMap<String, Vec>(\"a\" => Vec<Bucket>(Bucket(\"b\")),)"),
+            Ok(Value::TreeMap(
+                Type::String,
+                Type::Vec,
+                vec![(
+                    Value::String("a".into()),
+                    Value::Vec(Type::Bucket, vec![Value::Bucket("b".into())])
+                )]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_instruction() {
+        let tokens = Lexer::new("CALL_METHOD Address(\"02\") \"withdraw\" Decimal(\"1\");")
+            .tokenize()
+            .unwrap();
+        assert_eq!(
+            Parser::new(tokens).parse_manifest(),
+            Ok(vec![Instruction::CallMethod {
+                component_address: Value::Address("02".into()),
+                method: Value::String("withdraw".into()),
+                args: vec![Value::Decimal("1".into())],
+            }])
+        );
+
+        let tokens = Lexer::new("DROP_ALL_BUCKET_REFS").tokenize().unwrap();
+        assert_eq!(
+            Parser::new(tokens).parse_manifest(),
+            Err(ParserError::UnexpectedEof)
+        );
+
+        let tokens = Lexer::new("BURN;").tokenize().unwrap();
+        assert_eq!(
+            Parser::new(tokens).parse_manifest(),
+            Err(ParserError::UnknownInstruction(
+                "BURN".into(),
+                Position { line: 1, column: 1 }
+            ))
+        );
+    }
+}
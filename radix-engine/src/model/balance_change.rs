@@ -0,0 +1,22 @@
+use scrypto::rust::collections::BTreeSet;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+/// The change of a vault balance caused by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub vid: Vid,
+    pub resource_address: Address,
+    /// The change of amount, negative if resource is taken out.
+    pub amount: Decimal,
+    pub nfts_added: BTreeSet<u128>,
+    pub nfts_removed: BTreeSet<u128>,
+}
+
+/// The balance changes of all vaults owned by a component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentBalanceChanges {
+    /// The owner of the vaults, or `None` if they're not reachable from any component accessed.
+    pub component_address: Option<Address>,
+    pub changes: Vec<BalanceChange>,
+}
@@ -0,0 +1,38 @@
+use sbor::any::*;
+use sbor::*;
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+use crate::model::*;
+
+/// A node in the execution trace of a transaction, representing a function or method call.
+#[derive(Debug, Clone)]
+pub struct CallTrace {
+    /// The actor making the call; `SuperUser` for calls made by the transaction itself.
+    pub actor: Actor,
+    pub package_address: Address,
+    pub export_name: String,
+    pub function: String,
+    pub component_address: Option<Address>,
+    /// The decoded arguments, with an error in place of any argument that can't be decoded.
+    pub args: Vec<Result<Value, DecodeError>>,
+    /// The decoded return value, if the call succeeded.
+    pub output: Option<Result<Value, DecodeError>>,
+    /// The error, if the call failed.
+    pub error: Option<String>,
+    /// Buckets moved from the caller to the callee.
+    pub buckets_in: Vec<(Bid, Decimal, Address)>,
+    /// Bucket refs moved from the caller to the callee.
+    pub bucket_refs_in: Vec<(Rid, Decimal, Address)>,
+    /// Buckets returned from the callee to the caller.
+    pub buckets_out: Vec<(Bid, Decimal, Address)>,
+    /// Bucket refs returned from the callee to the caller.
+    pub bucket_refs_out: Vec<(Rid, Decimal, Address)>,
+    /// The kernel operations invoked by the callee, in order.
+    pub kernel_calls: Vec<u32>,
+    /// The time elapsed in microseconds, if available.
+    pub time_elapsed: Option<u128>,
+    /// The calls made by the callee.
+    pub children: Vec<CallTrace>,
+}
@@ -18,6 +18,7 @@ pub struct Component {
     blueprint_name: String,
     state: Vec<u8>,
     authority: Address,
+    reentrant_reads: bool,
 }
 
 impl Component {
@@ -27,6 +28,7 @@ impl Component {
             blueprint_name,
             state,
             authority: package_address,
+            reentrant_reads: false,
         }
     }
 
@@ -38,6 +40,19 @@ impl Component {
         &self.blueprint_name
     }
 
+    pub fn reentrant_reads(&self) -> bool {
+        self.reentrant_reads
+    }
+
+    pub fn allow_reentrant_reads(&mut self, actor: Actor) -> Result<(), ComponentError> {
+        if actor.check(self.authority) {
+            self.reentrant_reads = true;
+            Ok(())
+        } else {
+            Err(ComponentError::UnauthorizedAccess)
+        }
+    }
+
     pub fn state(&self, actor: Actor) -> Result<&[u8], ComponentError> {
         if actor.check(self.authority) {
             Ok(&self.state)
@@ -0,0 +1,44 @@
+use scrypto::rust::string::String;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+/// An event emitted by a blueprint.
+#[derive(Debug, Clone)]
+pub struct Event {
+    package_address: Address,
+    component_address: Option<Address>,
+    name: String,
+    data: Vec<u8>,
+}
+
+impl Event {
+    pub fn new(
+        package_address: Address,
+        component_address: Option<Address>,
+        name: String,
+        data: Vec<u8>,
+    ) -> Self {
+        Self {
+            package_address,
+            component_address,
+            name,
+            data,
+        }
+    }
+
+    pub fn package_address(&self) -> Address {
+        self.package_address
+    }
+
+    pub fn component_address(&self) -> Option<Address> {
+        self.component_address
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
@@ -16,6 +16,7 @@ pub enum LazyMapError {
 pub struct LazyMap {
     map: HashMap<Vec<u8>, Vec<u8>>,
     authority: Address,
+    owner: Option<Address>,
 }
 
 impl LazyMap {
@@ -23,9 +24,19 @@ impl LazyMap {
         Self {
             map: HashMap::new(),
             authority,
+            owner: None,
         }
     }
 
+    /// Returns the component which holds this lazy map, if it has been placed in one.
+    pub fn owner(&self) -> Option<Address> {
+        self.owner
+    }
+
+    pub fn set_owner(&mut self, owner: Address) {
+        self.owner = Some(owner);
+    }
+
     // for diagnosis purpose only
     pub fn map(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
         &self.map
@@ -1,17 +1,25 @@
 mod actor;
+mod balance_change;
 mod bucket;
+mod call_trace;
 mod component;
+mod event;
 mod lazy_map;
 mod nft;
 mod package;
 mod resource_def;
+mod substate;
 mod vault;
 
 pub use actor::Actor;
+pub use balance_change::{BalanceChange, ComponentBalanceChanges};
 pub use bucket::{Bucket, BucketError, BucketRef, LockedBucket, Supply};
+pub use call_trace::CallTrace;
 pub use component::{Component, ComponentError};
+pub use event::Event;
 pub use lazy_map::{LazyMap, LazyMapError};
 pub use nft::{Nft, NftError};
 pub use package::Package;
 pub use resource_def::{ResourceDef, ResourceDefError};
+pub use substate::{Substate, SubstateChange, SubstateId};
 pub use vault::{Vault, VaultError};
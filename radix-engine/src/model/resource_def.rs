@@ -2,6 +2,7 @@ use sbor::*;
 use scrypto::kernel::*;
 use scrypto::resource::resource_flags::*;
 use scrypto::resource::resource_permissions::*;
+use scrypto::resource::AccessRule;
 use scrypto::rust::collections::HashMap;
 use scrypto::rust::string::String;
 use scrypto::types::*;
@@ -34,6 +35,7 @@ pub struct ResourceDef {
     mutable_flags: u16,
     authorities: HashMap<Address, u16>,
     total_supply: Decimal,
+    withdraw_rule: Option<AccessRule>,
 }
 
 impl ResourceDef {
@@ -52,6 +54,7 @@ impl ResourceDef {
             mutable_flags,
             authorities,
             total_supply: Decimal::zero(),
+            withdraw_rule: None,
         };
 
         resource_def.total_supply = match (resource_type, initial_supply) {
@@ -97,6 +100,14 @@ impl ResourceDef {
         self.total_supply
     }
 
+    pub fn withdraw_rule(&self) -> Option<&AccessRule> {
+        self.withdraw_rule.as_ref()
+    }
+
+    pub fn set_withdraw_rule(&mut self, withdraw_rule: AccessRule) {
+        self.withdraw_rule = Some(withdraw_rule);
+    }
+
     pub fn is_flag_on(&self, flag: u16) -> bool {
         self.flags() & flag == flag
     }
@@ -203,14 +214,40 @@ impl ResourceDef {
         Ok(())
     }
 
-    pub fn check_take_from_vault_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
+    pub fn check_take_from_vault_auth(
+        &self,
+        actor: Actor,
+        proofs: &HashMap<Address, Decimal>,
+    ) -> Result<(), ResourceDefError> {
         if !self.is_flag_on(RESTRICTED_TRANSFER) {
             Ok(())
         } else {
+            (actor.check_permission(self.authorities(), MAY_TRANSFER)
+                || matches!(&self.withdraw_rule, Some(rule) if Self::is_satisfied(rule, proofs)))
+            .then_some(())
+            .ok_or(ResourceDefError::UnauthorizedAccess)
+        }
+    }
+
+    pub fn check_recall_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
+        if self.is_flag_on(RECALLABLE) {
             actor
-                .check_permission(self.authorities(), MAY_TRANSFER)
-                .then(|| ())
+                .check_permission(self.authorities(), MAY_RECALL)
+                .then_some(())
+                .ok_or(ResourceDefError::UnauthorizedAccess)
+        } else {
+            Err(ResourceDefError::OperationNotAllowed)
+        }
+    }
+
+    pub fn check_freeze_auth(&self, actor: Actor) -> Result<(), ResourceDefError> {
+        if self.is_flag_on(FREEZABLE) {
+            actor
+                .check_permission(self.authorities(), MAY_FREEZE)
+                .then_some(())
                 .ok_or(ResourceDefError::UnauthorizedAccess)
+        } else {
+            Err(ResourceDefError::OperationNotAllowed)
         }
     }
 
@@ -276,4 +313,22 @@ impl ResourceDef {
             Ok(())
         }
     }
+
+    /// Checks if the resources presented, keyed by resource address, satisfy the rule.
+    fn is_satisfied(rule: &AccessRule, proofs: &HashMap<Address, Decimal>) -> bool {
+        match rule {
+            AccessRule::Require {
+                resource_address,
+                amount,
+            } => matches!(proofs.get(resource_address), Some(presented) if presented >= amount),
+            AccessRule::AllOf(rules) => rules.iter().all(|r| Self::is_satisfied(r, proofs)),
+            AccessRule::CountOf(count, rules) => {
+                rules
+                    .iter()
+                    .filter(|r| Self::is_satisfied(r, proofs))
+                    .count()
+                    >= *count as usize
+            }
+        }
+    }
 }
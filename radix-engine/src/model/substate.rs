@@ -0,0 +1,61 @@
+use sbor::*;
+use scrypto::buffer::*;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+use crate::model::*;
+
+/// Identifies a piece of ledger state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubstateId {
+    Package(Address),
+    Component(Address),
+    LazyMap(Mid),
+    ResourceDef(Address),
+    Vault(Vid),
+    Nft(Address, u128),
+}
+
+/// A decoded piece of ledger state.
+#[derive(Debug, Clone)]
+pub enum Substate {
+    Package(Package),
+    Component(Component),
+    LazyMap(LazyMap),
+    ResourceDef(ResourceDef),
+    Vault(Vault),
+    Nft(Nft),
+}
+
+/// The change of a substate caused by a transaction.
+#[derive(Debug, Clone)]
+pub struct SubstateChange {
+    pub id: SubstateId,
+    /// The SBOR-encoded substate before the transaction, `None` if newly created.
+    pub before: Option<Vec<u8>>,
+    /// The SBOR-encoded substate after the transaction.
+    pub after: Vec<u8>,
+}
+
+impl SubstateChange {
+    /// Decodes the substate before the transaction.
+    pub fn decode_before(&self) -> Option<Result<Substate, DecodeError>> {
+        self.before.as_ref().map(|data| Self::decode(self.id, data))
+    }
+
+    /// Decodes the substate after the transaction.
+    pub fn decode_after(&self) -> Result<Substate, DecodeError> {
+        Self::decode(self.id, &self.after)
+    }
+
+    fn decode(id: SubstateId, data: &[u8]) -> Result<Substate, DecodeError> {
+        match id {
+            SubstateId::Package(_) => scrypto_decode(data).map(Substate::Package),
+            SubstateId::Component(_) => scrypto_decode(data).map(Substate::Component),
+            SubstateId::LazyMap(_) => scrypto_decode(data).map(Substate::LazyMap),
+            SubstateId::ResourceDef(_) => scrypto_decode(data).map(Substate::ResourceDef),
+            SubstateId::Vault(_) => scrypto_decode(data).map(Substate::Vault),
+            SubstateId::Nft(_, _) => scrypto_decode(data).map(Substate::Nft),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use sbor::*;
+use scrypto::resource::vault_freeze_flags::*;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
 
@@ -9,6 +10,7 @@ use crate::model::{Actor, Bucket, BucketError, Supply};
 pub enum VaultError {
     AccountingError(BucketError),
     UnauthorizedAccess,
+    Frozen,
 }
 
 /// A persistent resource container on ledger state.
@@ -16,15 +18,33 @@ pub enum VaultError {
 pub struct Vault {
     bucket: Bucket,
     authority: Address,
+    freeze_flags: u8,
+    owner: Option<Address>,
 }
 
 impl Vault {
     pub fn new(bucket: Bucket, authority: Address) -> Self {
-        Self { bucket, authority }
+        Self {
+            bucket,
+            authority,
+            freeze_flags: 0,
+            owner: None,
+        }
+    }
+
+    /// Returns the component which holds this vault, if it has been placed in one.
+    pub fn owner(&self) -> Option<Address> {
+        self.owner
+    }
+
+    pub fn set_owner(&mut self, owner: Address) {
+        self.owner = Some(owner);
     }
 
     pub fn put(&mut self, other: Bucket, actor: Actor) -> Result<(), VaultError> {
-        if actor.check(self.authority) {
+        if self.is_frozen(FREEZE_DEPOSIT) {
+            Err(VaultError::Frozen)
+        } else if actor.check(self.authority) {
             self.bucket.put(other).map_err(VaultError::AccountingError)
         } else {
             Err(VaultError::UnauthorizedAccess)
@@ -32,7 +52,9 @@ impl Vault {
     }
 
     pub fn take(&mut self, amount: Decimal, actor: Actor) -> Result<Bucket, VaultError> {
-        if actor.check(self.authority) {
+        if self.is_frozen(FREEZE_WITHDRAW) {
+            Err(VaultError::Frozen)
+        } else if actor.check(self.authority) {
             self.bucket
                 .take(amount)
                 .map_err(VaultError::AccountingError)
@@ -42,7 +64,9 @@ impl Vault {
     }
 
     pub fn take_nft(&mut self, id: u128, actor: Actor) -> Result<Bucket, VaultError> {
-        if actor.check(self.authority) {
+        if self.is_frozen(FREEZE_WITHDRAW) {
+            Err(VaultError::Frozen)
+        } else if actor.check(self.authority) {
             self.bucket
                 .take_nft(id)
                 .map_err(VaultError::AccountingError)
@@ -51,6 +75,30 @@ impl Vault {
         }
     }
 
+    /// Takes resource regardless of the vault authority and freeze flags, so a frozen vault can
+    /// still be recalled from.
+    pub fn recall(&mut self, amount: Decimal) -> Result<Bucket, VaultError> {
+        self.bucket
+            .take(amount)
+            .map_err(VaultError::AccountingError)
+    }
+
+    pub fn freeze_flags(&self, actor: Actor) -> Result<u8, VaultError> {
+        if actor.check(self.authority) {
+            Ok(self.freeze_flags)
+        } else {
+            Err(VaultError::UnauthorizedAccess)
+        }
+    }
+
+    pub fn is_frozen(&self, flag: u8) -> bool {
+        self.freeze_flags & flag == flag
+    }
+
+    pub fn set_freeze_flags(&mut self, new_freeze_flags: u8) {
+        self.freeze_flags = new_freeze_flags;
+    }
+
     pub fn get_nft_ids(&self, actor: Actor) -> Result<Vec<u128>, VaultError> {
         if actor.check(self.authority) {
             self.bucket
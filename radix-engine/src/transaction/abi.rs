@@ -1,8 +1,6 @@
 use scrypto::abi;
-use scrypto::buffer::*;
 use scrypto::rust::borrow::ToOwned;
 use scrypto::rust::string::String;
-use scrypto::rust::string::ToString;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
 use scrypto::utils::*;
@@ -74,19 +72,16 @@ impl AbiProvider for BasicAbiProvider {
         let transaction_hash = sha256([]);
 
         // Start a process and run abi generator
-        let mut track = Track::new(&mut ledger, current_epoch, transaction_hash, Vec::new());
+        let mut track = Track::new(
+            &mut ledger,
+            current_epoch,
+            transaction_hash,
+            Vec::new(),
+            DEFAULT_COST_UNIT_LIMIT,
+            DEFAULT_MAX_CALL_DEPTH,
+        );
         let mut proc = track.start_process(trace);
-        let output: (Vec<abi::Function>, Vec<abi::Method>) = proc
-            .call_abi(package_address, blueprint_name.as_ref())
-            .and_then(|rtn| scrypto_decode(&rtn).map_err(RuntimeError::InvalidData))?;
-
-        // Return ABI
-        Ok(abi::Blueprint {
-            package: package_address.to_string(),
-            name: blueprint_name.as_ref().to_owned(),
-            functions: output.0,
-            methods: output.1,
-        })
+        proc.export_abi(package_address, blueprint_name.as_ref())
     }
 
     fn export_abi_component(
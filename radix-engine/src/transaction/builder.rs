@@ -1,3 +1,4 @@
+use sbor::any::*;
 use sbor::describe::*;
 use sbor::*;
 use scrypto::abi;
@@ -15,7 +16,9 @@ use scrypto::rust::vec::Vec;
 use scrypto::types::*;
 
 use crate::engine::*;
+use crate::manifest::compile_value;
 use crate::transaction::*;
+use crate::utils::*;
 
 /// Represents some amount of resource.
 pub enum ResourceAmount {
@@ -109,6 +112,10 @@ pub struct TransactionBuilder<'a, A: AbiProvider> {
     abi_provider: &'a A,
     /// The address allocator for calculating reserved bucket id.
     allocator: IdAllocator,
+    /// The transaction header
+    header: TransactionHeader,
+    /// Fee lock, which goes right after the reservations
+    fee_lock: Option<Instruction>,
     /// Bucket or BucketRef reservations
     reservations: Vec<Instruction>,
     /// Instructions generated.
@@ -123,18 +130,58 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         Self {
             abi_provider,
             allocator: IdAllocator::new(),
+            header: TransactionHeader::default(),
+            fee_lock: None,
             reservations: Vec::new(),
             instructions: Vec::new(),
             errors: Vec::new(),
         }
     }
 
+    /// Sets the network the transaction is intended for.
+    pub fn set_network_id(&mut self, network_id: u8) -> &mut Self {
+        self.header.network_id = network_id;
+        self
+    }
+
+    /// Sets the range of epochs in which the transaction is valid.
+    pub fn set_epochs(
+        &mut self,
+        start_epoch_inclusive: u64,
+        end_epoch_exclusive: u64,
+    ) -> &mut Self {
+        self.header.start_epoch_inclusive = start_epoch_inclusive;
+        self.header.end_epoch_exclusive = end_epoch_exclusive;
+        self
+    }
+
+    /// Sets the transaction nonce.
+    pub fn set_nonce(&mut self, nonce: u64) -> &mut Self {
+        self.header.nonce = nonce;
+        self
+    }
+
+    /// Sets the notary, who must sign the transaction.
+    pub fn set_notary(&mut self, notary: Address) -> &mut Self {
+        self.header.notary = Some(notary);
+        self
+    }
+
     /// Adds a raw instruction.
     pub fn add_instruction(&mut self, inst: Instruction) -> &mut Self {
         self.instructions.push(inst);
         self
     }
 
+    /// Locks XRD from an account for paying the transaction fee.
+    ///
+    /// The fee lock is placed right after the bucket and bucket ref reservations, so that the
+    /// IDs reserved don't depend on the IDs allocated by the fee lock.
+    pub fn lock_fee(&mut self, amount: Decimal, account: Address) -> &mut Self {
+        self.fee_lock = Some(Instruction::LockFee { account, amount });
+        self
+    }
+
     /// Reserves a bucket id.
     pub fn declare_bucket<F>(&mut self, then: F) -> &mut Self
     where
@@ -169,6 +216,33 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         })
     }
 
+    /// Creates a bucket by withdrawing all of a resource from context.
+    pub fn take_all_from_context(&mut self, resource_address: Address, to: Bid) -> &mut Self {
+        self.add_instruction(Instruction::TakeAllFromContext {
+            resource_address,
+            to,
+        })
+    }
+
+    /// Creates a bucket by withdrawing the given NFTs from context.
+    pub fn take_nfts_from_context(
+        &mut self,
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+        to: Bid,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::TakeNftsFromContext {
+            ids,
+            resource_address,
+            to,
+        })
+    }
+
+    /// Returns a bucket to context.
+    pub fn return_to_context(&mut self, bid: Bid) -> &mut Self {
+        self.add_instruction(Instruction::ReturnToContext { bid })
+    }
+
     /// Creates a bucket ref by borrowing resource from context.
     pub fn borrow_from_context(
         &mut self,
@@ -183,6 +257,35 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         })
     }
 
+    /// Asserts that the transaction context holds at least the given amount of a resource.
+    pub fn assert_context_contains(
+        &mut self,
+        amount: Decimal,
+        resource_address: Address,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::AssertContextContains {
+            amount,
+            resource_address,
+        })
+    }
+
+    /// Asserts that the transaction context holds all the given NFTs.
+    pub fn assert_context_contains_nfts(
+        &mut self,
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+    ) -> &mut Self {
+        self.add_instruction(Instruction::AssertContextContainsNfts {
+            ids,
+            resource_address,
+        })
+    }
+
+    /// Asserts that the transaction context holds none of a resource.
+    pub fn assert_context_does_not_contain(&mut self, resource_address: Address) -> &mut Self {
+        self.add_instruction(Instruction::AssertContextDoesNotContain { resource_address })
+    }
+
     /// Calls a function.
     ///
     /// The implementation will automatically prepare the arguments based on the
@@ -199,16 +302,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         account: Option<Address>,
     ) -> &mut Self {
         let result = self
-            .abi_provider
-            .export_abi(package_address, blueprint_name, false)
-            .map_err(|_| {
-                BuildTransactionError::FailedToExportFunctionAbi(
-                    package_address,
-                    blueprint_name.to_owned(),
-                    function.to_owned(),
-                )
-            })
-            .and_then(|abi| Self::find_function_abi(&abi, function))
+            .find_function_abi(package_address, blueprint_name, function)
             .and_then(|f| {
                 self.prepare_args(&f.inputs, args, account)
                     .map_err(|e| BuildTransactionError::FailedToBuildArgs(e))
@@ -229,6 +323,51 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         self
     }
 
+    /// Calls a function with encoded arguments, e.g. `SmartValue::from(5u32)`.
+    ///
+    /// The arguments are checked against the function ABI.
+    pub fn call_function_with_args(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+        function: &str,
+        args: Vec<SmartValue>,
+    ) -> &mut Self {
+        let result = self
+            .find_function_abi(package_address, blueprint_name, function)
+            .and_then(|f| {
+                Self::check_args(&f.inputs, &args).map_err(BuildTransactionError::FailedToBuildArgs)
+            });
+
+        match result {
+            Ok(()) => {
+                self.add_instruction(Instruction::CallFunction {
+                    package_address,
+                    blueprint_name: blueprint_name.to_owned(),
+                    function: function.to_owned(),
+                    args,
+                });
+            }
+            Err(e) => self.errors.push(e),
+        }
+
+        self
+    }
+
+    /// Calls a function with SBOR values as arguments.
+    ///
+    /// The arguments are checked against the function ABI.
+    pub fn call_function_with_values(
+        &mut self,
+        package_address: Address,
+        blueprint_name: &str,
+        function: &str,
+        args: Vec<Value>,
+    ) -> &mut Self {
+        let args = args.iter().map(SmartValue::from_value).collect();
+        self.call_function_with_args(package_address, blueprint_name, function, args)
+    }
+
     /// Calls a method.
     ///
     /// The implementation will automatically prepare the arguments based on the
@@ -244,12 +383,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         account: Option<Address>,
     ) -> &mut Self {
         let result = self
-            .abi_provider
-            .export_abi_component(component_address, false)
-            .map_err(|_| {
-                BuildTransactionError::FailedToExportMethodAbi(component_address, method.to_owned())
-            })
-            .and_then(|abi| Self::find_method_abi(&abi, method))
+            .find_method_abi(component_address, method)
             .and_then(|m| {
                 self.prepare_args(&m.inputs, args, account)
                     .map_err(|e| BuildTransactionError::FailedToBuildArgs(e))
@@ -269,6 +403,53 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         self
     }
 
+    /// Calls a method with encoded arguments, e.g. `SmartValue::from(5u32)`.
+    ///
+    /// The arguments are checked against the method ABI.
+    pub fn call_method_with_args(
+        &mut self,
+        component_address: Address,
+        method: &str,
+        args: Vec<SmartValue>,
+    ) -> &mut Self {
+        let result = self
+            .find_method_abi(component_address, method)
+            .and_then(|m| {
+                Self::check_args(&m.inputs, &args).map_err(BuildTransactionError::FailedToBuildArgs)
+            });
+
+        match result {
+            Ok(()) => {
+                self.add_instruction(Instruction::CallMethod {
+                    component_address,
+                    method: method.to_owned(),
+                    args,
+                });
+            }
+            Err(e) => self.errors.push(e),
+        }
+
+        self
+    }
+
+    /// Calls a method with SBOR values as arguments.
+    ///
+    /// The arguments are checked against the method ABI.
+    pub fn call_method_with_values(
+        &mut self,
+        component_address: Address,
+        method: &str,
+        args: Vec<Value>,
+    ) -> &mut Self {
+        let args = args.iter().map(SmartValue::from_value).collect();
+        self.call_method_with_args(component_address, method, args)
+    }
+
+    /// Runs a partial transaction, which is signed by its own signers.
+    pub fn add_partial_transaction(&mut self, transaction: SignedPartialTransaction) -> &mut Self {
+        self.add_instruction(Instruction::PartialTransaction { transaction })
+    }
+
     /// Drops all bucket refs.
     pub fn drop_all_bucket_refs(&mut self) -> &mut Self {
         self.add_instruction(Instruction::DropAllBucketRefs)
@@ -287,10 +468,39 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
 
         let mut v = Vec::new();
         v.extend(self.reservations.clone());
+        v.extend(self.fee_lock.clone());
         v.extend(self.instructions.clone());
         v.push(Instruction::End { signers });
 
-        Ok(Transaction { instructions: v })
+        Ok(Transaction {
+            header: self.header.clone(),
+            instructions: v,
+        })
+    }
+
+    /// Builds a partial transaction, which is combined with others into a transaction.
+    ///
+    /// The buckets and bucket refs declared are private to the partial transaction.
+    pub fn build_partial(
+        &mut self,
+        signers: Vec<Address>,
+    ) -> Result<PartialTransaction, BuildTransactionError> {
+        if !self.errors.is_empty() {
+            return Err(self.errors[0].clone());
+        }
+        if self.fee_lock.is_some() {
+            return Err(BuildTransactionError::FeeLockInPartialTransaction);
+        }
+
+        let mut v = Vec::new();
+        v.extend(self.reservations.clone());
+        v.extend(self.instructions.clone());
+
+        Ok(PartialTransaction {
+            header: self.header.clone(),
+            instructions: v,
+            signers,
+        })
     }
 
     //===============================
@@ -496,27 +706,63 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
     //===============================
 
     fn find_function_abi(
-        abi: &abi::Blueprint,
+        &self,
+        package_address: Address,
+        blueprint_name: &str,
         function: &str,
     ) -> Result<abi::Function, BuildTransactionError> {
-        abi.functions
+        self.abi_provider
+            .export_abi(package_address, blueprint_name, false)
+            .map_err(|_| {
+                BuildTransactionError::FailedToExportFunctionAbi(
+                    package_address,
+                    blueprint_name.to_owned(),
+                    function.to_owned(),
+                )
+            })?
+            .functions
             .iter()
             .find(|f| f.name == function)
-            .map(Clone::clone)
+            .cloned()
             .ok_or_else(|| BuildTransactionError::FunctionNotFound(function.to_owned()))
     }
 
     fn find_method_abi(
-        abi: &abi::Blueprint,
+        &self,
+        component_address: Address,
         method: &str,
     ) -> Result<abi::Method, BuildTransactionError> {
-        abi.methods
+        self.abi_provider
+            .export_abi_component(component_address, false)
+            .map_err(|_| {
+                BuildTransactionError::FailedToExportMethodAbi(component_address, method.to_owned())
+            })?
+            .methods
             .iter()
             .find(|m| m.name == method)
-            .map(Clone::clone)
+            .cloned()
             .ok_or_else(|| BuildTransactionError::MethodNotFound(method.to_owned()))
     }
 
+    fn check_args(types: &[Type], args: &[SmartValue]) -> Result<(), BuildArgsError> {
+        if args.len() > types.len() {
+            return Err(BuildArgsError::UnexpectedArgument(types.len()));
+        }
+
+        for (i, t) in types.iter().enumerate() {
+            let arg = args
+                .get(i)
+                .ok_or_else(|| BuildArgsError::MissingArgument(i, t.clone()))?;
+            let value = decode_any(&arg.encoded)
+                .map_err(|_| BuildArgsError::MismatchedType(i, t.clone()))?;
+            if !value_matches_type(&value, t) {
+                return Err(BuildArgsError::MismatchedType(i, t.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
     fn prepare_args(
         &mut self,
         types: &[Type],
@@ -543,7 +789,7 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
                 Type::U128 => self.prepare_basic_ty::<u128>(i, t, arg),
                 Type::String => self.prepare_basic_ty::<String>(i, t, arg),
                 Type::Custom { name, .. } => self.prepare_custom_ty(i, t, arg, name, account),
-                _ => Err(BuildArgsError::UnsupportedType(i, t.clone())),
+                _ => self.prepare_composite_ty(i, t, arg),
             };
             encoded.push(res?);
         }
@@ -567,6 +813,21 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
         Ok(SmartValue::from(value))
     }
 
+    /// Parses a composite value written in the manifest value syntax, e.g. `Struct { 1u8 }`.
+    fn prepare_composite_ty(
+        &mut self,
+        i: usize,
+        ty: &Type,
+        arg: &str,
+    ) -> Result<SmartValue, BuildArgsError> {
+        let value = compile_value(arg)
+            .map_err(|_| BuildArgsError::FailedToParse(i, ty.clone(), arg.to_owned()))?;
+        if !value_matches_type(&value, ty) {
+            return Err(BuildArgsError::MismatchedType(i, ty.clone()));
+        }
+        Ok(SmartValue::from_value(&value))
+    }
+
     fn prepare_custom_ty(
         &mut self,
         i: usize,
@@ -619,17 +880,20 @@ impl<'a, A: AbiProvider> TransactionBuilder<'a, A> {
             }
             SCRYPTO_NAME_RID | SCRYPTO_NAME_BUCKET_REF => {
                 let resource_spec = parse_resource_spec(i, ty, arg)?;
-                if let Some(account) = account {
-                    self.withdraw_from_account(&resource_spec, account);
-                }
+                // a public key stands for the virtual badge of the signer, which is never withdrawn
+                let resource_address = match resource_spec.resource_address().signer_badge() {
+                    Some(badge) => badge,
+                    None => {
+                        if let Some(account) = account {
+                            self.withdraw_from_account(&resource_spec, account);
+                        }
+                        resource_spec.resource_address()
+                    }
+                };
                 let mut created_rid = None;
                 self.declare_bucket_ref(|builder, rid| {
                     created_rid = Some(rid);
-                    builder.borrow_from_context(
-                        resource_spec.amount(),
-                        resource_spec.resource_address(),
-                        rid,
-                    )
+                    builder.borrow_from_context(resource_spec.amount(), resource_address, rid)
                 });
                 Ok(SmartValue::from(created_rid.unwrap()))
             }
@@ -13,6 +13,12 @@ pub enum BuildArgsError {
 
     /// Failure when parsing an argument.
     FailedToParse(usize, Type, String),
+
+    /// The argument does not match its type.
+    MismatchedType(usize, Type),
+
+    /// More arguments are provided than expected, the first of which is at the given index.
+    UnexpectedArgument(usize),
 }
 
 /// Represents an error when building a transaction.
@@ -35,4 +41,7 @@ pub enum BuildTransactionError {
 
     /// Account is required but not provided.
     AccountNotProvided,
+
+    /// Fee can't be locked within a partial transaction.
+    FeeLockInPartialTransaction,
 }
@@ -1,5 +1,11 @@
+use sbor::any::*;
+use sbor::rust::boxed::Box;
+use sbor::DecodeError;
 use scrypto::abi;
 use scrypto::args;
+use scrypto::buffer::*;
+use scrypto::rust::collections::*;
+use scrypto::rust::convert::TryFrom;
 use scrypto::rust::string::ToString;
 use scrypto::rust::vec;
 use scrypto::rust::vec::Vec;
@@ -14,14 +20,30 @@ use crate::transaction::*;
 /// An executor that runs transactions.
 pub struct TransactionExecutor<'l, L: Ledger> {
     ledger: &'l mut L,
+    network_id: u8,
     current_epoch: u64,
     nonce: u64,
+    cost_unit_limit: u32,
+    max_call_depth: usize,
+    cost_unit_price: Decimal,
+    call_trace: bool,
+    state_diff: bool,
 }
 
 /// Represents an error when executing the transaction.
 #[derive(Debug)]
 pub enum TransactionExecutionError {
-    MissingEndInstruction,
+    InvalidTransaction(Vec<(usize, TransactionValidationError)>),
+    InvalidSignature,
+    MissingSignature(Address),
+    InvalidNetwork(u8),
+    TransactionNotYetValid,
+    TransactionExpired,
+    DuplicateTransaction(H256),
+    FeeLockFailed(RuntimeError),
+    FeeSettlementFailed(RuntimeError),
+    InvalidPartialSignature(usize),
+    MissingPartialSignature(usize, Address),
 }
 
 impl<'l, L: Ledger> AbiProvider for TransactionExecutor<'l, L> {
@@ -64,8 +86,14 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
     pub fn new(ledger: &'l mut L, current_epoch: u64, nonce: u64) -> Self {
         Self {
             ledger,
+            network_id: DEFAULT_NETWORK_ID,
             current_epoch,
             nonce,
+            cost_unit_limit: DEFAULT_COST_UNIT_LIMIT,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            cost_unit_price: DEFAULT_COST_UNIT_PRICE,
+            call_trace: false,
+            state_diff: false,
         }
     }
 
@@ -74,6 +102,16 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
         self.ledger
     }
 
+    /// Returns the id of the network this executor belongs to.
+    pub fn network_id(&self) -> u8 {
+        self.network_id
+    }
+
+    /// Sets the id of the network this executor belongs to.
+    pub fn set_network_id(&mut self, network_id: u8) {
+        self.network_id = network_id;
+    }
+
     /// Returns the current epoch.
     pub fn current_epoch(&self) -> u64 {
         self.current_epoch
@@ -94,20 +132,73 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
         self.nonce
     }
 
-    /// Generates a new public key.
-    pub fn new_public_key(&mut self) -> Address {
-        let mut raw = [0u8; 33];
-        raw[1..].copy_from_slice(sha256(self.nonce.to_string()).as_ref());
+    /// Returns the maximum number of cost units a transaction may consume.
+    pub fn cost_unit_limit(&self) -> u32 {
+        self.cost_unit_limit
+    }
+
+    /// Sets the maximum number of cost units a transaction may consume.
+    pub fn set_cost_unit_limit(&mut self, cost_unit_limit: u32) {
+        self.cost_unit_limit = cost_unit_limit;
+    }
+
+    /// Returns the maximum depth of nested calls.
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// Sets the maximum depth of nested calls.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    /// Returns the price of a cost unit, in XRD.
+    pub fn cost_unit_price(&self) -> Decimal {
+        self.cost_unit_price
+    }
+
+    /// Sets the price of a cost unit, in XRD.
+    pub fn set_cost_unit_price(&mut self, cost_unit_price: Decimal) {
+        self.cost_unit_price = cost_unit_price;
+    }
+
+    /// Returns whether the call tree of a transaction is recorded in the receipt.
+    pub fn call_trace(&self) -> bool {
+        self.call_trace
+    }
+
+    /// Sets whether the call tree of a transaction is recorded in the receipt.
+    pub fn set_call_trace(&mut self, call_trace: bool) {
+        self.call_trace = call_trace;
+    }
+
+    /// Returns whether the state changes of a transaction are recorded in the receipt.
+    pub fn state_diff(&self) -> bool {
+        self.state_diff
+    }
+
+    /// Sets whether the state changes of a transaction are recorded in the receipt.
+    pub fn set_state_diff(&mut self, state_diff: bool) {
+        self.state_diff = state_diff;
+    }
+
+    /// Generates a new key pair for testing, which is derived from the nonce.
+    ///
+    /// Anyone can derive the same private key, so it must never guard anything of value.
+    pub fn new_key_pair(&mut self) -> (Address, EcdsaPrivateKey) {
+        let private_key =
+            EcdsaPrivateKey::from_bytes(sha256(self.nonce.to_string()).as_ref()).unwrap();
         self.nonce += 1;
-        Address::PublicKey(raw)
+        (private_key.public_key(), private_key)
     }
 
     /// Creates an account with 1,000,000 XRD in balance.
     pub fn new_account(&mut self, key: Address) -> Address {
         let free_xrd_amount = Decimal::from(1_000_000);
 
-        self.run(
+        self.run_system(
             TransactionBuilder::new(self)
+                .set_nonce(self.nonce)
                 .call_method(
                     SYSTEM_COMPONENT,
                     "free_xrd",
@@ -116,7 +207,8 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
                 )
                 .new_account_with_resource(key, free_xrd_amount, RADIX_TOKEN)
                 .build(Vec::new())
-                .unwrap(),
+                .unwrap()
+                .sign([]),
             false,
         )
         .unwrap()
@@ -127,11 +219,13 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
     /// Publishes a package.
     pub fn publish_package(&mut self, code: &[u8]) -> Address {
         let receipt = self
-            .run(
+            .run_system(
                 TransactionBuilder::new(self)
+                    .set_nonce(self.nonce)
                     .publish_package(code)
                     .build(Vec::new())
-                    .unwrap(),
+                    .unwrap()
+                    .sign([]),
                 false,
             )
             .unwrap();
@@ -151,95 +245,174 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
             .put_package(address, Package::new(code.to_vec()));
     }
 
-    /// Executes a transaction.
+    /// Executes a transaction, which must lock a fee.
     pub fn run(
+        &mut self,
+        signed: SignedTransaction,
+        trace: bool,
+    ) -> Result<Receipt, TransactionExecutionError> {
+        Self::validate(&signed.transaction, true)?;
+        let signers = Self::verify_signatures(&signed)?;
+        self.execute(signed.transaction, signers, trace, false)
+    }
+
+    /// Executes a system transaction, which is exempt from locking a fee, e.g. one which funds
+    /// a new account.
+    ///
+    /// This is for the system's own use, never for transactions submitted by users.
+    pub fn run_system(
+        &mut self,
+        signed: SignedTransaction,
+        trace: bool,
+    ) -> Result<Receipt, TransactionExecutionError> {
+        Self::validate(&signed.transaction, false)?;
+        let signers = Self::verify_signatures(&signed)?;
+        self.execute(signed.transaction, signers, trace, false)
+    }
+
+    /// Executes transactions in order, on top of the state updates of the ones before.
+    ///
+    /// The state updates of the batch are committed only if every transaction succeeds,
+    /// in which case the nonce advances once per transaction. Otherwise, nothing is committed
+    /// and the receipts end with the first failed transaction. Fees are not charged either,
+    /// so none of the receipts reports a fee.
+    ///
+    /// If a transaction is rejected, its index is returned along with the error.
+    pub fn run_batch(
+        &mut self,
+        batch: Vec<SignedTransaction>,
+        trace: bool,
+    ) -> Result<Vec<Receipt>, (usize, TransactionExecutionError)> {
+        let mut overlay = OverlayLedger::new(self.ledger);
+        let mut executor = TransactionExecutor {
+            ledger: &mut overlay,
+            network_id: self.network_id,
+            current_epoch: self.current_epoch,
+            nonce: self.nonce,
+            cost_unit_limit: self.cost_unit_limit,
+            max_call_depth: self.max_call_depth,
+            cost_unit_price: self.cost_unit_price,
+            call_trace: self.call_trace,
+            state_diff: self.state_diff,
+        };
+
+        let mut receipts = Vec::new();
+        for (i, signed) in batch.into_iter().enumerate() {
+            let receipt = executor.run(signed, trace).map_err(|e| (i, e))?;
+            let success = receipt.success;
+            receipts.push(receipt);
+            if !success {
+                for receipt in &mut receipts {
+                    receipt.fee = None;
+                }
+                return Ok(receipts);
+            }
+        }
+
+        let nonce = executor.nonce;
+        overlay.commit();
+        self.nonce = nonce;
+        Ok(receipts)
+    }
+
+    /// Executes a transaction without committing its state updates or advancing the nonce.
+    ///
+    /// The receipt always includes the state diff. If `verify_signatures` is false, all
+    /// declared signers are assumed to have signed the transaction.
+    ///
+    /// The header and duplicate checks still apply, so a transaction which has already been
+    /// committed, or is outside its validity window, can't be previewed.
+    pub fn preview(
+        &mut self,
+        signed: SignedTransaction,
+        verify_signatures: bool,
+        trace: bool,
+    ) -> Result<Receipt, TransactionExecutionError> {
+        Self::validate(&signed.transaction, true)?;
+        let signers = if verify_signatures {
+            Self::verify_signatures(&signed)?
+        } else {
+            Self::declared_signers(&signed.transaction).to_vec()
+        };
+        self.execute(signed.transaction, signers, trace, true)
+    }
+
+    fn execute(
         &mut self,
         transaction: Transaction,
+        signers: Vec<Address>,
         trace: bool,
+        preview: bool,
     ) -> Result<Receipt, TransactionExecutionError> {
         #[cfg(not(feature = "alloc"))]
         let now = std::time::Instant::now();
 
-        let signers = if let Some(Instruction::End { signers }) = transaction.instructions.last() {
-            // TODO: check all signer addresses are public key; eventually should be computed from signature.
-            signers.clone()
-        } else {
-            return Err(TransactionExecutionError::MissingEndInstruction);
+        self.verify_header(&transaction.header)?;
+
+        // the same transaction, or partial transaction, can't be committed twice
+        let mut intent_hashes = vec![transaction.hash()];
+        for inst in &transaction.instructions {
+            if let Instruction::PartialTransaction { transaction } = inst {
+                self.verify_header(&transaction.partial.header)?;
+                intent_hashes.push(transaction.partial.hash());
+            }
+        }
+        for (i, intent_hash) in intent_hashes.iter().enumerate() {
+            if self.ledger.has_transaction(*intent_hash) || intent_hashes[..i].contains(intent_hash)
+            {
+                return Err(TransactionExecutionError::DuplicateTransaction(
+                    *intent_hash,
+                ));
+            }
+        }
+
+        // the fee lock, if any, follows the declarations and caps the cost units
+        let fee_lock = match transaction.instructions.iter().find(|inst| {
+            !matches!(
+                inst,
+                Instruction::DeclareTempBucket | Instruction::DeclareTempBucketRef
+            )
+        }) {
+            Some(Instruction::LockFee { account, amount }) => Some((*account, *amount)),
+            _ => None,
+        };
+        let cost_unit_price = self.cost_unit_price;
+        let cost_unit_limit = match fee_lock {
+            Some((_, amount)) => self
+                .cost_unit_limit
+                .min(CostUnitCounter::affordable(amount, cost_unit_price)),
+            None => self.cost_unit_limit,
         };
 
+        // all addresses and UUIDs allocated are derived from the intent hash
+        let transaction_hash = transaction.hash();
         let mut track = Track::new(
             self.ledger,
             self.current_epoch,
-            sha256(self.nonce.to_string()),
+            transaction_hash,
             signers,
+            self.cost_unit_limit,
+            self.max_call_depth,
         );
         let mut proc = track.start_process(trace);
+        if self.call_trace {
+            proc.enable_call_trace();
+        }
 
         let mut results = vec![];
         let mut success = true;
+        let mut fee_reserve = None;
         for inst in &transaction.instructions {
             let res = match inst {
-                Instruction::DeclareTempBucket => {
-                    proc.declare_bucket();
-                    Ok(None)
-                }
-                Instruction::DeclareTempBucketRef => {
-                    proc.declare_bucket_ref();
-                    Ok(None)
-                }
-                Instruction::TakeFromContext {
-                    amount,
-                    resource_address,
-                    to,
-                } => proc
-                    .take_from_context(*amount, *resource_address, *to)
-                    .map(|_| None),
-                Instruction::BorrowFromContext {
-                    amount,
-                    resource_address,
-                    to,
-                } => proc
-                    .borrow_from_context(*amount, *resource_address, *to)
-                    .map(|_| None),
-                Instruction::CallFunction {
-                    package_address,
-                    blueprint_name,
-                    function,
-                    args,
-                } => proc
-                    .call_function(
-                        *package_address,
-                        blueprint_name.as_str(),
-                        function.as_str(),
-                        args.iter().map(|v| v.encoded.clone()).collect(),
-                    )
-                    .map(|rtn| Some(SmartValue { encoded: rtn })),
-                Instruction::CallMethod {
-                    component_address,
-                    method,
-                    args,
-                } => proc
-                    .call_method(
-                        *component_address,
-                        method.as_str(),
-                        args.iter().map(|v| v.encoded.clone()).collect(),
-                    )
-                    .map(|rtn| Some(SmartValue { encoded: rtn })),
-
-                Instruction::DropAllBucketRefs => {
-                    proc.drop_bucket_refs();
+                Instruction::LockFee { account, amount } => {
+                    // a transaction which can't lock its fee is rejected
+                    let reserve = proc
+                        .lock_fee(*account, *amount, cost_unit_limit)
+                        .map_err(TransactionExecutionError::FeeLockFailed)?;
+                    fee_reserve = Some(reserve);
                     Ok(None)
                 }
-                Instruction::DepositAllBuckets { account } => {
-                    let buckets = proc.list_buckets();
-                    if !buckets.is_empty() {
-                        proc.call_method(*account, "deposit_batch", args!(buckets))
-                            .map(|rtn| Some(SmartValue { encoded: rtn }))
-                    } else {
-                        Ok(None)
-                    }
-                }
-                Instruction::End { .. } => proc.check_resource().map(|_| None),
+                _ => Self::execute_instruction(&mut proc, inst),
             };
             success &= res.is_ok();
             results.push(res);
@@ -248,11 +421,68 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
             }
         }
 
+        let call_traces = proc.take_call_traces();
+        let cost_units_consumed = track.cost_unit_counter().consumed();
+
+        // charge fee, even if the transaction failed, in which case everything but the
+        // fee lock is rolled back
+        let fee = match (fee_lock, fee_reserve) {
+            (Some((payer, locked)), Some(reserve)) => {
+                if success {
+                    track.discard_checkpoint();
+                } else {
+                    track.rollback();
+                }
+                Some(
+                    Self::settle_fee(
+                        &mut track,
+                        cost_unit_price,
+                        payer,
+                        locked,
+                        reserve,
+                        cost_units_consumed,
+                        trace,
+                    )
+                    .map_err(TransactionExecutionError::FeeSettlementFailed)?,
+                )
+            }
+            _ => None,
+        };
+
         // commit state updates
-        if success {
+        let commit = success || fee.is_some();
+        let balance_changes = if commit {
+            track.balance_changes()
+        } else {
+            Vec::new()
+        };
+        let state_diff = match (self.state_diff || preview, commit) {
+            (true, true) => Some(track.state_diff()),
+            (true, false) => Some(Vec::new()),
+            (false, _) => None,
+        };
+        if commit && !preview {
             track.commit();
             self.nonce += 1;
         }
+        let logs = track.logs().clone();
+        let events = if success {
+            track.events().to_vec()
+        } else {
+            Vec::new()
+        };
+        let new_entities = if success {
+            track.new_entities().to_vec()
+        } else {
+            Vec::new()
+        };
+        // a transaction which has paid its fee can't be replayed, even if it failed
+        if commit && !preview {
+            for intent_hash in intent_hashes {
+                self.ledger.put_transaction(intent_hash);
+            }
+        }
+
         #[cfg(feature = "alloc")]
         let execution_time = None;
         #[cfg(not(feature = "alloc"))]
@@ -260,15 +490,427 @@ impl<'l, L: Ledger> TransactionExecutor<'l, L> {
 
         Ok(Receipt {
             transaction,
+            transaction_hash,
             success,
             results,
-            logs: track.logs().clone(),
-            new_entities: if success {
-                track.new_entities().to_vec()
-            } else {
-                Vec::new()
-            },
+            logs,
+            events,
+            balance_changes,
+            cost_units_consumed,
+            fee,
+            new_entities,
+            call_traces,
+            state_diff,
             execution_time,
         })
     }
+
+    /// Executes a single instruction, other than a fee lock.
+    fn execute_instruction(
+        proc: &mut Process<L>,
+        inst: &Instruction,
+    ) -> Result<Option<SmartValue>, RuntimeError> {
+        match inst {
+            // fee locks are run by `execute`, and ruled out within partial transactions
+            Instruction::LockFee { .. } => Ok(None),
+            Instruction::DeclareTempBucket => {
+                proc.declare_bucket();
+                Ok(None)
+            }
+            Instruction::DeclareTempBucketRef => {
+                proc.declare_bucket_ref();
+                Ok(None)
+            }
+            Instruction::TakeFromContext {
+                amount,
+                resource_address,
+                to,
+            } => proc
+                .take_from_context(*amount, *resource_address, *to)
+                .map(|_| None),
+            Instruction::TakeAllFromContext {
+                resource_address,
+                to,
+            } => proc
+                .take_all_from_context(*resource_address, *to)
+                .map(|_| None),
+            Instruction::TakeNftsFromContext {
+                ids,
+                resource_address,
+                to,
+            } => proc
+                .take_nfts_from_context(ids, *resource_address, *to)
+                .map(|_| None),
+            Instruction::ReturnToContext { bid } => proc.return_to_context(*bid).map(|_| None),
+            Instruction::BorrowFromContext {
+                amount,
+                resource_address,
+                to,
+            } => proc
+                .borrow_from_context(*amount, *resource_address, *to)
+                .map(|_| None),
+            Instruction::AssertContextContains {
+                amount,
+                resource_address,
+            } => proc
+                .assert_context_contains(*amount, *resource_address)
+                .map(|_| None),
+            Instruction::AssertContextContainsNfts {
+                ids,
+                resource_address,
+            } => proc
+                .assert_context_contains_nfts(ids, *resource_address)
+                .map(|_| None),
+            Instruction::AssertContextDoesNotContain { resource_address } => proc
+                .assert_context_does_not_contain(*resource_address)
+                .map(|_| None),
+            Instruction::CallFunction {
+                package_address,
+                blueprint_name,
+                function,
+                args,
+            } => proc
+                .call_function(
+                    *package_address,
+                    blueprint_name.as_str(),
+                    function.as_str(),
+                    args.iter().map(|v| v.encoded.clone()).collect(),
+                )
+                .map(|rtn| Some(SmartValue { encoded: rtn })),
+            Instruction::CallMethod {
+                component_address,
+                method,
+                args,
+            } => proc
+                .call_method(
+                    *component_address,
+                    method.as_str(),
+                    args.iter().map(|v| v.encoded.clone()).collect(),
+                )
+                .map(|rtn| Some(SmartValue { encoded: rtn })),
+
+            Instruction::DropAllBucketRefs => {
+                proc.drop_bucket_refs();
+                Ok(None)
+            }
+            Instruction::DepositAllBuckets { account } => {
+                let buckets = proc.list_buckets();
+                if !buckets.is_empty() {
+                    proc.call_method(*account, "deposit_batch", args!(buckets))
+                        .map(|rtn| Some(SmartValue { encoded: rtn }))
+                } else {
+                    Ok(None)
+                }
+            }
+            Instruction::PartialTransaction { transaction } => {
+                Self::execute_partial(proc, &transaction.partial)
+            }
+            Instruction::End { .. } => proc.check_resource().map(|_| None),
+        }
+    }
+
+    /// Executes the instructions of a partial transaction, with its signers as the
+    /// transaction signers.
+    ///
+    /// The buckets and bucket refs declared within are numbered from zero, independent of the
+    /// rest of the transaction, and mapped to the IDs reserved at runtime.
+    ///
+    /// The bucket refs still held when the partial transaction ends are dropped, for they
+    /// might be backed by the badges of its signers.
+    ///
+    /// Returns a tuple with an optional output for each instruction of the partial transaction.
+    fn execute_partial(
+        proc: &mut Process<L>,
+        partial: &PartialTransaction,
+    ) -> Result<Option<SmartValue>, RuntimeError> {
+        let rids = proc.list_bucket_refs();
+        let signers = proc.set_transaction_signers(partial.signers.clone());
+        let result = Self::execute_partial_instructions(proc, partial);
+        proc.drop_bucket_refs_except(&rids);
+        proc.set_transaction_signers(signers);
+        result
+    }
+
+    fn execute_partial_instructions(
+        proc: &mut Process<L>,
+        partial: &PartialTransaction,
+    ) -> Result<Option<SmartValue>, RuntimeError> {
+        let mut scope = PartialScope::new();
+        let mut outputs = Vec::new();
+        for inst in &partial.instructions {
+            let output = match inst {
+                Instruction::DeclareTempBucket => {
+                    let bid = scope.allocator.new_bid();
+                    scope.bids.insert(bid, proc.declare_bucket());
+                    None
+                }
+                Instruction::DeclareTempBucketRef => {
+                    let rid = scope.allocator.new_rid();
+                    scope.rids.insert(rid, proc.declare_bucket_ref());
+                    None
+                }
+                _ => {
+                    let inst = scope.remap(inst)?;
+                    match Self::execute_instruction(proc, &inst)? {
+                        Some(output) => {
+                            Some(decode_any(&output.encoded).map_err(RuntimeError::InvalidData)?)
+                        }
+                        None => None,
+                    }
+                }
+            };
+            outputs.push(Value::Option(Box::new(output)));
+        }
+
+        Ok(Some(SmartValue::from_value(&Value::Tuple(outputs))))
+    }
+
+    /// Checks the transaction statically, before its signatures are verified.
+    fn validate(
+        transaction: &Transaction,
+        fee_lock_required: bool,
+    ) -> Result<(), TransactionExecutionError> {
+        let mut validator = TransactionValidator::new();
+        validator.set_fee_lock_required(fee_lock_required);
+        validator
+            .validate(transaction)
+            .map_err(TransactionExecutionError::InvalidTransaction)
+    }
+
+    /// Checks that the transaction is intended for this network and valid in the current epoch.
+    fn verify_header(&self, header: &TransactionHeader) -> Result<(), TransactionExecutionError> {
+        if header.network_id != self.network_id {
+            Err(TransactionExecutionError::InvalidNetwork(header.network_id))
+        } else if self.current_epoch < header.start_epoch_inclusive {
+            Err(TransactionExecutionError::TransactionNotYetValid)
+        } else if self.current_epoch >= header.end_epoch_exclusive {
+            Err(TransactionExecutionError::TransactionExpired)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the signers declared by the `End` instruction of a validated transaction.
+    fn declared_signers(transaction: &Transaction) -> &[Address] {
+        match transaction.instructions.last() {
+            Some(Instruction::End { signers }) => signers,
+            _ => &[],
+        }
+    }
+
+    /// Derives the signers from the signatures, and checks that every signer declared
+    /// by the transaction, as well as the notary, has signed it.
+    fn verify_signatures(
+        signed: &SignedTransaction,
+    ) -> Result<Vec<Address>, TransactionExecutionError> {
+        let hash = signed.transaction.hash();
+        let mut signers = Vec::new();
+        for signature in &signed.signatures {
+            let signer = signature
+                .recover(hash)
+                .ok_or(TransactionExecutionError::InvalidSignature)?;
+            if !signers.contains(&signer) {
+                signers.push(signer);
+            }
+        }
+
+        if let Some(signer) = Self::declared_signers(&signed.transaction)
+            .iter()
+            .chain(&signed.transaction.header.notary)
+            .find(|s| !signers.contains(s))
+        {
+            return Err(TransactionExecutionError::MissingSignature(*signer));
+        }
+
+        for (i, inst) in signed.transaction.instructions.iter().enumerate() {
+            if let Instruction::PartialTransaction { transaction } = inst {
+                Self::verify_partial_signatures(i, transaction)?;
+            }
+        }
+
+        Ok(signers)
+    }
+
+    /// Checks that a partial transaction is signed by all its signers, including the notary,
+    /// and that none of its signatures is for anything else.
+    fn verify_partial_signatures(
+        index: usize,
+        signed: &SignedPartialTransaction,
+    ) -> Result<(), TransactionExecutionError> {
+        let hash = signed.partial.hash();
+        let declared: Vec<Address> = signed
+            .partial
+            .signers
+            .iter()
+            .chain(&signed.partial.header.notary)
+            .copied()
+            .collect();
+
+        let mut signers = Vec::new();
+        for signature in &signed.signatures {
+            match signature.recover(hash) {
+                Some(signer) if declared.contains(&signer) => signers.push(signer),
+                _ => return Err(TransactionExecutionError::InvalidPartialSignature(index)),
+            }
+        }
+
+        match declared.iter().find(|s| !signers.contains(s)) {
+            Some(signer) => Err(TransactionExecutionError::MissingPartialSignature(
+                index, *signer,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Charges the fee for the cost units consumed, up to the amount locked, and refunds
+    /// the rest of the reserve to the payer.
+    ///
+    /// The refund is not charged, but its cost units are capped. If the payer fails to take
+    /// the refund, the whole reserve is charged instead, so the fee is always paid.
+    fn settle_fee(
+        track: &mut Track<L>,
+        cost_unit_price: Decimal,
+        payer: Address,
+        locked: Decimal,
+        mut reserve: Bucket,
+        cost_units_consumed: u32,
+        trace: bool,
+    ) -> Result<FeeSummary, RuntimeError> {
+        let charged = (cost_unit_price * cost_units_consumed).min(locked);
+
+        track.set_cost_unit_limit(cost_units_consumed.saturating_add(MAX_FEE_REFUND_COST_UNITS));
+        let mut proc = track.start_process(trace);
+        let mut fee = reserve.take(charged).map_err(RuntimeError::BucketError)?;
+        if !reserve.amount().is_zero() {
+            if let Err(reserve) = proc.refund_fee(payer, reserve) {
+                fee.put(reserve).map_err(RuntimeError::BucketError)?;
+            }
+        }
+        let charged = fee.amount();
+        proc.collect_fee(fee)?;
+
+        Ok(FeeSummary {
+            payer,
+            cost_unit_price,
+            locked,
+            charged,
+            refunded: locked - charged,
+        })
+    }
+}
+
+/// Maps the bucket and bucket ref IDs of a partial transaction to the ones reserved at runtime.
+///
+/// IDs which are not declared by the partial transaction are not found.
+struct PartialScope {
+    allocator: IdAllocator,
+    bids: HashMap<Bid, Bid>,
+    rids: HashMap<Rid, Rid>,
+}
+
+impl PartialScope {
+    fn new() -> Self {
+        Self {
+            allocator: IdAllocator::new(),
+            bids: HashMap::new(),
+            rids: HashMap::new(),
+        }
+    }
+
+    fn bid(&self, bid: Bid) -> Result<Bid, RuntimeError> {
+        self.bids
+            .get(&bid)
+            .copied()
+            .ok_or(RuntimeError::BucketNotFound(bid))
+    }
+
+    fn rid(&self, rid: Rid) -> Result<Rid, RuntimeError> {
+        self.rids
+            .get(&rid)
+            .copied()
+            .ok_or(RuntimeError::BucketRefNotFound(rid))
+    }
+
+    fn remap(&self, inst: &Instruction) -> Result<Instruction, RuntimeError> {
+        let mut inst = inst.clone();
+        match &mut inst {
+            Instruction::TakeFromContext { to, .. }
+            | Instruction::TakeAllFromContext { to, .. }
+            | Instruction::TakeNftsFromContext { to, .. } => *to = self.bid(*to)?,
+            Instruction::ReturnToContext { bid } => *bid = self.bid(*bid)?,
+            Instruction::BorrowFromContext { to, .. } => *to = self.rid(*to)?,
+            Instruction::CallFunction { args, .. } | Instruction::CallMethod { args, .. } => {
+                for arg in args.iter_mut() {
+                    let value = decode_any(&arg.encoded).map_err(RuntimeError::InvalidData)?;
+                    *arg = SmartValue::from_value(&self.remap_value(value)?);
+                }
+            }
+            _ => {}
+        }
+        Ok(inst)
+    }
+
+    fn remap_value(&self, v: Value) -> Result<Value, RuntimeError> {
+        match v {
+            // struct & enum
+            Value::Struct(fields) => Ok(Value::Struct(self.remap_fields(fields)?)),
+            Value::Enum(index, fields) => Ok(Value::Enum(index, self.remap_fields(fields)?)),
+            // composite types
+            Value::Option(x) => match *x {
+                Some(value) => Ok(Value::Option(Box::new(Some(self.remap_value(value)?)))),
+                None => Ok(Value::Option(Box::new(None))),
+            },
+            Value::Box(value) => Ok(Value::Box(Box::new(self.remap_value(*value)?))),
+            Value::Array(ty, values) => Ok(Value::Array(ty, self.remap_vec(values)?)),
+            Value::Tuple(values) => Ok(Value::Tuple(self.remap_vec(values)?)),
+            Value::Result(x) => match *x {
+                Ok(value) => Ok(Value::Result(Box::new(Ok(self.remap_value(value)?)))),
+                Err(value) => Ok(Value::Result(Box::new(Err(self.remap_value(value)?)))),
+            },
+            // collections
+            Value::Vec(ty, values) => Ok(Value::Vec(ty, self.remap_vec(values)?)),
+            Value::TreeSet(ty, values) => Ok(Value::TreeSet(ty, self.remap_vec(values)?)),
+            Value::HashSet(ty, values) => Ok(Value::HashSet(ty, self.remap_vec(values)?)),
+            Value::TreeMap(ty_k, ty_v, entries) => {
+                Ok(Value::TreeMap(ty_k, ty_v, self.remap_map(entries)?))
+            }
+            Value::HashMap(ty_k, ty_v, entries) => {
+                Ok(Value::HashMap(ty_k, ty_v, self.remap_map(entries)?))
+            }
+            // custom types
+            Value::Custom(SCRYPTO_TYPE_BID, data) => {
+                let bid = Bid::try_from(data.as_slice()).map_err(|_| {
+                    RuntimeError::InvalidData(DecodeError::InvalidCustomData(SCRYPTO_TYPE_BID))
+                })?;
+                Ok(Value::Custom(SCRYPTO_TYPE_BID, self.bid(bid)?.to_vec()))
+            }
+            Value::Custom(SCRYPTO_TYPE_RID, data) => {
+                let rid = Rid::try_from(data.as_slice()).map_err(|_| {
+                    RuntimeError::InvalidData(DecodeError::InvalidCustomData(SCRYPTO_TYPE_RID))
+                })?;
+                Ok(Value::Custom(SCRYPTO_TYPE_RID, self.rid(rid)?.to_vec()))
+            }
+            // primitive and other custom types
+            _ => Ok(v),
+        }
+    }
+
+    fn remap_fields(&self, fields: Fields) -> Result<Fields, RuntimeError> {
+        match fields {
+            Fields::Named(named) => Ok(Fields::Named(self.remap_vec(named)?)),
+            Fields::Unnamed(unnamed) => Ok(Fields::Unnamed(self.remap_vec(unnamed)?)),
+            Fields::Unit => Ok(Fields::Unit),
+        }
+    }
+
+    fn remap_vec(&self, values: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        values.into_iter().map(|v| self.remap_value(v)).collect()
+    }
+
+    fn remap_map(&self, entries: Vec<(Value, Value)>) -> Result<Vec<(Value, Value)>, RuntimeError> {
+        entries
+            .into_iter()
+            .map(|(k, v)| Ok((self.remap_value(k)?, self.remap_value(v)?)))
+            .collect()
+    }
 }
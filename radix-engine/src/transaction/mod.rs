@@ -3,9 +3,19 @@ mod builder;
 mod error;
 mod executor;
 mod model;
+mod signature;
+mod validator;
 
 pub use abi::{AbiProvider, BasicAbiProvider};
 pub use builder::{ParseResourceAmountError, ResourceAmount, TransactionBuilder};
 pub use error::{BuildArgsError, BuildTransactionError};
-pub use executor::TransactionExecutor;
-pub use model::{Instruction, Receipt, SmartValue, Transaction};
+pub use executor::{TransactionExecutionError, TransactionExecutor};
+pub use model::{
+    FeeSummary, Instruction, PartialTransaction, Receipt, SignedPartialTransaction,
+    SignedTransaction, SmartValue, Transaction, TransactionHeader, DEFAULT_NETWORK_ID,
+};
+pub use signature::{EcdsaPrivateKey, EcdsaSignature};
+pub use validator::{
+    TransactionValidationError, TransactionValidator, DEFAULT_MAX_ARG_SIZE,
+    DEFAULT_MAX_INSTRUCTIONS,
+};
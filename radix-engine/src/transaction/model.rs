@@ -1,14 +1,20 @@
 use colored::*;
+use sbor::any::*;
 use sbor::*;
 use scrypto::buffer::*;
 use scrypto::kernel::*;
+use scrypto::rust::collections::*;
 use scrypto::rust::fmt;
+use scrypto::rust::format;
 use scrypto::rust::string::String;
 use scrypto::rust::string::ToString;
 use scrypto::rust::vec::Vec;
 use scrypto::types::*;
+use scrypto::utils::*;
 
 use crate::engine::*;
+use crate::model::*;
+use crate::transaction::*;
 use crate::utils::*;
 
 /// Represents a universally recognizable value.
@@ -23,6 +29,14 @@ impl SmartValue {
             encoded: scrypto_encode(&v),
         }
     }
+
+    pub fn from_value(v: &Value) -> Self {
+        let mut enc = Encoder::with_type(Vec::new());
+        encode_any(None, v, &mut enc);
+        Self {
+            encoded: enc.into(),
+        }
+    }
 }
 
 impl fmt::Debug for SmartValue {
@@ -35,13 +49,129 @@ impl fmt::Debug for SmartValue {
     }
 }
 
-/// A transaction consists a sequence of instructions.
+/// The network id of a local simulator.
+pub const DEFAULT_NETWORK_ID: u8 = 0;
+
+/// The header of a transaction, which controls where and when it may be executed.
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct TransactionHeader {
+    /// The network this transaction is intended for.
+    pub network_id: u8,
+    /// The first epoch in which this transaction is valid.
+    pub start_epoch_inclusive: u64,
+    /// The epoch from which on this transaction has expired.
+    pub end_epoch_exclusive: u64,
+    /// A nonce chosen by the sender, which distinguishes otherwise identical transactions.
+    pub nonce: u64,
+    /// The public key of the notary, who must sign the transaction if present.
+    pub notary: Option<Address>,
+}
+
+impl Default for TransactionHeader {
+    fn default() -> Self {
+        Self {
+            network_id: DEFAULT_NETWORK_ID,
+            start_epoch_inclusive: 0,
+            end_epoch_exclusive: u64::MAX,
+            nonce: 0,
+            notary: None,
+        }
+    }
+}
+
+/// A transaction consists a header and a sequence of instructions.
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct Transaction {
+    pub header: TransactionHeader,
+    pub instructions: Vec<Instruction>,
+}
+
+impl Transaction {
+    /// Returns the hash of the SBOR-encoded transaction, including its header, which is
+    /// what signers sign.
+    pub fn hash(&self) -> H256 {
+        sha256(scrypto_encode(self))
+    }
+
+    /// Signs this transaction with the given private keys.
+    pub fn sign<'a, T: IntoIterator<Item = &'a EcdsaPrivateKey>>(
+        self,
+        private_keys: T,
+    ) -> SignedTransaction {
+        let hash = self.hash();
+        let signatures = private_keys.into_iter().map(|k| k.sign(hash)).collect();
+
+        SignedTransaction {
+            transaction: self,
+            signatures,
+        }
+    }
+
+    /// Combines partial transactions into a transaction, which runs them in order.
+    pub fn combine(
+        header: TransactionHeader,
+        partials: Vec<SignedPartialTransaction>,
+        signers: Vec<Address>,
+    ) -> Self {
+        let mut instructions: Vec<Instruction> = partials
+            .into_iter()
+            .map(|transaction| Instruction::PartialTransaction { transaction })
+            .collect();
+        instructions.push(Instruction::End { signers });
+
+        Self {
+            header,
+            instructions,
+        }
+    }
+}
+
+/// A transaction with the signatures of its signers.
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct SignedTransaction {
+    pub transaction: Transaction,
+    pub signatures: Vec<EcdsaSignature>,
+}
+
+/// A fragment of a transaction, which its signers sign separately from the rest.
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct PartialTransaction {
+    pub header: TransactionHeader,
     pub instructions: Vec<Instruction>,
+    pub signers: Vec<Address>,
+}
+
+impl PartialTransaction {
+    /// Returns the hash of the SBOR-encoded partial transaction, which is what its signers sign.
+    pub fn hash(&self) -> H256 {
+        sha256(scrypto_encode(self))
+    }
+
+    /// Signs this partial transaction with the given private keys.
+    pub fn sign<'a, T: IntoIterator<Item = &'a EcdsaPrivateKey>>(
+        self,
+        private_keys: T,
+    ) -> SignedPartialTransaction {
+        let hash = self.hash();
+        let signatures = private_keys.into_iter().map(|k| k.sign(hash)).collect();
+
+        SignedPartialTransaction {
+            partial: self,
+            signatures,
+        }
+    }
+}
+
+/// A partial transaction with the signatures of its signers.
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct SignedPartialTransaction {
+    pub partial: PartialTransaction,
+    pub signatures: Vec<EcdsaSignature>,
 }
 
 /// Represents an instruction in transaction
+///
+/// New instructions are added to the end, so that the encoding of existing ones stays the same.
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub enum Instruction {
     /// Declares a temporary bucket for later use.
@@ -60,6 +190,8 @@ pub enum Instruction {
     /// Borrows resource from transaction context to a temporary bucket ref.
     ///
     /// A bucket will be created to support the reference and it will stay within the context.
+    ///
+    /// The virtual badges of transaction signers can be borrowed as well.
     BorrowFromContext {
         amount: Decimal,
         resource_address: Address,
@@ -91,21 +223,89 @@ pub enum Instruction {
     /// Deposits all resources from transaction context into the designated account.
     DepositAllBuckets { account: Address },
 
-    /// Marks the end of transaction with signatures.
+    /// Marks the end of transaction with the public keys of its signers.
+    ///
+    /// Each of the signers must provide a signature of the transaction.
     End { signers: Vec<Address> },
+
+    /// Locks XRD from an account for paying the transaction fee.
+    ///
+    /// Required by all but system transactions, and must come right after the bucket and
+    /// bucket ref declarations. Unused fee is refunded when the transaction completes.
+    LockFee { account: Address, amount: Decimal },
+
+    /// Asserts that the transaction context holds at least the given amount of a resource.
+    AssertContextContains {
+        amount: Decimal,
+        resource_address: Address,
+    },
+
+    /// Asserts that the transaction context holds all the given NFTs.
+    AssertContextContainsNfts {
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+    },
+
+    /// Asserts that the transaction context holds none of a resource.
+    AssertContextDoesNotContain { resource_address: Address },
+
+    /// Takes all of a resource from transaction context to a temporary bucket.
+    TakeAllFromContext { resource_address: Address, to: Bid },
+
+    /// Takes the given NFTs from transaction context to a temporary bucket.
+    TakeNftsFromContext {
+        ids: BTreeSet<u128>,
+        resource_address: Address,
+        to: Bid,
+    },
+
+    /// Returns a temporary bucket to transaction context.
+    ReturnToContext { bid: Bid },
+
+    /// Runs a partial transaction, which is signed by its own signers.
+    ///
+    /// Only the virtual badges of its signers are available to its instructions, and they
+    /// can only use the temporary buckets and bucket refs declared within it. Instructions
+    /// which act on the whole transaction context, like `DepositAllBuckets`, are not allowed.
+    PartialTransaction {
+        transaction: SignedPartialTransaction,
+    },
+}
+
+/// Represents the fee paid by a transaction.
+#[derive(Debug, Clone)]
+pub struct FeeSummary {
+    pub payer: Address,
+    pub cost_unit_price: Decimal,
+    pub locked: Decimal,
+    pub charged: Decimal,
+    pub refunded: Decimal,
 }
 
 /// Represents a transaction receipt.
 pub struct Receipt {
     pub transaction: Transaction,
+    pub transaction_hash: H256,
     pub success: bool,
     pub results: Vec<Result<Option<SmartValue>, RuntimeError>>,
     pub logs: Vec<(LogLevel, String)>,
+    pub events: Vec<Event>,
+    pub balance_changes: Vec<ComponentBalanceChanges>,
+    pub cost_units_consumed: u32,
+    pub fee: Option<FeeSummary>,
     pub new_entities: Vec<Address>,
+    pub call_traces: Option<Vec<CallTrace>>,
+    pub state_diff: Option<Vec<SubstateChange>>,
     pub execution_time: Option<u128>,
 }
 
 impl Receipt {
+    /// Returns whether the transaction was committed, which a failed transaction is too if it
+    /// paid its fee.
+    pub fn committed(&self) -> bool {
+        self.success || self.fee.is_some()
+    }
+
     pub fn package(&self, nth: usize) -> Option<Address> {
         self.new_entities
             .iter()
@@ -155,6 +355,13 @@ impl fmt::Debug for Receipt {
             .bold()
         )?;
 
+        write!(
+            f,
+            "\n{} {}",
+            "Transaction Hash:".bold().green(),
+            self.transaction_hash
+        )?;
+
         write!(
             f,
             "\n{} {} ms",
@@ -164,6 +371,22 @@ impl fmt::Debug for Receipt {
                 .unwrap_or(String::from("?"))
         )?;
 
+        write!(
+            f,
+            "\n{} {}",
+            "Cost Units Consumed:".bold().green(),
+            self.cost_units_consumed
+        )?;
+
+        if let Some(fee) = &self.fee {
+            write!(f, "\n{}", "Fee:".bold().green())?;
+            write!(f, "\n├─ Payer: {}", fee.payer)?;
+            write!(f, "\n├─ Cost Unit Price: {}", fee.cost_unit_price)?;
+            write!(f, "\n├─ Locked: {}", fee.locked)?;
+            write!(f, "\n├─ Charged: {}", fee.charged)?;
+            write!(f, "\n└─ Refunded: {}", fee.refunded)?;
+        }
+
         write!(f, "\n{}", "Instructions:".bold().green())?;
         for (i, inst) in self.transaction.instructions.iter().enumerate() {
             write!(
@@ -191,6 +414,60 @@ impl fmt::Debug for Receipt {
             write!(f, "\n{} [{:5}] {}", prefix!(i, self.logs), l, m)?;
         }
 
+        write!(f, "\n{} {}", "Events:".bold().green(), self.events.len())?;
+        for (i, event) in self.events.iter().enumerate() {
+            write!(
+                f,
+                "\n{} {} from {}: {}",
+                prefix!(i, self.events),
+                event.name(),
+                event
+                    .component_address()
+                    .unwrap_or_else(|| event.package_address()),
+                format_data(event.data()).unwrap_or_else(|e| format!("InvalidData({:?})", e))
+            )?;
+        }
+
+        write!(
+            f,
+            "\n{} {}",
+            "Balance Changes:".bold().green(),
+            self.balance_changes.len()
+        )?;
+        for (i, group) in self.balance_changes.iter().enumerate() {
+            write!(
+                f,
+                "\n{} {}",
+                prefix!(i, self.balance_changes),
+                group
+                    .component_address
+                    .map(|a| a.to_string())
+                    .unwrap_or(String::from("?"))
+            )?;
+            let indent = if i == self.balance_changes.len() - 1 {
+                "   "
+            } else {
+                "│  "
+            };
+            for (j, change) in group.changes.iter().enumerate() {
+                write!(
+                    f,
+                    "\n{}{} {:?}: {} {}",
+                    indent,
+                    prefix!(j, group.changes),
+                    change.vid,
+                    change.amount,
+                    change.resource_address
+                )?;
+                if !change.nfts_added.is_empty() {
+                    write!(f, ", +{:?}", change.nfts_added)?;
+                }
+                if !change.nfts_removed.is_empty() {
+                    write!(f, ", -{:?}", change.nfts_removed)?;
+                }
+            }
+        }
+
         write!(
             f,
             "\n{} {}",
@@ -207,6 +484,80 @@ impl fmt::Debug for Receipt {
             write!(f, "\n{} {}: {}", prefix!(i, self.new_entities), ty, address)?;
         }
 
+        if let Some(state_diff) = &self.state_diff {
+            write!(f, "\n{} {}", "State Diff:".bold().green(), state_diff.len())?;
+            for (i, change) in state_diff.iter().enumerate() {
+                write!(
+                    f,
+                    "\n{} {:?}: {} ({} -> {} bytes)",
+                    prefix!(i, state_diff),
+                    change.id,
+                    if change.before.is_some() {
+                        "updated"
+                    } else {
+                        "created"
+                    },
+                    change.before.as_ref().map(Vec::len).unwrap_or(0),
+                    change.after.len()
+                )?;
+            }
+        }
+
+        if let Some(call_traces) = &self.call_traces {
+            write!(f, "\n{}", "Call Trace:".bold().green())?;
+            fmt_call_traces(f, call_traces, "")?;
+        }
+
         Ok(())
     }
 }
+
+/// Formats an argument or return value of a call, which might not be decodable.
+fn format_call_value(value: &Result<Value, DecodeError>) -> String {
+    value
+        .as_ref()
+        .map_err(Clone::clone)
+        .and_then(format_any)
+        .unwrap_or_else(|e| format!("InvalidData({:?})", e))
+}
+
+fn fmt_call_traces(
+    f: &mut fmt::Formatter<'_>,
+    call_traces: &[CallTrace],
+    indent: &str,
+) -> fmt::Result {
+    for (i, trace) in call_traces.iter().enumerate() {
+        let target = match trace.component_address {
+            Some(address) => address.to_string(),
+            None => format!("{}::{}", trace.package_address, trace.export_name),
+        };
+        let args: Vec<String> = trace.args.iter().map(format_call_value).collect();
+        let result = match (&trace.output, &trace.error) {
+            (Some(output), _) => format_call_value(output),
+            (None, Some(error)) => error.red().to_string(),
+            (None, None) => String::from("?"),
+        };
+        write!(
+            f,
+            "\n{}{} {}.{}({}) -> {} [{} kernel calls, {} us]",
+            indent,
+            prefix!(i, call_traces),
+            target,
+            trace.function,
+            args.join(", "),
+            result,
+            trace.kernel_calls.len(),
+            trace
+                .time_elapsed
+                .map(|v| v.to_string())
+                .unwrap_or(String::from("?"))
+        )?;
+        let child_indent = if i == call_traces.len() - 1 {
+            format!("{}   ", indent)
+        } else {
+            format!("{}│  ", indent)
+        };
+        fmt_call_traces(f, &trace.children, &child_indent)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use sbor::*;
+use scrypto::rust::fmt;
+use scrypto::types::*;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+/// A secp256k1 private key, used for signing transactions.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EcdsaPrivateKey(SecretKey);
+
+impl EcdsaPrivateKey {
+    /// Creates a private key from its 32-byte representation.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        SecretKey::from_slice(bytes).ok().map(Self)
+    }
+
+    /// Returns the 32-byte representation of this private key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.secret_bytes()
+    }
+
+    /// Returns the public key, in compressed form.
+    pub fn public_key(&self) -> Address {
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &self.0);
+        Address::PublicKey(public_key.serialize())
+    }
+
+    /// Signs a message hash.
+    pub fn sign(&self, hash: H256) -> EcdsaSignature {
+        let message = Message::from_slice(hash.as_ref()).unwrap();
+        let (recovery_id, compact) = Secp256k1::signing_only()
+            .sign_ecdsa_recoverable(&message, &self.0)
+            .serialize_compact();
+
+        let mut bytes = [0u8; 65];
+        bytes[0] = recovery_id.to_i32() as u8;
+        bytes[1..].copy_from_slice(&compact);
+        EcdsaSignature(bytes)
+    }
+}
+
+impl fmt::Debug for EcdsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EcdsaPrivateKey({})", self.public_key())
+    }
+}
+
+/// A recoverable secp256k1 signature, which consists of a recovery id and 64-byte compact signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TypeId, Encode, Decode)]
+pub struct EcdsaSignature(pub [u8; 65]);
+
+impl EcdsaSignature {
+    /// Recovers the public key of the signer, or `None` if the signature is malformed.
+    ///
+    /// A well-formed signature over a different message yields a different public key.
+    pub fn recover(&self, hash: H256) -> Option<Address> {
+        let message = Message::from_slice(hash.as_ref()).ok()?;
+        let recovery_id = RecoveryId::from_i32(self.0[0].into()).ok()?;
+        let signature = RecoverableSignature::from_compact(&self.0[1..], recovery_id).ok()?;
+        let public_key = Secp256k1::verification_only()
+            .recover_ecdsa(&message, &signature)
+            .ok()?;
+        Some(Address::PublicKey(public_key.serialize()))
+    }
+}
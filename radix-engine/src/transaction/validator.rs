@@ -0,0 +1,794 @@
+use sbor::any::*;
+use sbor::DecodeError;
+use scrypto::buffer::*;
+use scrypto::rust::collections::*;
+use scrypto::rust::convert::TryFrom;
+use scrypto::rust::vec::Vec;
+use scrypto::types::*;
+
+use crate::engine::*;
+use crate::transaction::*;
+use crate::utils::*;
+
+/// The default maximum number of instructions in a transaction.
+pub const DEFAULT_MAX_INSTRUCTIONS: usize = 1024;
+
+/// The default maximum size of a call argument, in bytes.
+pub const DEFAULT_MAX_ARG_SIZE: usize = 4 * 1024 * 1024;
+
+/// Represents a violation found when validating a transaction.
+#[derive(Debug, Clone)]
+pub enum TransactionValidationError {
+    /// The instruction exceeds the maximum number of instructions.
+    TooManyInstructions,
+
+    /// The argument at the given index exceeds the maximum size.
+    ArgumentTooLarge(usize, usize),
+
+    /// The argument at the given index can't be decoded.
+    InvalidArgument(usize, DecodeError),
+
+    /// The address is not a package address.
+    InvalidPackageAddress(Address),
+
+    /// The address is not a component address.
+    InvalidComponentAddress(Address),
+
+    /// The address is not a resource definition address.
+    InvalidResourceAddress(Address),
+
+    /// The address is not a public key.
+    InvalidSignerAddress(Address),
+
+    /// `LockFee` is not placed right after the bucket and bucket ref declarations.
+    MisplacedFeeLock,
+
+    /// The transaction does not lock a fee, though it's required to.
+    MissingFeeLock,
+
+    /// The transaction does not end with an `End` instruction.
+    MissingEndInstruction,
+
+    /// `End` is not the last instruction.
+    EndNotLastInstruction,
+
+    /// `LockFee`, `DropAllBucketRefs`, `DepositAllBuckets`, `End` and partial transactions are
+    /// not allowed within a partial transaction, for they aren't limited to its own resources.
+    InvalidPartialInstruction,
+
+    /// The bucket has not been declared.
+    UndeclaredBucket(Bid),
+
+    /// The bucket is used before any resource is put into it.
+    EmptyBucket(Bid),
+
+    /// The bucket has been filled or used already.
+    BucketAlreadyUsed(Bid),
+
+    /// The bucket ref has not been declared.
+    UndeclaredBucketRef(Rid),
+
+    /// The bucket ref is used before anything is borrowed into it.
+    EmptyBucketRef(Rid),
+
+    /// The bucket ref has been filled or used already.
+    BucketRefAlreadyUsed(Rid),
+
+    /// The bucket ref has been dropped by `DropAllBucketRefs`.
+    BucketRefDropped(Rid),
+}
+
+/// The lifecycle of a temporary bucket or bucket ref.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Declared,
+    Filled,
+    Used,
+    Dropped,
+}
+
+/// A validator that statically checks a transaction before it's executed.
+///
+/// Reserved IDs are numbered in declaration order, the way the builder and the manifest
+/// compiler assign them. This matches the IDs reserved at runtime as long as nothing runs
+/// before the declarations, which is why the fee is locked after them.
+///
+/// Buckets and bucket refs left over when the transaction ends are not checked, because the
+/// transaction fails at runtime in that case, like any other resource leak.
+pub struct TransactionValidator {
+    max_instructions: usize,
+    max_arg_size: usize,
+    fee_lock_required: bool,
+}
+
+impl Default for TransactionValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionValidator {
+    pub fn new() -> Self {
+        Self {
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+            max_arg_size: DEFAULT_MAX_ARG_SIZE,
+            fee_lock_required: true,
+        }
+    }
+
+    /// Returns the maximum number of instructions in a transaction.
+    pub fn max_instructions(&self) -> usize {
+        self.max_instructions
+    }
+
+    /// Sets the maximum number of instructions in a transaction.
+    pub fn set_max_instructions(&mut self, max_instructions: usize) {
+        self.max_instructions = max_instructions;
+    }
+
+    /// Returns the maximum size of a call argument, in bytes.
+    pub fn max_arg_size(&self) -> usize {
+        self.max_arg_size
+    }
+
+    /// Sets the maximum size of a call argument, in bytes.
+    pub fn set_max_arg_size(&mut self, max_arg_size: usize) {
+        self.max_arg_size = max_arg_size;
+    }
+
+    /// Returns whether a transaction must lock a fee.
+    pub fn fee_lock_required(&self) -> bool {
+        self.fee_lock_required
+    }
+
+    /// Sets whether a transaction must lock a fee, which only system transactions are exempt from.
+    pub fn set_fee_lock_required(&mut self, fee_lock_required: bool) {
+        self.fee_lock_required = fee_lock_required;
+    }
+
+    /// Validates a transaction, returning every violation along with the index of the
+    /// instruction at fault.
+    ///
+    /// A missing fee lock is reported at the index where it belongs, and a missing `End` at
+    /// the index past the last instruction.
+    pub fn validate(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<(), Vec<(usize, TransactionValidationError)>> {
+        let mut state = ValidationState::new();
+        state.declarations = Some(
+            transaction
+                .instructions
+                .iter()
+                .take_while(|inst| Self::is_declaration(inst))
+                .count(),
+        );
+
+        for (i, inst) in transaction.instructions.iter().enumerate() {
+            if i == self.max_instructions {
+                state.report(i, TransactionValidationError::TooManyInstructions);
+            }
+            self.validate_instruction(&mut state, i, inst);
+        }
+
+        if self.fee_lock_required && !state.fee_locked {
+            let i = state.declarations.unwrap_or_default();
+            state.report(i, TransactionValidationError::MissingFeeLock);
+        }
+
+        let len = transaction.instructions.len();
+        match transaction
+            .instructions
+            .iter()
+            .position(|inst| matches!(inst, Instruction::End { .. }))
+        {
+            Some(i) if i == len - 1 => {}
+            Some(i) => state.report(i, TransactionValidationError::EndNotLastInstruction),
+            None => state.report(len, TransactionValidationError::MissingEndInstruction),
+        }
+
+        if state.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(state.violations)
+        }
+    }
+
+    fn validate_instruction(&self, state: &mut ValidationState, i: usize, inst: &Instruction) {
+        match inst {
+            Instruction::LockFee { account, .. } => {
+                if state.declarations != Some(i) {
+                    state.report(i, TransactionValidationError::MisplacedFeeLock);
+                }
+                state.fee_locked = true;
+                state.check_component(i, *account);
+            }
+            Instruction::DeclareTempBucket => {
+                state.check_declaration(i);
+                let bid = state.allocator.new_bid();
+                state.buckets.insert(bid, State::Declared);
+            }
+            Instruction::DeclareTempBucketRef => {
+                state.check_declaration(i);
+                let rid = state.allocator.new_rid();
+                state.bucket_refs.insert(rid, State::Declared);
+            }
+            Instruction::TakeFromContext {
+                resource_address,
+                to,
+                ..
+            }
+            | Instruction::TakeAllFromContext {
+                resource_address,
+                to,
+            }
+            | Instruction::TakeNftsFromContext {
+                resource_address,
+                to,
+                ..
+            } => {
+                state.check_resource(i, *resource_address);
+                state.fill_bucket(i, *to);
+            }
+            Instruction::ReturnToContext { bid } => state.use_bucket(i, *bid),
+            Instruction::BorrowFromContext {
+                resource_address,
+                to,
+                ..
+            } => {
+                state.check_resource(i, *resource_address);
+                state.fill_bucket_ref(i, *to);
+            }
+            Instruction::AssertContextContains {
+                resource_address, ..
+            }
+            | Instruction::AssertContextContainsNfts {
+                resource_address, ..
+            }
+            | Instruction::AssertContextDoesNotContain { resource_address } => {
+                state.check_resource(i, *resource_address);
+            }
+            Instruction::CallFunction {
+                package_address,
+                args,
+                ..
+            } => {
+                if !matches!(package_address, Address::Package(_)) {
+                    state.report(
+                        i,
+                        TransactionValidationError::InvalidPackageAddress(*package_address),
+                    );
+                }
+                self.validate_args(state, i, args);
+            }
+            Instruction::CallMethod {
+                component_address,
+                args,
+                ..
+            } => {
+                state.check_component(i, *component_address);
+                self.validate_args(state, i, args);
+            }
+            Instruction::DropAllBucketRefs => {
+                for s in state.bucket_refs.values_mut() {
+                    if *s == State::Filled {
+                        *s = State::Dropped;
+                    }
+                }
+            }
+            Instruction::DepositAllBuckets { account } => state.check_component(i, *account),
+            Instruction::PartialTransaction { transaction } => {
+                let partial = &transaction.partial;
+                for signer in partial.signers.iter().chain(&partial.header.notary) {
+                    state.check_signer(i, *signer);
+                }
+
+                // a partial transaction can only use what it declares
+                let mut scope = ValidationState::new();
+                for inst in &partial.instructions {
+                    if matches!(
+                        inst,
+                        Instruction::LockFee { .. }
+                            | Instruction::DropAllBucketRefs
+                            | Instruction::DepositAllBuckets { .. }
+                            | Instruction::End { .. }
+                            | Instruction::PartialTransaction { .. }
+                    ) {
+                        scope.report(i, TransactionValidationError::InvalidPartialInstruction);
+                    } else {
+                        self.validate_instruction(&mut scope, i, inst);
+                    }
+                }
+                state.violations.extend(scope.violations);
+            }
+            Instruction::End { signers } => {
+                for signer in signers {
+                    state.check_signer(i, *signer);
+                }
+            }
+        }
+    }
+
+    fn is_declaration(inst: &Instruction) -> bool {
+        matches!(
+            inst,
+            Instruction::DeclareTempBucket | Instruction::DeclareTempBucketRef
+        )
+    }
+
+    fn validate_args(&self, state: &mut ValidationState, i: usize, args: &[SmartValue]) {
+        for (j, arg) in args.iter().enumerate() {
+            if arg.encoded.len() > self.max_arg_size {
+                state.report(
+                    i,
+                    TransactionValidationError::ArgumentTooLarge(j, arg.encoded.len()),
+                );
+                continue;
+            }
+
+            match decode_any(&arg.encoded) {
+                Ok(value) => {
+                    if let Err(e) = state.visit(i, &value) {
+                        state.report(i, TransactionValidationError::InvalidArgument(j, e));
+                    }
+                }
+                Err(e) => state.report(i, TransactionValidationError::InvalidArgument(j, e)),
+            }
+        }
+    }
+}
+
+/// What the validator knows about the transaction so far.
+struct ValidationState {
+    /// The number of leading declarations, where the fee lock goes, unless within a partial
+    /// transaction.
+    declarations: Option<usize>,
+    fee_locked: bool,
+    allocator: IdAllocator,
+    buckets: BTreeMap<Bid, State>,
+    bucket_refs: BTreeMap<Rid, State>,
+    violations: Vec<(usize, TransactionValidationError)>,
+}
+
+impl ValidationState {
+    fn new() -> Self {
+        Self {
+            declarations: None,
+            fee_locked: false,
+            allocator: IdAllocator::new(),
+            buckets: BTreeMap::new(),
+            bucket_refs: BTreeMap::new(),
+            violations: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, i: usize, error: TransactionValidationError) {
+        self.violations.push((i, error));
+    }
+
+    fn check_declaration(&mut self, i: usize) {
+        if self.fee_locked {
+            self.report(i, TransactionValidationError::MisplacedFeeLock);
+        }
+    }
+
+    fn check_component(&mut self, i: usize, address: Address) {
+        if !matches!(address, Address::Component(_)) {
+            self.report(
+                i,
+                TransactionValidationError::InvalidComponentAddress(address),
+            );
+        }
+    }
+
+    fn check_resource(&mut self, i: usize, address: Address) {
+        if !matches!(address, Address::ResourceDef(_)) {
+            self.report(
+                i,
+                TransactionValidationError::InvalidResourceAddress(address),
+            );
+        }
+    }
+
+    fn check_signer(&mut self, i: usize, address: Address) {
+        if !matches!(address, Address::PublicKey(_)) {
+            self.report(i, TransactionValidationError::InvalidSignerAddress(address));
+        }
+    }
+
+    fn fill_bucket(&mut self, i: usize, bid: Bid) {
+        match self.buckets.get_mut(&bid) {
+            Some(s @ State::Declared) => *s = State::Filled,
+            Some(_) => self.report(i, TransactionValidationError::BucketAlreadyUsed(bid)),
+            None => self.report(i, TransactionValidationError::UndeclaredBucket(bid)),
+        }
+    }
+
+    fn use_bucket(&mut self, i: usize, bid: Bid) {
+        match self.buckets.get_mut(&bid) {
+            Some(s @ State::Filled) => *s = State::Used,
+            Some(State::Declared) => self.report(i, TransactionValidationError::EmptyBucket(bid)),
+            Some(_) => self.report(i, TransactionValidationError::BucketAlreadyUsed(bid)),
+            None => self.report(i, TransactionValidationError::UndeclaredBucket(bid)),
+        }
+    }
+
+    fn fill_bucket_ref(&mut self, i: usize, rid: Rid) {
+        match self.bucket_refs.get_mut(&rid) {
+            Some(s @ State::Declared) => *s = State::Filled,
+            Some(_) => self.report(i, TransactionValidationError::BucketRefAlreadyUsed(rid)),
+            None => self.report(i, TransactionValidationError::UndeclaredBucketRef(rid)),
+        }
+    }
+
+    fn use_bucket_ref(&mut self, i: usize, rid: Rid) {
+        match self.bucket_refs.get_mut(&rid) {
+            Some(s @ State::Filled) => *s = State::Used,
+            Some(State::Declared) => {
+                self.report(i, TransactionValidationError::EmptyBucketRef(rid))
+            }
+            Some(State::Dropped) => {
+                self.report(i, TransactionValidationError::BucketRefDropped(rid))
+            }
+            Some(_) => self.report(i, TransactionValidationError::BucketRefAlreadyUsed(rid)),
+            None => self.report(i, TransactionValidationError::UndeclaredBucketRef(rid)),
+        }
+    }
+
+    /// Checks the custom data within a call argument, using up the buckets and bucket refs
+    /// it contains.
+    fn visit(&mut self, i: usize, v: &Value) -> Result<(), DecodeError> {
+        match v {
+            // primitive types
+            Value::Unit
+            | Value::Bool(_)
+            | Value::I8(_)
+            | Value::I16(_)
+            | Value::I32(_)
+            | Value::I64(_)
+            | Value::I128(_)
+            | Value::U8(_)
+            | Value::U16(_)
+            | Value::U32(_)
+            | Value::U64(_)
+            | Value::U128(_)
+            | Value::String(_) => Ok(()),
+            // struct & enum
+            Value::Struct(fields) | Value::Enum(_, fields) => match fields {
+                Fields::Named(values) | Fields::Unnamed(values) => self.visit_vec(i, values),
+                Fields::Unit => Ok(()),
+            },
+            // composite types
+            Value::Option(x) => match x.as_ref() {
+                Some(value) => self.visit(i, value),
+                None => Ok(()),
+            },
+            Value::Box(value) => self.visit(i, value),
+            Value::Array(_, values) | Value::Tuple(values) => self.visit_vec(i, values),
+            Value::Result(x) => match x.as_ref() {
+                Ok(value) | Err(value) => self.visit(i, value),
+            },
+            // collections
+            Value::Vec(_, values) | Value::TreeSet(_, values) | Value::HashSet(_, values) => {
+                self.visit_vec(i, values)
+            }
+            Value::TreeMap(_, _, entries) | Value::HashMap(_, _, entries) => {
+                for (k, v) in entries {
+                    self.visit(i, k)?;
+                    self.visit(i, v)?;
+                }
+                Ok(())
+            }
+            // custom types
+            Value::Custom(ty, data) => self.visit_custom(i, *ty, data),
+        }
+    }
+
+    fn visit_vec(&mut self, i: usize, values: &[Value]) -> Result<(), DecodeError> {
+        for v in values {
+            self.visit(i, v)?;
+        }
+        Ok(())
+    }
+
+    fn visit_custom(&mut self, i: usize, ty: u8, data: &[u8]) -> Result<(), DecodeError> {
+        let valid = match ty {
+            SCRYPTO_TYPE_BID => match Bid::try_from(data) {
+                Ok(bid) => {
+                    self.use_bucket(i, bid);
+                    true
+                }
+                Err(_) => false,
+            },
+            SCRYPTO_TYPE_RID => match Rid::try_from(data) {
+                Ok(rid) => {
+                    self.use_bucket_ref(i, rid);
+                    true
+                }
+                Err(_) => false,
+            },
+            _ => custom_value_is_valid(ty, data),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(DecodeError::InvalidCustomData(ty))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scrypto::rust::vec;
+
+    fn validate(instructions: Vec<Instruction>) -> Vec<(usize, TransactionValidationError)> {
+        let mut validator = TransactionValidator::new();
+        validator.set_fee_lock_required(false);
+        validate_with(&validator, instructions)
+    }
+
+    fn validate_with(
+        validator: &TransactionValidator,
+        instructions: Vec<Instruction>,
+    ) -> Vec<(usize, TransactionValidationError)> {
+        let transaction = Transaction {
+            header: TransactionHeader::default(),
+            instructions,
+        };
+        match validator.validate(&transaction) {
+            Ok(()) => Vec::new(),
+            Err(violations) => violations,
+        }
+    }
+
+    fn take(bid: Bid) -> Instruction {
+        Instruction::TakeFromContext {
+            amount: 1.into(),
+            resource_address: RADIX_TOKEN,
+            to: bid,
+        }
+    }
+
+    fn borrow(rid: Rid) -> Instruction {
+        Instruction::BorrowFromContext {
+            amount: 1.into(),
+            resource_address: RADIX_TOKEN,
+            to: rid,
+        }
+    }
+
+    fn call(args: Vec<SmartValue>) -> Instruction {
+        Instruction::CallMethod {
+            component_address: SYSTEM_COMPONENT,
+            method: "test".to_owned(),
+            args,
+        }
+    }
+
+    fn end() -> Instruction {
+        Instruction::End {
+            signers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_valid_transaction() {
+        let violations = validate(vec![
+            Instruction::DeclareTempBucket,
+            Instruction::DeclareTempBucketRef,
+            take(Bid(0)),
+            borrow(Rid(1)),
+            call(vec![SmartValue::from(Bid(0)), SmartValue::from(Rid(1))]),
+            Instruction::DropAllBucketRefs,
+            end(),
+        ]);
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_bucket_lifecycle() {
+        let violations = validate(vec![
+            Instruction::DeclareTempBucket,
+            call(vec![SmartValue::from(Bid(0))]),
+            take(Bid(0)),
+            take(Bid(0)),
+            call(vec![SmartValue::from(Bid(0))]),
+            Instruction::ReturnToContext { bid: Bid(0) },
+            take(Bid(1)),
+            Instruction::DeclareTempBucket,
+            take(Bid(1)),
+            end(),
+        ]);
+        assert!(matches!(
+            violations.as_slice(),
+            [
+                (1, TransactionValidationError::EmptyBucket(Bid(0))),
+                (3, TransactionValidationError::BucketAlreadyUsed(Bid(0))),
+                (5, TransactionValidationError::BucketAlreadyUsed(Bid(0))),
+                (6, TransactionValidationError::UndeclaredBucket(Bid(1))),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_bucket_ref_lifecycle() {
+        let violations = validate(vec![
+            Instruction::DeclareTempBucketRef,
+            Instruction::DeclareTempBucketRef,
+            borrow(Rid(0)),
+            borrow(Rid(1)),
+            call(vec![SmartValue::from(Rid(1))]),
+            Instruction::DropAllBucketRefs,
+            call(vec![SmartValue::from(Rid(0))]),
+            call(vec![SmartValue::from(Rid(1)), SmartValue::from(Rid(2))]),
+            end(),
+        ]);
+        assert!(matches!(
+            violations.as_slice(),
+            [
+                (6, TransactionValidationError::BucketRefDropped(Rid(0))),
+                (7, TransactionValidationError::BucketRefAlreadyUsed(Rid(1))),
+                (7, TransactionValidationError::UndeclaredBucketRef(Rid(2))),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_instruction_order() {
+        let lock_fee = || Instruction::LockFee {
+            account: SYSTEM_COMPONENT,
+            amount: 1.into(),
+        };
+
+        let violations = validate(vec![
+            Instruction::DeclareTempBucket,
+            lock_fee(),
+            take(Bid(0)),
+            Instruction::ReturnToContext { bid: Bid(0) },
+            end(),
+        ]);
+        assert!(violations.is_empty());
+
+        let violations = validate(vec![
+            lock_fee(),
+            Instruction::DeclareTempBucket,
+            end(),
+            lock_fee(),
+        ]);
+        assert!(matches!(
+            violations.as_slice(),
+            [
+                (1, TransactionValidationError::MisplacedFeeLock),
+                (3, TransactionValidationError::MisplacedFeeLock),
+                (2, TransactionValidationError::EndNotLastInstruction),
+            ]
+        ));
+
+        let violations = validate(vec![Instruction::DropAllBucketRefs]);
+        assert!(matches!(
+            violations.as_slice(),
+            [(1, TransactionValidationError::MissingEndInstruction)]
+        ));
+    }
+
+    #[test]
+    fn test_fee_lock_required() {
+        let validator = TransactionValidator::new();
+        let violations = validate_with(
+            &validator,
+            vec![
+                Instruction::LockFee {
+                    account: SYSTEM_COMPONENT,
+                    amount: 1.into(),
+                },
+                end(),
+            ],
+        );
+        assert!(violations.is_empty());
+
+        let violations = validate_with(
+            &validator,
+            vec![
+                Instruction::DeclareTempBucket,
+                take(Bid(0)),
+                Instruction::ReturnToContext { bid: Bid(0) },
+                end(),
+            ],
+        );
+        assert!(matches!(
+            violations.as_slice(),
+            [(1, TransactionValidationError::MissingFeeLock)]
+        ));
+    }
+
+    #[test]
+    fn test_addresses_and_args() {
+        let violations = validate(vec![
+            Instruction::CallFunction {
+                package_address: SYSTEM_COMPONENT,
+                blueprint_name: "Test".to_owned(),
+                function: "test".to_owned(),
+                args: vec![SmartValue::from(5u8), SmartValue { encoded: vec![255] }],
+            },
+            Instruction::AssertContextDoesNotContain {
+                resource_address: SYSTEM_PACKAGE,
+            },
+            Instruction::End {
+                signers: vec![RADIX_TOKEN],
+            },
+        ]);
+        assert!(matches!(
+            violations.as_slice(),
+            [
+                (0, TransactionValidationError::InvalidPackageAddress(_)),
+                (0, TransactionValidationError::InvalidArgument(1, _)),
+                (1, TransactionValidationError::InvalidResourceAddress(_)),
+                (2, TransactionValidationError::InvalidSignerAddress(_)),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_partial_transaction() {
+        let partial = PartialTransaction {
+            header: TransactionHeader::default(),
+            instructions: vec![
+                Instruction::DeclareTempBucket,
+                take(Bid(0)),
+                call(vec![SmartValue::from(Bid(1))]),
+                Instruction::DepositAllBuckets {
+                    account: SYSTEM_COMPONENT,
+                },
+                end(),
+            ],
+            signers: vec![RADIX_TOKEN],
+        };
+        let violations = validate(vec![
+            Instruction::DeclareTempBucket,
+            Instruction::DeclareTempBucket,
+            take(Bid(1)),
+            Instruction::PartialTransaction {
+                transaction: SignedPartialTransaction {
+                    partial,
+                    signatures: Vec::new(),
+                },
+            },
+            call(vec![SmartValue::from(Bid(1))]),
+            end(),
+        ]);
+        assert!(matches!(
+            violations.as_slice(),
+            [
+                (3, TransactionValidationError::InvalidSignerAddress(_)),
+                (3, TransactionValidationError::UndeclaredBucket(Bid(1))),
+                (3, TransactionValidationError::InvalidPartialInstruction),
+                (3, TransactionValidationError::InvalidPartialInstruction),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let mut validator = TransactionValidator::new();
+        validator.set_max_instructions(2);
+        validator.set_max_arg_size(4);
+        validator.set_fee_lock_required(false);
+        let transaction = Transaction {
+            header: TransactionHeader::default(),
+            instructions: vec![
+                call(vec![SmartValue::from(1u8), SmartValue::from(1u128)]),
+                Instruction::DropAllBucketRefs,
+                end(),
+            ],
+        };
+        assert!(matches!(
+            validator.validate(&transaction).unwrap_err().as_slice(),
+            [
+                (0, TransactionValidationError::ArgumentTooLarge(1, 17)),
+                (2, TransactionValidationError::TooManyInstructions),
+            ]
+        ));
+    }
+}
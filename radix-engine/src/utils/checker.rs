@@ -0,0 +1,215 @@
+use sbor::any::{Fields, Value};
+use sbor::describe::{self, Type};
+use sbor::type_id::*;
+use scrypto::buffer::*;
+use scrypto::rust::borrow::Borrow;
+use scrypto::rust::convert::TryFrom;
+use scrypto::types::*;
+
+/// Checks whether a decoded value conforms to a type, as described in an ABI.
+pub fn value_matches_type(value: &Value, ty: &Type) -> bool {
+    match (value, ty) {
+        // primitive types
+        (Value::Unit, Type::Unit)
+        | (Value::Bool(_), Type::Bool)
+        | (Value::I8(_), Type::I8)
+        | (Value::I16(_), Type::I16)
+        | (Value::I32(_), Type::I32)
+        | (Value::I64(_), Type::I64)
+        | (Value::I128(_), Type::I128)
+        | (Value::U8(_), Type::U8)
+        | (Value::U16(_), Type::U16)
+        | (Value::U32(_), Type::U32)
+        | (Value::U64(_), Type::U64)
+        | (Value::U128(_), Type::U128)
+        | (Value::String(_), Type::String) => true,
+        // struct & enum
+        (Value::Struct(fields), Type::Struct { fields: types, .. }) => {
+            fields_match_types(fields, types)
+        }
+        (Value::Enum(index, fields), Type::Enum { variants, .. }) => matches!(
+            variants.get(*index as usize),
+            Some(variant) if fields_match_types(fields, &variant.fields)
+        ),
+        // rust types
+        (Value::Option(v), Type::Option { value }) => match v.borrow() {
+            Some(x) => value_matches_type(x, value),
+            None => true,
+        },
+        (Value::Box(v), Type::Box { value }) => value_matches_type(v, value),
+        (Value::Array(ty, elements), Type::Array { element, length }) => {
+            elements.len() == *length as usize && elements_match_type(*ty, elements, element)
+        }
+        (Value::Tuple(elements), Type::Tuple { elements: types }) => {
+            values_match_types(elements, types)
+        }
+        (Value::Result(v), Type::Result { okay, error }) => match v.borrow() {
+            Ok(x) => value_matches_type(x, okay),
+            Err(x) => value_matches_type(x, error),
+        },
+        // collections
+        (Value::Vec(ty, elements), Type::Vec { element })
+        | (Value::TreeSet(ty, elements), Type::TreeSet { element })
+        | (Value::HashSet(ty, elements), Type::HashSet { element }) => {
+            elements_match_type(*ty, elements, element)
+        }
+        (Value::TreeMap(key_ty, value_ty, entries), Type::TreeMap { key, value })
+        | (Value::HashMap(key_ty, value_ty, entries), Type::HashMap { key, value }) => {
+            type_id_of(key) == Some(*key_ty)
+                && type_id_of(value) == Some(*value_ty)
+                && entries
+                    .iter()
+                    .all(|(k, v)| value_matches_type(k, key) && value_matches_type(v, value))
+        }
+        // custom types
+        (Value::Tuple(elements), Type::Custom { name, .. }) if name == SCRYPTO_NAME_BLUEPRINT => {
+            matches!(
+                elements.as_slice(),
+                [Value::Custom(SCRYPTO_TYPE_ADDRESS, data), Value::String(_)]
+                    if custom_value_is_valid(SCRYPTO_TYPE_ADDRESS, data)
+            )
+        }
+        (Value::Custom(id, data), Type::Custom { .. }) => {
+            type_id_of(ty) == Some(*id) && custom_value_is_valid(*id, data)
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether the payload of a custom value decodes as its Scrypto type.
+pub fn custom_value_is_valid(ty: u8, data: &[u8]) -> bool {
+    match ty {
+        SCRYPTO_TYPE_DECIMAL => Decimal::try_from(data).is_ok(),
+        SCRYPTO_TYPE_BIG_DECIMAL => BigDecimal::try_from(data).is_ok(),
+        SCRYPTO_TYPE_ADDRESS => Address::try_from(data).is_ok(),
+        SCRYPTO_TYPE_H256 => H256::try_from(data).is_ok(),
+        SCRYPTO_TYPE_MID => Mid::try_from(data).is_ok(),
+        SCRYPTO_TYPE_VID => Vid::try_from(data).is_ok(),
+        SCRYPTO_TYPE_BID => Bid::try_from(data).is_ok(),
+        SCRYPTO_TYPE_RID => Rid::try_from(data).is_ok(),
+        _ => false,
+    }
+}
+
+/// Returns the SBOR type id of values of the given type.
+pub fn type_id_of(ty: &Type) -> Option<u8> {
+    let id = match ty {
+        Type::Unit => TYPE_UNIT,
+        Type::Bool => TYPE_BOOL,
+        Type::I8 => TYPE_I8,
+        Type::I16 => TYPE_I16,
+        Type::I32 => TYPE_I32,
+        Type::I64 => TYPE_I64,
+        Type::I128 => TYPE_I128,
+        Type::U8 => TYPE_U8,
+        Type::U16 => TYPE_U16,
+        Type::U32 => TYPE_U32,
+        Type::U64 => TYPE_U64,
+        Type::U128 => TYPE_U128,
+        Type::String => TYPE_STRING,
+        Type::Option { .. } => TYPE_OPTION,
+        Type::Box { .. } => TYPE_BOX,
+        Type::Array { .. } => TYPE_ARRAY,
+        Type::Tuple { .. } => TYPE_TUPLE,
+        Type::Struct { .. } => TYPE_STRUCT,
+        Type::Enum { .. } => TYPE_ENUM,
+        Type::Result { .. } => TYPE_RESULT,
+        Type::Vec { .. } => TYPE_VEC,
+        Type::TreeSet { .. } => TYPE_TREE_SET,
+        Type::TreeMap { .. } => TYPE_TREE_MAP,
+        Type::HashSet { .. } => TYPE_HASH_SET,
+        Type::HashMap { .. } => TYPE_HASH_MAP,
+        Type::Custom { name, .. } => match name.as_str() {
+            SCRYPTO_NAME_DECIMAL => SCRYPTO_TYPE_DECIMAL,
+            SCRYPTO_NAME_BIG_DECIMAL => SCRYPTO_TYPE_BIG_DECIMAL,
+            SCRYPTO_NAME_ADDRESS
+            | SCRYPTO_NAME_ACCOUNT
+            | SCRYPTO_NAME_PACKAGE
+            | SCRYPTO_NAME_COMPONENT
+            | SCRYPTO_NAME_RESOURCE_DEF => SCRYPTO_TYPE_ADDRESS,
+            SCRYPTO_NAME_H256 => SCRYPTO_TYPE_H256,
+            SCRYPTO_NAME_BID | SCRYPTO_NAME_BUCKET => SCRYPTO_TYPE_BID,
+            SCRYPTO_NAME_RID | SCRYPTO_NAME_BUCKET_REF => SCRYPTO_TYPE_RID,
+            SCRYPTO_NAME_MID | SCRYPTO_NAME_LAZY_MAP => SCRYPTO_TYPE_MID,
+            SCRYPTO_NAME_VID | SCRYPTO_NAME_VAULT => SCRYPTO_TYPE_VID,
+            SCRYPTO_NAME_BLUEPRINT => TYPE_TUPLE,
+            _ => return None,
+        },
+    };
+    Some(id)
+}
+
+fn fields_match_types(fields: &Fields, types: &describe::Fields) -> bool {
+    match (fields, types) {
+        (Fields::Named(values), describe::Fields::Named { named }) => {
+            values.len() == named.len()
+                && values
+                    .iter()
+                    .zip(named)
+                    .all(|(v, (_, t))| value_matches_type(v, t))
+        }
+        (Fields::Unnamed(values), describe::Fields::Unnamed { unnamed }) => {
+            values_match_types(values, unnamed)
+        }
+        (Fields::Unit, describe::Fields::Unit) => true,
+        _ => false,
+    }
+}
+
+fn values_match_types(values: &[Value], types: &[Type]) -> bool {
+    values.len() == types.len()
+        && values
+            .iter()
+            .zip(types)
+            .all(|(v, t)| value_matches_type(v, t))
+}
+
+fn elements_match_type(element_ty: u8, elements: &[Value], ty: &Type) -> bool {
+    type_id_of(ty) == Some(element_ty) && elements.iter().all(|e| value_matches_type(e, ty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sbor::any::decode_any;
+    use sbor::{Describe, Encode};
+    use scrypto::rust::collections::*;
+    use scrypto::rust::string::String;
+    use scrypto::rust::vec;
+    use scrypto::rust::vec::Vec;
+
+    fn matches<T: Encode + Describe>(value: &T, ty: Type) -> bool {
+        value_matches_type(&decode_any(&scrypto_encode(value)).unwrap(), &ty)
+    }
+
+    #[test]
+    fn test_matching_values() {
+        assert!(matches(&5u32, u32::describe()));
+        assert!(matches(
+            &Some(String::from("a")),
+            Option::<String>::describe()
+        ));
+        assert!(matches(&vec![1u8, 2u8], Vec::<u8>::describe()));
+        assert!(matches(&Vec::<u8>::new(), Vec::<u8>::describe()));
+        assert!(matches(
+            &BTreeMap::from([(1u128, Decimal::one())]),
+            BTreeMap::<u128, Decimal>::describe()
+        ));
+        assert!(matches(&(RADIX_TOKEN, 1i8), <(Address, i8)>::describe()));
+        assert!(matches(&Bid(0), Bid::describe()));
+    }
+
+    #[test]
+    fn test_mismatching_values() {
+        assert!(!matches(&5u32, u64::describe()));
+        assert!(!matches(&Some(5u8), Option::<String>::describe()));
+        assert!(!matches(&Vec::<u8>::new(), Vec::<u16>::describe()));
+        assert!(!matches(&[1u8, 2u8], <[u8; 3]>::describe()));
+        assert!(!matches(&Bid(0), Rid::describe()));
+        assert!(!matches(&RADIX_TOKEN, Decimal::describe()));
+        assert!(!value_matches_type(
+            &Value::Custom(SCRYPTO_TYPE_DECIMAL, vec![1]),
+            &Decimal::describe()
+        ));
+    }
+}
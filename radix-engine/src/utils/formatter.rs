@@ -19,6 +19,13 @@ pub fn format_data(data: &[u8]) -> Result<String, DecodeError> {
     format_data_with_ledger(data, &ledger, &mut vaults)
 }
 
+/// Formats any decoded value.
+pub fn format_any(value: &Value) -> Result<String, DecodeError> {
+    let ledger = InMemoryLedger::new();
+    let mut vaults = vec![];
+    format_value(value, &ledger, &mut vaults)
+}
+
 /// Formats any data, using ledger state.
 pub fn format_data_with_ledger<L: Ledger>(
     data: &[u8],
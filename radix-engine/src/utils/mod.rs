@@ -1,5 +1,7 @@
+mod checker;
 mod decoder;
 mod formatter;
 
+pub use checker::{custom_value_is_valid, type_id_of, value_matches_type};
 pub use decoder::decode_data;
-pub use formatter::{format_data, format_data_with_ledger};
+pub use formatter::{format_any, format_data, format_data_with_ledger};
@@ -1 +1,2 @@
 pub mod badge;
+pub mod signer;
@@ -0,0 +1,47 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct SignerTest {
+        owner_badge: Address,
+        token: ResourceDef,
+        treasury: Vault,
+    }
+
+    impl SignerTest {
+        pub fn new(owner: Address) -> Component {
+            let owner_badge = owner.signer_badge().unwrap();
+            let token = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .flags(MINTABLE)
+                .badge(owner_badge, MAY_MINT)
+                .no_initial_supply();
+            let treasury = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .flags(RESTRICTED_TRANSFER)
+                .withdraw_rule(AccessRule::require_amount(2, owner_badge))
+                .initial_supply_fungible(100);
+
+            Self {
+                owner_badge,
+                token,
+                treasury: Vault::with_bucket(treasury),
+            }
+            .instantiate()
+        }
+
+        /// Hands the given bucket ref back to the caller.
+        pub fn pass(auth: BucketRef) -> BucketRef {
+            auth
+        }
+
+        #[auth(owner_badge, keep_auth)]
+        pub fn mint(&self, amount: Decimal) -> Bucket {
+            self.token.mint(amount, auth)
+        }
+
+        /// Withdraws from the treasury, which requires two of the owner badge, of which
+        /// the supply is one.
+        pub fn withdraw(&self, auths: Vec<BucketRef>) -> Bucket {
+            self.treasury.take_with_auths(1, auths)
+        }
+    }
+}
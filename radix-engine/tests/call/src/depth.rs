@@ -0,0 +1,21 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct DepthTest;
+
+    impl DepthTest {
+        pub fn recurse(n: u32) -> u32 {
+            if n == 0 {
+                Context::call_depth()
+            } else {
+                let rtn = call_function(
+                    Context::package_address(),
+                    "DepthTest",
+                    "recurse",
+                    args!(n - 1),
+                );
+                scrypto_decode(&rtn).unwrap()
+            }
+        }
+    }
+}
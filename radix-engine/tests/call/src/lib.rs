@@ -1 +1,3 @@
+pub mod depth;
 pub mod moving;
+pub mod try_call;
@@ -0,0 +1,94 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct TryTest {
+        vault: Vault,
+        deposits: u32,
+    }
+
+    impl TryTest {
+        pub fn deposit(&mut self, bucket: Bucket, fail: bool) {
+            self.vault.put(bucket);
+            self.deposits += 1;
+            if fail {
+                panic!("Deposit rejected");
+            }
+        }
+
+        pub fn deposits(&self) -> u32 {
+            self.deposits
+        }
+
+        pub fn inspect(&self, auth: BucketRef) -> Decimal {
+            let amount = auth.amount();
+            auth.drop();
+            if !amount.is_zero() {
+                panic!("Inspection rejected");
+            }
+            amount
+        }
+
+        pub fn try_deposit(fail: bool) -> Bucket {
+            let bucket = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .initial_supply_fungible(100);
+            let component = TryTest {
+                vault: Vault::new(bucket.resource_def()),
+                deposits: 0,
+            }
+            .instantiate();
+
+            let to_deposit = bucket.take(40);
+            let result = component.call_try::<()>("deposit", args!(to_deposit, fail));
+            let deposits: u32 = component.call("deposits", args!());
+            if fail {
+                // the deposit is rolled back and the bucket is returned
+                assert!(result.is_err());
+                assert_eq!(deposits, 0);
+                bucket.put(to_deposit);
+            } else {
+                assert!(result.is_ok());
+                assert_eq!(deposits, 1);
+            }
+            bucket
+        }
+
+        pub fn try_deposit_unknown_bucket() -> u32 {
+            let bucket = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .initial_supply_fungible(100);
+            let component = TryTest {
+                vault: Vault::with_bucket(bucket),
+                deposits: 0,
+            }
+            .instantiate();
+
+            let unknown = Bucket::from(Bid(u32::MAX));
+            let result = component.call_try::<()>("deposit", args!(unknown, false));
+            assert!(result.is_err());
+
+            // the component is not left locked by the failed call
+            component.call("deposits", args!())
+        }
+
+        pub fn try_inspect_bucket_ref() -> Bucket {
+            let bucket = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .initial_supply_fungible(100);
+            let component = TryTest {
+                vault: Vault::new(bucket.resource_def()),
+                deposits: 0,
+            }
+            .instantiate();
+
+            let auth = bucket.present();
+            let result = component.call_try::<Decimal>("inspect", args!(auth));
+            assert!(result.is_err());
+
+            // the bucket ref is returned to the caller, which still borrows the bucket
+            assert_eq!(auth.amount(), 100.into());
+            auth.drop();
+            bucket
+        }
+    }
+}
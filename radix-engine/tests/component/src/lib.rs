@@ -1 +1,3 @@
 pub mod component;
+pub mod reentrancy;
+pub mod vault_owner;
@@ -0,0 +1,30 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct ReentrancyTest {
+        counter: u32,
+    }
+
+    impl ReentrancyTest {
+        pub fn new(allow_reentrant_reads: bool) -> Component {
+            let component = Self { counter: 0 }.instantiate();
+            if allow_reentrant_reads {
+                component.allow_reentrant_reads();
+            }
+            component
+        }
+
+        pub fn get_counter(&self) -> u32 {
+            self.counter
+        }
+
+        pub fn increase_counter(&mut self) {
+            self.counter += 1;
+        }
+
+        pub fn reenter(&mut self, address: Address, method: String) {
+            call_method(address, &method, vec![]);
+            self.counter += 1;
+        }
+    }
+}
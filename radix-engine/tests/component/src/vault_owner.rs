@@ -0,0 +1,36 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct VaultHolder {
+        vault: Option<Vault>,
+    }
+
+    impl VaultHolder {
+        pub fn with_token() -> Component {
+            let bucket = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .initial_supply_fungible(1000);
+            Self {
+                vault: Some(Vault::with_bucket(bucket)),
+            }
+            .instantiate()
+        }
+
+        pub fn empty() -> Component {
+            Self { vault: None }.instantiate()
+        }
+
+        pub fn hand_over(&mut self, to: Address) {
+            let vault = self.vault.take().unwrap();
+            Component::from(to).call::<()>("receive", args!(vault));
+        }
+
+        pub fn receive(&mut self, vault: Vault) {
+            self.vault = Some(vault);
+        }
+
+        pub fn withdraw(&mut self, amount: Decimal) -> Bucket {
+            self.vault.as_mut().unwrap().take(amount)
+        }
+    }
+}
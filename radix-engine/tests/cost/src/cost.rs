@@ -0,0 +1,26 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct CostTest;
+
+    impl CostTest {
+        pub fn infinite_loop() -> u64 {
+            let mut i: u64 = 0;
+            loop {
+                i = i.wrapping_add(Context::current_epoch());
+                if i == u64::MAX {
+                    break;
+                }
+            }
+            i
+        }
+
+        pub fn bounded_loop(n: u32) -> u64 {
+            let mut sum: u64 = 0;
+            for i in 0..n {
+                sum += i as u64;
+            }
+            sum
+        }
+    }
+}
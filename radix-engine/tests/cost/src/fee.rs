@@ -0,0 +1,25 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct FeePayer {
+        xrd: Vault,
+    }
+
+    impl FeePayer {
+        pub fn new(xrd: Bucket) -> Component {
+            Self {
+                xrd: Vault::with_bucket(xrd),
+            }
+            .instantiate()
+        }
+
+        pub fn withdraw(&mut self, amount: Decimal, resource_address: Address) -> Bucket {
+            assert_eq!(resource_address, RADIX_TOKEN);
+            self.xrd.take(amount)
+        }
+
+        pub fn deposit(&mut self, _bucket: Bucket) {
+            panic!("Deposits are refused");
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use sbor::*;
+use scrypto::prelude::*;
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Swapped {
+    pub amount: Decimal,
+}
+
+impl Event for Swapped {}
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Undeclared {}
+
+impl Event for Undeclared {}
+
+mod mismatched {
+    use sbor::*;
+    use scrypto::prelude::*;
+
+    #[derive(TypeId, Encode, Decode, Describe)]
+    pub struct Swapped {
+        pub amount: u32,
+    }
+
+    impl Event for Swapped {}
+}
+
+blueprint! {
+    #[events(Swapped)]
+    struct EventTest {
+        swaps: u32,
+    }
+
+    impl EventTest {
+        pub fn new() -> Component {
+            Self { swaps: 0 }.instantiate()
+        }
+
+        pub fn swap(&mut self, amount: Decimal) {
+            self.swaps += 1;
+            Swapped { amount }.emit();
+        }
+
+        pub fn swap_and_fail(&mut self, amount: Decimal) {
+            self.swap(amount);
+            panic!("Swap failed");
+        }
+
+        pub fn emit_undeclared(&self) {
+            Undeclared {}.emit();
+        }
+
+        pub fn emit_mismatched(&self) {
+            mismatched::Swapped { amount: 5 }.emit();
+        }
+    }
+}
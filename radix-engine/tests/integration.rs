@@ -1,7 +1,9 @@
 use std::fs;
 use std::process::Command;
 
+use radix_engine::engine::*;
 use radix_engine::ledger::*;
+use radix_engine::model::*;
 use radix_engine::transaction::*;
 use scrypto::prelude::*;
 
@@ -19,15 +21,52 @@ pub fn compile(name: &str) -> Vec<u8> {
     .unwrap()
 }
 
+/// Creates a non-fungible resource with NFTs of the given IDs and deposits them into an account.
+pub fn new_nft_resource(
+    executor: &mut TransactionExecutor<InMemoryLedger>,
+    account: Address,
+    key: Address,
+    sk: &EcdsaPrivateKey,
+    ids: Vec<u128>,
+) -> Address {
+    let entries = ids
+        .into_iter()
+        .map(|id| (id, (scrypto_encode(&()), scrypto_encode(&()))))
+        .collect();
+    let transaction = TransactionBuilder::new(executor)
+        .lock_fee(100.into(), account)
+        .call_function_with_args(
+            SYSTEM_PACKAGE,
+            "System",
+            "new_resource",
+            vec![
+                SmartValue::from(ResourceType::NonFungible),
+                SmartValue::from(HashMap::<String, String>::new()),
+                SmartValue::from(0u16),
+                SmartValue::from(0u16),
+                SmartValue::from(HashMap::<Address, u16>::new()),
+                SmartValue::from(Some(NewSupply::NonFungible { entries })),
+            ],
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([sk]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    receipt.resource_def(0).unwrap()
+}
+
 #[test]
 fn test_package() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("package"));
 
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "PackageTest",
@@ -36,7 +75,8 @@ fn test_package() {
             Some(account),
         )
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, true).unwrap();
     assert!(receipt1.success);
 }
@@ -45,14 +85,16 @@ fn test_package() {
 fn test_context() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("context"));
 
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "ContextTest", "query", vec![], Some(account))
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, true).unwrap();
     assert!(receipt1.success);
 }
@@ -61,12 +103,13 @@ fn test_context() {
 fn test_component() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("component"));
 
     // Create component
     let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "ComponentTest",
@@ -75,7 +118,8 @@ fn test_component() {
             Some(account),
         )
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt1 = executor.run(transaction1, true).unwrap();
     assert!(receipt1.success);
 
@@ -84,6 +128,7 @@ fn test_component() {
 
     // Call functions & methods
     let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "ComponentTest",
@@ -96,20 +141,118 @@ fn test_component() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt2 = executor.run(transaction2, true).unwrap();
     assert!(receipt2.success);
 }
 
+#[test]
+fn test_reentrancy() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("component"));
+
+    // Create components with and without re-entrant reads
+    let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "ReentrancyTest",
+            "new",
+            vec!["false".to_owned()],
+            Some(account),
+        )
+        .call_function(
+            package,
+            "ReentrancyTest",
+            "new",
+            vec!["true".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt1 = executor.run(transaction1, true).unwrap();
+    assert!(receipt1.success);
+    let component1 = receipt1.component(0).unwrap();
+    let component2 = receipt1.component(1).unwrap();
+
+    // Re-entrant calls are rejected by default
+    let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(
+            component1,
+            "reenter",
+            vec![component1.to_string(), "get_counter".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt2 = executor.run(transaction2, true).unwrap();
+    assert!(!receipt2.success);
+    assert!(matches!(
+        receipt2.results.last(),
+        Some(Err(RuntimeError::ReentrantCallNotAllowed(_, _)))
+    ));
+
+    // Re-entrant calls to read-only methods are allowed when opted in
+    let transaction3 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(
+            component2,
+            "reenter",
+            vec![component2.to_string(), "get_counter".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    executor.set_call_trace(true);
+    let receipt3 = executor.run(transaction3, true).unwrap();
+    assert!(receipt3.success);
+    executor.set_call_trace(false);
+
+    // The ABI lookup behind the check isn't traced as a call
+    let call_traces = receipt3.call_traces.unwrap();
+    let reenter = &call_traces[1];
+    assert_eq!(reenter.function, "reenter");
+    assert_eq!(reenter.children.len(), 1);
+    assert_eq!(reenter.children[0].function, "get_counter");
+
+    // Re-entrant calls to mutable methods are always rejected
+    let transaction4 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(
+            component2,
+            "reenter",
+            vec![component2.to_string(), "increase_counter".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt4 = executor.run(transaction4, true).unwrap();
+    assert!(!receipt4.success);
+    assert!(matches!(
+        receipt4.results.last(),
+        Some(Err(RuntimeError::ReentrantCallNotAllowed(_, _)))
+    ));
+}
+
 #[test]
 fn test_lazy_map() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("lazy_map"));
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "LazyMapTest",
@@ -118,7 +261,8 @@ fn test_lazy_map() {
             Some(account),
         )
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     assert!(receipt.success);
 }
@@ -127,11 +271,12 @@ fn test_lazy_map() {
 fn test_resource_def() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("resource_def"));
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "ResourceTest",
@@ -158,12 +303,14 @@ fn test_resource_def() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(receipt.success);
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "ResourceTest",
@@ -174,12 +321,14 @@ fn test_resource_def() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(!receipt.success);
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "ResourceTest",
@@ -190,21 +339,320 @@ fn test_resource_def() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(!receipt.success);
 }
 
+#[test]
+fn test_recall() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, _) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, sk2) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let package = executor.publish_package(&compile("resource_def"));
+
+    for recallable in [true, false] {
+        // Issue tokens to account1
+        let transaction1 = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account2)
+            .call_function(
+                package,
+                "RecallTest",
+                "new",
+                vec![recallable.to_string()],
+                None,
+            )
+            .deposit_all_buckets(account1)
+            .build(vec![key2])
+            .unwrap()
+            .sign([&sk2]);
+        let receipt1 = executor.run(transaction1, true).unwrap();
+        assert!(receipt1.success);
+        let component = receipt1.component(0).unwrap();
+        let vid = receipt1
+            .balance_changes
+            .iter()
+            .find(|c| c.component_address == Some(account1))
+            .unwrap()
+            .changes[0]
+            .vid;
+
+        // Recall some of the tokens, without the consent of account1
+        let transaction2 = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account2)
+            .add_instruction(Instruction::CallMethod {
+                component_address: component,
+                method: "recall".to_owned(),
+                args: vec![SmartValue::from(vid), SmartValue::from(Decimal::from(40))],
+            })
+            .deposit_all_buckets(account2)
+            .build(vec![key2])
+            .unwrap()
+            .sign([&sk2]);
+        let receipt2 = executor.run(transaction2, true).unwrap();
+        assert_eq!(receipt2.success, recallable);
+        if recallable {
+            // The change is attributed to account1, though its component isn't loaded
+            let changes1 = receipt2
+                .balance_changes
+                .iter()
+                .find(|c| c.component_address == Some(account1))
+                .unwrap();
+            assert_eq!(changes1.changes[0].vid, vid);
+            assert_eq!(changes1.changes[0].amount, Decimal::from(-40));
+        }
+        assert_eq!(
+            executor
+                .ledger()
+                .get_vault(vid)
+                .unwrap()
+                .amount(Actor::SuperUser)
+                .unwrap(),
+            Decimal::from(if recallable { 60 } else { 100 })
+        );
+    }
+}
+
+#[test]
+fn test_freeze() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, sk2) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let package = executor.publish_package(&compile("resource_def"));
+
+    // Issue freezable tokens to account1
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account2)
+        .call_function(package, "FreezeTest", "new", vec!["true".to_owned()], None)
+        .deposit_all_buckets(account1)
+        .build(vec![key2])
+        .unwrap()
+        .sign([&sk2]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+    let token = receipt.resource_def(1).unwrap();
+    let vid = receipt
+        .balance_changes
+        .iter()
+        .find(|c| c.component_address == Some(account1))
+        .unwrap()
+        .changes[0]
+        .vid;
+
+    let update_freeze = |executor: &mut TransactionExecutor<InMemoryLedger>, flags: u8| {
+        let transaction = TransactionBuilder::new(executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), account2)
+            .add_instruction(Instruction::CallMethod {
+                component_address: component,
+                method: "freeze".to_owned(),
+                args: vec![SmartValue::from(vid), SmartValue::from(flags)],
+            })
+            .build(vec![key2])
+            .unwrap()
+            .sign([&sk2]);
+        executor.run(transaction, true).unwrap().success
+    };
+    let transfer = |executor: &mut TransactionExecutor<InMemoryLedger>,
+                    from: Address,
+                    sk: &EcdsaPrivateKey,
+                    to: Address| {
+        let transaction = TransactionBuilder::new(executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), from)
+            .call_method(
+                from,
+                "withdraw",
+                vec!["1".to_owned(), token.to_string()],
+                Some(from),
+            )
+            .deposit_all_buckets(to)
+            .build(vec![sk.public_key()])
+            .unwrap()
+            .sign([sk]);
+        executor.run(transaction, true).unwrap().success
+    };
+
+    // Both accounts hold some tokens
+    assert!(transfer(&mut executor, account1, &sk1, account2));
+
+    // A vault frozen for withdrawal still accepts deposits
+    assert!(update_freeze(&mut executor, FREEZE_WITHDRAW));
+    assert!(!transfer(&mut executor, account1, &sk1, account2));
+    assert!(transfer(&mut executor, account2, &sk2, account1));
+
+    // A vault frozen for deposit still allows withdrawal
+    assert!(update_freeze(&mut executor, FREEZE_DEPOSIT));
+    assert!(transfer(&mut executor, account1, &sk1, account2));
+    assert!(!transfer(&mut executor, account2, &sk2, account1));
+
+    // Unfrozen
+    assert!(update_freeze(&mut executor, 0));
+    assert!(transfer(&mut executor, account1, &sk1, account2));
+    assert!(transfer(&mut executor, account2, &sk2, account1));
+
+    // Resources which are not freezable can't be frozen
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account2)
+        .call_function(package, "FreezeTest", "new", vec!["false".to_owned()], None)
+        .deposit_all_buckets(account1)
+        .build(vec![key2])
+        .unwrap()
+        .sign([&sk2]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+    let vid = receipt
+        .balance_changes
+        .iter()
+        .find(|c| c.component_address == Some(account1))
+        .unwrap()
+        .changes[0]
+        .vid;
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account2)
+        .add_instruction(Instruction::CallMethod {
+            component_address: component,
+            method: "freeze".to_owned(),
+            args: vec![SmartValue::from(vid), SmartValue::from(FREEZE_ALL)],
+        })
+        .build(vec![key2])
+        .unwrap()
+        .sign([&sk2]);
+    assert!(!executor.run(transaction, true).unwrap().success);
+}
+
+#[test]
+fn test_access_rule() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("resource_def"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(package, "AccessRuleTest", "new", vec![], None)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    let mut withdraw = |badges: Vec<u8>, tokens: u32| {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account)
+            .add_instruction(Instruction::CallMethod {
+                component_address: component,
+                method: "withdraw".to_owned(),
+                args: vec![
+                    SmartValue::from(Decimal::from(10)),
+                    SmartValue::from(badges),
+                    SmartValue::from(Decimal::from(tokens)),
+                ],
+            })
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        executor.run(transaction, true).unwrap().success
+    };
+
+    // Badge A and two of badges B, C and D
+    assert!(withdraw(vec![0, 1, 2], 0));
+    assert!(withdraw(vec![0, 2, 3], 0));
+    assert!(withdraw(vec![0, 1, 2, 3], 0));
+    assert!(!withdraw(vec![0, 1], 0));
+    assert!(!withdraw(vec![1, 2, 3], 0));
+
+    // At least 100 tokens
+    assert!(withdraw(vec![], 100));
+    assert!(withdraw(vec![1], 200));
+    assert!(!withdraw(vec![], 99));
+    assert!(!withdraw(vec![0, 3], 50));
+
+    // References to the same bucket are counted once
+    let mut withdraw_with_duplicate_ref = |tokens: u32| {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account)
+            .add_instruction(Instruction::CallMethod {
+                component_address: component,
+                method: "withdraw_with_duplicate_ref".to_owned(),
+                args: vec![
+                    SmartValue::from(Decimal::from(10)),
+                    SmartValue::from(Decimal::from(tokens)),
+                ],
+            })
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        executor.run(transaction, true).unwrap().success
+    };
+    assert!(!withdraw_with_duplicate_ref(60));
+    assert!(withdraw_with_duplicate_ref(100));
+
+    // Rules which require none of their rules are rejected
+    for all_of in [true, false] {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account)
+            .call_function(
+                package,
+                "AccessRuleTest",
+                "create_with_empty_rule",
+                vec![all_of.to_string()],
+                Some(account),
+            )
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        let receipt = executor.run(transaction, true).unwrap();
+        assert!(matches!(
+            receipt.results.last(),
+            Some(Err(RuntimeError::InvalidAccessRule))
+        ));
+    }
+
+    // So are withdraw rules which would never be checked
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "AccessRuleTest",
+            "create_with_unrestricted_transfer",
+            vec![],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(matches!(
+        receipt.results.last(),
+        Some(Err(RuntimeError::WithdrawRuleNotEnforced))
+    ));
+}
+
 #[test]
 fn test_bucket() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("bucket"));
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "BucketTest", "combine", vec![], Some(account))
         .call_function(package, "BucketTest", "split", vec![], Some(account))
         .call_function(package, "BucketTest", "borrow", vec![], Some(account))
@@ -227,7 +675,8 @@ fn test_bucket() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     assert!(receipt.success);
 }
@@ -236,11 +685,12 @@ fn test_bucket() {
 fn test_badge() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("badge"));
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "BadgeTest", "combine", vec![], Some(account))
         .call_function(package, "BadgeTest", "split", vec![], Some(account))
         .call_function(package, "BadgeTest", "borrow", vec![], Some(account))
@@ -248,20 +698,442 @@ fn test_badge() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_signature() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (_, sk2) = executor.new_key_pair();
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(
+            account1,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            None,
+        )
+        .deposit_all_buckets(account1)
+        .build(vec![key1])
+        .unwrap();
+
+    // Signed by the declared signer
+    let receipt = executor.run(transaction.clone().sign([&sk1]), true);
+    assert!(receipt.unwrap().success);
+
+    // Signed by someone else
+    let receipt = executor.run(transaction.clone().sign([&sk2]), true);
+    assert!(matches!(
+        receipt,
+        Err(TransactionExecutionError::MissingSignature(k)) if k == key1
+    ));
+
+    // Tampered after signing
+    let mut signed = transaction.clone().sign([&sk1]);
+    signed.transaction.instructions.insert(
+        1,
+        Instruction::CallMethod {
+            component_address: account1,
+            method: "withdraw".to_owned(),
+            args: vec![
+                SmartValue::from(Decimal::from(2)),
+                SmartValue::from(RADIX_TOKEN),
+            ],
+        },
+    );
+    let receipt = executor.run(signed, true);
+    assert!(matches!(
+        receipt,
+        Err(TransactionExecutionError::MissingSignature(k)) if k == key1
+    ));
+
+    // Malformed signature
+    let mut signed = transaction.sign([&sk1]);
+    signed.signatures[0].0[0] = 4;
+    let receipt = executor.run(signed, true);
+    assert!(matches!(
+        receipt,
+        Err(TransactionExecutionError::InvalidSignature)
+    ));
+}
+
+#[test]
+fn test_transaction_header() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 5, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, sk2) = executor.new_key_pair();
+
+    let run = |executor: &mut TransactionExecutor<InMemoryLedger>,
+               network_id: u8,
+               epochs: (u64, u64),
+               notary_sk: &EcdsaPrivateKey| {
+        let transaction = TransactionBuilder::new(executor)
+            .lock_fee(100.into(), account1)
+            .set_network_id(network_id)
+            .set_epochs(epochs.0, epochs.1)
+            .set_notary(notary_sk.public_key())
+            .withdraw_from_account(
+                &ResourceAmount::Fungible {
+                    amount: 1.into(),
+                    resource_address: RADIX_TOKEN,
+                },
+                account1,
+            )
+            .deposit_all_buckets(account1)
+            .build(vec![key1])
+            .unwrap()
+            .sign([&sk1, notary_sk]);
+        executor.run(transaction, false)
+    };
+
+    assert!(
+        run(&mut executor, DEFAULT_NETWORK_ID, (5, 6), &sk2)
+            .unwrap()
+            .success
+    );
+    assert!(matches!(
+        run(&mut executor, 1, (5, 6), &sk2),
+        Err(TransactionExecutionError::InvalidNetwork(1))
+    ));
+    assert!(matches!(
+        run(&mut executor, DEFAULT_NETWORK_ID, (6, 10), &sk2),
+        Err(TransactionExecutionError::TransactionNotYetValid)
+    ));
+    assert!(matches!(
+        run(&mut executor, DEFAULT_NETWORK_ID, (0, 5), &sk2),
+        Err(TransactionExecutionError::TransactionExpired)
+    ));
+
+    // The notary must sign the transaction
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .set_notary(key2)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    assert!(matches!(
+        executor.run(transaction, false),
+        Err(TransactionExecutionError::MissingSignature(key)) if key == key2
+    ));
+}
+
+#[test]
+fn test_transaction_hash() {
+    let mut ledger1 = InMemoryLedger::with_bootstrap();
+    let mut executor1 = TransactionExecutor::new(&mut ledger1, 0, 0);
+    let (key, sk) = executor1.new_key_pair();
+    let account = executor1.new_account(key);
+    let mut ledger2 = InMemoryLedger::with_bootstrap();
+    let mut executor2 = TransactionExecutor::new(&mut ledger2, 0, 0);
+    let (key2, _) = executor2.new_key_pair();
+    executor2.new_account(key2);
+
+    let new_token = |executor: &TransactionExecutor<InMemoryLedger>, supply: i32| {
+        TransactionBuilder::new(executor)
+            .lock_fee(100.into(), account)
+            .new_token_fixed(HashMap::new(), supply.into())
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+    };
+
+    // The same transaction has the same hash on any ledger and allocates the same addresses
+    let transaction = new_token(&executor1, 100);
+    let hash = transaction.hash();
+    let receipt1 = executor1
+        .run(transaction.clone().sign([&sk]), false)
+        .unwrap();
+    let receipt2 = executor2
+        .run(transaction.clone().sign([&sk]), false)
+        .unwrap();
+    assert!(receipt1.success && receipt2.success);
+    assert_eq!(receipt1.transaction_hash, hash);
+    assert_eq!(receipt2.transaction_hash, hash);
+    assert_eq!(receipt1.resource_def(0), receipt2.resource_def(0));
+
+    // The hash doesn't depend on the executor nonce, so a replay is detectable
+    assert_ne!(executor1.nonce(), 0);
+    assert!(matches!(
+        executor1.run(transaction.sign([&sk]), false),
+        Err(TransactionExecutionError::DuplicateTransaction(h)) if h == hash
+    ));
+
+    // A different transaction has a different hash
+    assert_ne!(
+        new_token(&executor2, 200).hash(),
+        new_token(&executor2, 100).hash()
+    );
+}
+
+#[test]
+fn test_duplicate_transaction() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+
+    let new_token = |executor: &TransactionExecutor<InMemoryLedger>, nonce: u64| {
+        TransactionBuilder::new(executor)
+            .set_nonce(nonce)
+            .lock_fee(100.into(), account)
+            .new_token_fixed(HashMap::new(), 100.into())
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk])
+    };
+
+    let transaction = new_token(&executor, 0);
+    let hash = transaction.transaction.hash();
+    assert!(!executor.ledger().has_transaction(hash));
+    assert!(executor.run(transaction.clone(), false).unwrap().success);
+    assert!(executor.ledger().has_transaction(hash));
+    assert!(matches!(
+        executor.run(transaction, false),
+        Err(TransactionExecutionError::DuplicateTransaction(h)) if h == hash
+    ));
+
+    // The same instructions with a different nonce make a different transaction
+    assert!(
+        executor
+            .run(new_token(&executor, 1), false)
+            .unwrap()
+            .success
+    );
+
+    // A failed transaction which has paid its fee can't be replayed either
+    let overdraw = |executor: &TransactionExecutor<InMemoryLedger>| {
+        TransactionBuilder::new(executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(10.into(), account)
+            .call_method(
+                account,
+                "withdraw",
+                vec!["2000000".to_owned(), RADIX_TOKEN.to_string()],
+                Some(account),
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk])
+    };
+    let nonce = executor.nonce();
+    let transaction = overdraw(&executor);
+    let hash = transaction.transaction.hash();
+    let receipt = executor.run(transaction.clone(), false).unwrap();
+    assert!(!receipt.success && receipt.committed());
+    assert!(executor.ledger().has_transaction(hash));
+    assert_eq!(executor.nonce(), nonce + 1);
+    assert!(matches!(
+        executor.run(transaction, false),
+        Err(TransactionExecutionError::DuplicateTransaction(h)) if h == hash
+    ));
+
+    // But it can be retried with the nonce it has advanced
+    let transaction = overdraw(&executor);
+    assert_ne!(transaction.transaction.hash(), hash);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(!receipt.success && receipt.committed());
+    assert_eq!(executor.nonce(), nonce + 2);
+
+    // A transaction rejected for not being able to lock its fee can be retried
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(2_000_000.into(), account)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    assert!(matches!(
+        executor.run(transaction.clone(), false),
+        Err(TransactionExecutionError::FeeLockFailed(_))
+    ));
+    assert!(!executor
+        .ledger()
+        .has_transaction(transaction.transaction.hash()));
+}
+
+#[test]
+fn test_preview() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(
+            account1,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account1),
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key1])
+        .unwrap();
+
+    // Nothing is committed by a preview
+    let nonce = executor.nonce();
+    let receipt1 = executor
+        .preview(transaction.clone().sign([&sk1]), true, false)
+        .unwrap();
+    assert!(receipt1.success);
+    assert_eq!(receipt1.state_diff.unwrap().len(), 3);
+    assert_eq!(executor.nonce(), nonce);
+    assert!(!executor.ledger().has_transaction(transaction.hash()));
+
+    // The signature check can be skipped
+    assert!(matches!(
+        executor.preview(transaction.clone().sign([]), true, false),
+        Err(TransactionExecutionError::MissingSignature(k)) if k == key1
+    ));
+    let receipt2 = executor
+        .preview(transaction.clone().sign([]), false, false)
         .unwrap();
+    assert!(receipt2.success);
+
+    // Running it afterwards has the same effects
+    let receipt3 = executor.run(transaction.sign([&sk1]), false).unwrap();
+    assert!(receipt3.success);
+    assert_eq!(receipt3.transaction_hash, receipt1.transaction_hash);
+    assert_eq!(
+        receipt3.balance_changes.len(),
+        receipt1.balance_changes.len()
+    );
+    assert_eq!(executor.nonce(), nonce + 1);
+}
+
+#[test]
+fn test_signer_badge() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, sk2) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let badge = key1.signer_badge().unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .new_token_mutable(HashMap::new(), badge)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+    let token = receipt.resource_def(0).unwrap();
+
+    // The virtual badge is only available when the transaction is signed by the key
+    for (signer, signer_sk, payer, success) in
+        [(key1, &sk1, account1, true), (key2, &sk2, account2, false)]
+    {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), payer)
+            .mint(10.into(), token, badge)
+            .drop_all_bucket_refs()
+            .deposit_all_buckets(account1)
+            .build(vec![signer])
+            .unwrap()
+            .sign([signer_sk]);
+        let receipt = executor.run(transaction, true).unwrap();
+        assert_eq!(receipt.success, success);
+    }
+
+    // The virtual badge can't be withdrawn from the transaction context
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .declare_bucket(|builder, bid| builder.take_from_context(1.into(), badge, bid))
+        .deposit_all_buckets(account1)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(!receipt.success);
+}
+
+#[test]
+fn test_signer_badge_auth() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, sk2) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let package = executor.publish_package(&compile("badge"));
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_function(package, "SignerTest", "new", vec![key1.to_string()], None)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
     let receipt = executor.run(transaction, true).unwrap();
     assert!(receipt.success);
+    let component = receipt.component(0).unwrap();
+
+    // A public key argument presents the virtual badge of the signer
+    for (signer, signer_sk, payer, success) in
+        [(key1, &sk1, account1, true), (key2, &sk2, account2, false)]
+    {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), payer)
+            .call_method(
+                component,
+                "mint",
+                vec!["10".to_owned(), format!("1,{}", key1)],
+                Some(account1),
+            )
+            .drop_all_bucket_refs()
+            .deposit_all_buckets(account1)
+            .build(vec![signer])
+            .unwrap()
+            .sign([signer_sk]);
+        let receipt = executor.run(transaction, true).unwrap();
+        assert_eq!(receipt.success, success);
+    }
+
+    // Borrowing the virtual badge repeatedly presents it once
+    let badge = key1.signer_badge().unwrap();
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .declare_bucket_ref(|builder, rid1| {
+            builder.declare_bucket_ref(|builder, rid2| {
+                builder
+                    .borrow_from_context(1.into(), badge, rid1)
+                    .borrow_from_context(1.into(), badge, rid2)
+                    .call_method_with_args(
+                        component,
+                        "withdraw",
+                        vec![SmartValue::from(vec![rid1, rid2])],
+                    )
+            })
+        })
+        .deposit_all_buckets(account1)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(!receipt.success);
 }
 
 #[test]
 fn test_call() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("call"));
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(package, "MoveTest", "move_bucket", vec![], Some(account))
         .call_function(
             package,
@@ -273,20 +1145,212 @@ fn test_call() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     assert!(receipt.success);
 }
 
+#[test]
+fn test_call_try() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("call"));
+
+    for (fail, returned) in [(false, 60), (true, 100)] {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account)
+            .call_function(
+                package,
+                "TryTest",
+                "try_deposit",
+                vec![fail.to_string()],
+                Some(account),
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        let receipt = executor.run(transaction, true).unwrap();
+        assert!(receipt.success);
+        let changes = receipt
+            .balance_changes
+            .iter()
+            .find(|c| c.component_address == Some(account))
+            .unwrap();
+        let token_change = changes
+            .changes
+            .iter()
+            .find(|change| change.resource_address != RADIX_TOKEN)
+            .unwrap();
+        assert_eq!(token_change.amount, Decimal::from(returned));
+    }
+
+    // A call which fails while moving its arguments doesn't leave the component locked
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "TryTest",
+            "try_deposit_unknown_bucket",
+            vec![],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "TryTest",
+            "try_inspect_bucket_ref",
+            vec![],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+}
+
+#[test]
+fn test_track_checkpoint() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut track = Track::new(
+        &mut ledger,
+        0,
+        H256([0u8; 32]),
+        Vec::new(),
+        u32::MAX,
+        DEFAULT_MAX_CALL_DEPTH,
+    );
+    let set_state = |track: &mut Track<InMemoryLedger>, state: u8| {
+        track
+            .get_component_mut(SYSTEM_COMPONENT)
+            .unwrap()
+            .set_state(vec![state], Actor::SuperUser)
+            .unwrap();
+    };
+    let get_state = |track: &mut Track<InMemoryLedger>| {
+        track
+            .get_component(SYSTEM_COMPONENT)
+            .unwrap()
+            .state(Actor::SuperUser)
+            .unwrap()[0]
+    };
+
+    set_state(&mut track, 1);
+    track.checkpoint();
+    set_state(&mut track, 2);
+    track.checkpoint();
+    set_state(&mut track, 3);
+    let component = track.new_component_address();
+    track.put_component(
+        component,
+        radix_engine::model::Component::new(SYSTEM_PACKAGE, "System".to_owned(), Vec::new()),
+    );
+
+    // Rolling back the inner checkpoint reverts the updates made after it
+    track.rollback();
+    assert_eq!(get_state(&mut track), 2);
+    assert!(track.get_component(component).is_none());
+    assert!(track.new_entities().is_empty());
+
+    // Discarding the outer checkpoint keeps the updates made after it
+    track.discard_checkpoint();
+    assert_eq!(get_state(&mut track), 2);
+
+    // Rolling back a checkpoint also reverts the updates kept from the ones nested in it
+    track.checkpoint();
+    track.checkpoint();
+    set_state(&mut track, 4);
+    track.discard_checkpoint();
+    track.rollback();
+    assert_eq!(get_state(&mut track), 2);
+    track.commit();
+    assert_eq!(
+        ledger
+            .get_component(SYSTEM_COMPONENT)
+            .unwrap()
+            .state(Actor::SuperUser)
+            .unwrap(),
+        &[2]
+    );
+}
+
+#[test]
+fn test_call_depth() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("call"));
+
+    let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "DepthTest",
+            "recurse",
+            vec!["3".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt1 = executor.run(transaction1, true).unwrap();
+    assert!(receipt1.success);
+    let depth: u32 = scrypto_decode(
+        &receipt1.results[1]
+            .as_ref()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .encoded,
+    )
+    .unwrap();
+    assert_eq!(depth, 4);
+
+    executor.set_max_call_depth(3);
+    let transaction2 = TransactionBuilder::new(&executor)
+        .set_nonce(1)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "DepthTest",
+            "recurse",
+            vec!["3".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt2 = executor.run(transaction2, true).unwrap();
+    assert!(!receipt2.success);
+    assert!(matches!(
+        receipt2.results.last(),
+        Some(Err(RuntimeError::MaxCallDepthExceeded(3)))
+    ));
+}
+
 #[test]
 fn test_nft() {
     let mut ledger = InMemoryLedger::with_bootstrap();
     let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
-    let key = executor.new_public_key();
+    let (key, sk) = executor.new_key_pair();
     let account = executor.new_account(key);
     let package = executor.publish_package(&compile("nft"));
 
     let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
         .call_function(
             package,
             "NftTest",
@@ -340,8 +1404,1194 @@ fn test_nft() {
         .drop_all_bucket_refs()
         .deposit_all_buckets(account)
         .build(vec![key])
-        .unwrap();
+        .unwrap()
+        .sign([&sk]);
     let receipt = executor.run(transaction, true).unwrap();
     println!("{:?}", receipt);
     assert!(receipt.success);
 }
+
+#[test]
+fn test_event() {
+    #[derive(sbor::TypeId, sbor::Decode)]
+    struct Swapped {
+        amount: Decimal,
+    }
+
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("event"));
+
+    // The event schema is part of the ABI
+    let abi = executor.export_abi(package, "EventTest", false).unwrap();
+    assert_eq!(abi.events.len(), 1);
+    assert_eq!(abi.events[0].name, "Swapped");
+
+    let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(package, "EventTest", "new", vec![], Some(account))
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt1 = executor.run(transaction1, true).unwrap();
+    assert!(receipt1.success);
+    let component = receipt1.component(0).unwrap();
+
+    // Events are recorded with the emitting component and package
+    let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(component, "swap", vec!["5".to_owned()], Some(account))
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt2 = executor.run(transaction2, true).unwrap();
+    assert!(receipt2.success);
+    assert_eq!(receipt2.events.len(), 1);
+    assert_eq!(receipt2.events[0].name(), "Swapped");
+    assert_eq!(receipt2.events[0].package_address(), package);
+    assert_eq!(receipt2.events[0].component_address(), Some(component));
+    let swapped: Swapped = scrypto_decode(receipt2.events[0].data()).unwrap();
+    assert_eq!(swapped.amount, 5.into());
+
+    // Looking up the ABI to check an event isn't traced as a call
+    executor.set_call_trace(true);
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(component, "swap", vec!["5".to_owned()], Some(account))
+        .call_method(component, "swap", vec!["6".to_owned()], Some(account))
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+    assert_eq!(receipt.events.len(), 2);
+    let call_traces = receipt.call_traces.unwrap();
+    assert_eq!(call_traces.len(), 3);
+    assert!(call_traces[1..].iter().all(|t| t.children.is_empty()));
+    executor.set_call_trace(false);
+
+    // Events of failed transactions are discarded
+    let transaction3 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(
+            component,
+            "swap_and_fail",
+            vec!["5".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt3 = executor.run(transaction3, true).unwrap();
+    assert!(!receipt3.success);
+    assert!(receipt3.events.is_empty());
+
+    // Events must be declared in the ABI and match their schema
+    let mut emit = |method: &str| {
+        let transaction = TransactionBuilder::new(&executor)
+            .lock_fee(100.into(), account)
+            .call_method(component, method, vec![], Some(account))
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        executor.run(transaction, true).unwrap()
+    };
+    let receipt4 = emit("emit_undeclared");
+    assert!(!receipt4.success);
+    assert!(matches!(
+        receipt4.results.last(),
+        Some(Err(RuntimeError::EventNotDeclared(name))) if name == "Undeclared"
+    ));
+    let receipt5 = emit("emit_mismatched");
+    assert!(!receipt5.success);
+    assert!(matches!(
+        receipt5.results.last(),
+        Some(Err(RuntimeError::InvalidEventData(name))) if name == "Swapped"
+    ));
+}
+
+#[test]
+fn test_cost_limit() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let package = executor.publish_package(&compile("cost"));
+
+    let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(
+            package,
+            "CostTest",
+            "bounded_loop",
+            vec!["100".to_owned()],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt1 = executor.run(transaction1, true).unwrap();
+    assert!(receipt1.success);
+    assert!(receipt1.cost_units_consumed > 0);
+
+    executor.set_cost_unit_limit(1_000_000);
+    let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_function(package, "CostTest", "infinite_loop", vec![], Some(account))
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt2 = executor.run(transaction2, true).unwrap();
+    assert!(!receipt2.success);
+    assert!(matches!(
+        receipt2.results.last(),
+        Some(Err(RuntimeError::CostLimitExceeded))
+    ));
+    assert_eq!(receipt2.cost_units_consumed, 1_000_000);
+}
+
+#[test]
+fn test_cost_limit_on_kernel_calls() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+
+    // Leave 1,000 cost units on top of what locking the fee consumes
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(receipt.success);
+    executor.set_cost_unit_limit(receipt.cost_units_consumed + 1_000);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(SYSTEM_COMPONENT, "free_xrd", vec!["1".to_owned()], None)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, true).unwrap();
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.results.last(),
+        Some(Err(RuntimeError::CostLimitExceeded))
+    ));
+}
+
+#[test]
+fn test_fee() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let collected = |executor: &TransactionExecutor<InMemoryLedger>| {
+        executor
+            .ledger()
+            .get_vault(XRD_VAULT_ID)
+            .unwrap()
+            .amount(Actor::SuperUser)
+            .unwrap()
+    };
+
+    let balance_change = |receipt: &Receipt, component: Address| {
+        receipt
+            .balance_changes
+            .iter()
+            .find(|c| c.component_address == Some(component))
+            .unwrap()
+            .changes[0]
+            .amount
+    };
+
+    // A successful transaction pays for what it consumes and gets the rest refunded
+    let before = collected(&executor);
+    let transaction1 = TransactionBuilder::new(&executor)
+        .call_method(
+            account,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .lock_fee(10.into(), account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt1 = executor.run(transaction1, true).unwrap();
+    assert!(receipt1.success);
+    let fee1 = receipt1.fee.as_ref().unwrap();
+    assert!(fee1.charged.is_positive());
+    assert_eq!(
+        fee1.charged,
+        executor.cost_unit_price() * receipt1.cost_units_consumed
+    );
+    assert_eq!(fee1.refunded, Decimal::from(10) - fee1.charged);
+    assert_eq!(collected(&executor), before + fee1.charged);
+    assert_eq!(balance_change(&receipt1, account), -fee1.charged);
+
+    // A failed transaction still pays, and is rolled back except for the fee
+    let before = collected(&executor);
+    let transaction2 = TransactionBuilder::new(&executor)
+        .lock_fee(10.into(), account)
+        .call_method(
+            account,
+            "withdraw",
+            vec!["2000000".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt2 = executor.run(transaction2, true).unwrap();
+    assert!(!receipt2.success);
+    let fee2 = receipt2.fee.as_ref().unwrap();
+    assert!(fee2.charged.is_positive());
+    assert_eq!(fee2.refunded, Decimal::from(10) - fee2.charged);
+    assert_eq!(collected(&executor), before + fee2.charged);
+    assert_eq!(balance_change(&receipt2, account), -fee2.charged);
+
+    // The fee lock caps the cost units, and the whole fee locked is charged once they're used up
+    let before = collected(&executor);
+    let locked: Decimal = "0.001".parse().unwrap();
+    let transaction3 = TransactionBuilder::new(&executor)
+        .lock_fee(locked, account)
+        .call_method(
+            account,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt3 = executor.run(transaction3, true).unwrap();
+    assert!(!receipt3.success);
+    assert!(matches!(
+        receipt3.results.last(),
+        Some(Err(RuntimeError::CostLimitExceeded))
+    ));
+    let fee3 = receipt3.fee.as_ref().unwrap();
+    assert_eq!(fee3.charged, locked);
+    assert_eq!(fee3.refunded, Decimal::zero());
+    assert_eq!(collected(&executor), before + locked);
+
+    // A transaction which can't lock its fee is rejected
+    let transaction4 = TransactionBuilder::new(&executor)
+        .lock_fee(2_000_000.into(), account)
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    assert!(matches!(
+        executor.run(transaction4, true),
+        Err(TransactionExecutionError::FeeLockFailed(_))
+    ));
+
+    // Buckets declared along with a fee lock are reserved before the fee is locked
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let transaction5 = TransactionBuilder::new(&executor)
+        .lock_fee(10.into(), account)
+        .call_method(
+            account2,
+            "deposit",
+            vec![format!("1,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt5 = executor.run(transaction5, true).unwrap();
+    assert!(receipt5.success);
+    let fee5 = receipt5.fee.as_ref().unwrap();
+    assert_eq!(balance_change(&receipt5, account2), Decimal::from(1));
+    assert_eq!(
+        balance_change(&receipt5, account),
+        -(Decimal::from(1) + fee5.charged)
+    );
+
+    // A payer which refuses the refund is charged the whole fee locked, and the transaction
+    // still succeeds
+    let package = executor.publish_package(&compile("cost"));
+    let transaction6 = TransactionBuilder::new(&executor)
+        .lock_fee(10.into(), account)
+        .call_function(
+            package,
+            "FeePayer",
+            "new",
+            vec![format!("20,{}", RADIX_TOKEN)],
+            Some(account),
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt6 = executor.run(transaction6, true).unwrap();
+    assert!(receipt6.success);
+    let payer = receipt6.component(0).unwrap();
+
+    let before = collected(&executor);
+    let transaction7 = TransactionBuilder::new(&executor)
+        .lock_fee(10.into(), payer)
+        .call_function(
+            package,
+            "CostTest",
+            "bounded_loop",
+            vec!["10".to_owned()],
+            None,
+        )
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt7 = executor.run(transaction7, true).unwrap();
+    assert!(receipt7.success);
+    let fee7 = receipt7.fee.as_ref().unwrap();
+    assert_eq!(fee7.charged, Decimal::from(10));
+    assert_eq!(fee7.refunded, Decimal::zero());
+    assert_eq!(collected(&executor), before + Decimal::from(10));
+    assert_eq!(balance_change(&receipt7, payer), Decimal::from(-10));
+}
+
+#[test]
+fn test_call_trace() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let transfer = |executor: &TransactionExecutor<InMemoryLedger>| {
+        TransactionBuilder::new(executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), account)
+            .call_method(
+                account,
+                "withdraw",
+                vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+                Some(account),
+            )
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk])
+    };
+
+    // No trace is recorded by default
+    let receipt1 = executor.run(transfer(&executor), false).unwrap();
+    assert!(receipt1.success);
+    assert!(receipt1.call_traces.is_none());
+
+    // The receipt records one node for each call made by the transaction, starting with the
+    // withdrawal of the fee locked
+    executor.set_call_trace(true);
+    let receipt2 = executor.run(transfer(&executor), false).unwrap();
+    assert!(receipt2.success);
+    let call_traces = receipt2.call_traces.unwrap();
+    assert_eq!(call_traces.len(), 3);
+    assert_eq!(call_traces[0].function, "withdraw");
+
+    let withdraw = &call_traces[1];
+    assert_eq!(withdraw.function, "withdraw");
+    assert_eq!(withdraw.component_address, Some(account));
+    assert!(matches!(withdraw.actor, Actor::SuperUser));
+    assert_eq!(withdraw.args.len(), 3);
+    assert!(withdraw.args.iter().all(|arg| arg.is_ok()));
+    assert!(matches!(withdraw.output, Some(Ok(_))));
+    assert!(withdraw.error.is_none());
+    assert!(withdraw.buckets_in.is_empty());
+    assert_eq!(withdraw.buckets_out.len(), 1);
+    assert_eq!(withdraw.buckets_out[0].1, Decimal::one());
+    assert_eq!(withdraw.buckets_out[0].2, RADIX_TOKEN);
+    assert!(!withdraw.kernel_calls.is_empty());
+
+    let deposit = &call_traces[2];
+    assert_eq!(deposit.function, "deposit_batch");
+    assert_eq!(deposit.buckets_in, withdraw.buckets_out);
+    assert!(deposit.buckets_out.is_empty());
+}
+
+#[test]
+fn test_balance_changes() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(
+            account1,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account1),
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let fee = receipt.fee.as_ref().unwrap();
+
+    // The fee collected is a balance change too
+    assert_eq!(receipt.balance_changes.len(), 3);
+
+    let changes_of = |account: Address| {
+        receipt
+            .balance_changes
+            .iter()
+            .find(|c| c.component_address == Some(account))
+            .map(|c| c.changes.clone())
+            .unwrap()
+    };
+    let changes1 = changes_of(account1);
+    assert_eq!(changes1.len(), 1);
+    assert_eq!(changes1[0].resource_address, RADIX_TOKEN);
+    assert_eq!(changes1[0].amount, -(Decimal::one() + fee.charged));
+    assert!(changes1[0].nfts_added.is_empty());
+    let changes2 = changes_of(account2);
+    assert_eq!(changes2.len(), 1);
+    assert_eq!(changes2[0].resource_address, RADIX_TOKEN);
+    assert_eq!(changes2[0].amount, Decimal::one());
+
+    // A vault handed over to another component is attributed to its new holder
+    let package = executor.publish_package(&compile("component"));
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_function(package, "VaultHolder", "with_token", vec![], None)
+        .call_function(package, "VaultHolder", "empty", vec![], None)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let holder1 = receipt.component(0).unwrap();
+    let holder2 = receipt.component(1).unwrap();
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(holder1, "hand_over", vec![holder2.to_string()], None)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    assert!(executor.run(transaction, false).unwrap().success);
+
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(holder2, "withdraw", vec!["1".to_owned()], None)
+        .deposit_all_buckets(account1)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert!(receipt
+        .balance_changes
+        .iter()
+        .all(|c| c.component_address != Some(holder1)));
+    let changes = receipt
+        .balance_changes
+        .iter()
+        .find(|c| c.component_address == Some(holder2))
+        .unwrap();
+    assert_eq!(changes.changes[0].amount, Decimal::from(-1));
+}
+
+#[test]
+fn test_state_diff() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    executor.set_state_diff(true);
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(
+            account1,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account1),
+        )
+        .deposit_all_buckets(account2)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let state_diff = receipt.state_diff.unwrap();
+
+    // Only the two XRD vaults have changed, along with the system vault collecting the fee
+    assert_eq!(state_diff.len(), 3);
+    let mut total = Decimal::zero();
+    for change in state_diff {
+        let vid = match change.id {
+            SubstateId::Vault(vid) => vid,
+            _ => panic!("Unexpected substate change: {:?}", change.id),
+        };
+        let before = match change.decode_before() {
+            Some(Ok(Substate::Vault(vault))) => vault,
+            _ => panic!("Invalid vault"),
+        };
+        let after = match change.decode_after() {
+            Ok(Substate::Vault(vault)) => vault,
+            _ => panic!("Invalid vault"),
+        };
+        assert_eq!(
+            executor
+                .ledger()
+                .get_vault(vid)
+                .unwrap()
+                .amount(Actor::SuperUser)
+                .unwrap(),
+            after.amount(Actor::SuperUser).unwrap()
+        );
+        total += after.amount(Actor::SuperUser).unwrap() - before.amount(Actor::SuperUser).unwrap();
+    }
+    assert_eq!(total, Decimal::zero());
+}
+
+#[test]
+fn test_manifest() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+
+    // A built transaction, which locks a fee and passes a bucket, survives a round trip through
+    // its manifest
+    let transaction1 = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(
+            account2,
+            "deposit",
+            vec![format!("1,{}", RADIX_TOKEN)],
+            Some(account1),
+        )
+        .build(vec![key1])
+        .unwrap();
+    let manifest = radix_engine::manifest::decompile(&transaction1).unwrap();
+    let transaction2 = radix_engine::manifest::compile(&manifest).unwrap();
+    assert_eq!(scrypto_encode(&transaction2), scrypto_encode(&transaction1));
+    let receipt1 = executor.run(transaction2.sign([&sk1]), false).unwrap();
+    assert!(receipt1.success);
+    assert!(receipt1.fee.is_some());
+
+    // A manifest written by hand, with named buckets
+    let manifest = format!(
+        r#"
+            # move 2 XRD from account1 to account2
+            LOCK_FEE Address("{account1}") Decimal("100");
+            CALL_METHOD Address("{account1}") "withdraw" Decimal("2") Address("{xrd}");
+            TAKE_FROM_CONTEXT Decimal("2") Address("{xrd}") Bucket("xrd");
+            CALL_METHOD Address("{account2}") "deposit" Bucket("xrd");
+            END Address("{key1}");
+        "#,
+        account1 = account1,
+        account2 = account2,
+        xrd = RADIX_TOKEN,
+        key1 = key1
+    );
+    let transaction3 = radix_engine::manifest::compile(&manifest).unwrap();
+    let receipt2 = executor.run(transaction3.sign([&sk1]), false).unwrap();
+    assert!(receipt2.success);
+}
+
+#[test]
+fn test_context_assertions() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+    let mut run = |amount: Decimal, not_contained: Option<Address>| {
+        let mut builder = TransactionBuilder::new(&executor);
+        builder
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), account)
+            .call_method(
+                account,
+                "withdraw",
+                vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+                Some(account),
+            )
+            .assert_context_contains(amount, RADIX_TOKEN);
+        if let Some(resource_address) = not_contained {
+            builder.assert_context_does_not_contain(resource_address);
+        }
+        let transaction = builder
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        executor.run(transaction, false).unwrap()
+    };
+
+    // The assertions pass when the context holds the resources
+    let receipt1 = run(1.into(), key.signer_badge());
+    assert!(receipt1.success);
+
+    // Otherwise the transaction fails
+    let receipt2 = run(2.into(), None);
+    assert!(!receipt2.success);
+    assert!(matches!(
+        receipt2.results.last(),
+        Some(Err(RuntimeError::ContextAssertionFailed(r))) if *r == RADIX_TOKEN
+    ));
+    let receipt3 = run(1.into(), Some(RADIX_TOKEN));
+    assert!(!receipt3.success);
+
+    // NFTs are asserted by ID
+    let nft = new_nft_resource(&mut executor, account, key, &sk, vec![1, 2]);
+    let mut run_nfts = |ids: Vec<u128>| {
+        let transaction = TransactionBuilder::new(&executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), account)
+            .call_method_with_args(
+                account,
+                "withdraw_nfts",
+                vec![
+                    SmartValue::from(BTreeSet::from([1u128, 2u128])),
+                    SmartValue::from(nft),
+                ],
+            )
+            .assert_context_contains_nfts(ids.into_iter().collect(), nft)
+            .deposit_all_buckets(account)
+            .build(vec![key])
+            .unwrap()
+            .sign([&sk]);
+        executor.run(transaction, false).unwrap()
+    };
+    let receipt4 = run_nfts(vec![1, 2]);
+    assert!(receipt4.success);
+    let receipt5 = run_nfts(vec![1, 3]);
+    assert!(!receipt5.success);
+    assert!(matches!(
+        receipt5.results.last(),
+        Some(Err(RuntimeError::ContextAssertionFailed(r))) if *r == nft
+    ));
+
+    // The assertions survive a manifest round trip
+    let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(executor.nonce())
+        .lock_fee(100.into(), account)
+        .call_method(
+            account,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account),
+        )
+        .call_method_with_args(
+            account,
+            "withdraw_nfts",
+            vec![
+                SmartValue::from(BTreeSet::from([1u128])),
+                SmartValue::from(nft),
+            ],
+        )
+        .assert_context_contains(1.into(), RADIX_TOKEN)
+        .assert_context_contains_nfts(BTreeSet::from([1]), nft)
+        .assert_context_does_not_contain(key.signer_badge().unwrap())
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap();
+    let manifest = radix_engine::manifest::decompile(&transaction).unwrap();
+    assert!(manifest.contains("ASSERT_CONTEXT_CONTAINS Decimal(\"1\")"));
+    assert!(manifest.contains("ASSERT_CONTEXT_CONTAINS_NFTS TreeSet<u128>(1u128)"));
+    assert!(manifest.contains("ASSERT_CONTEXT_DOES_NOT_CONTAIN"));
+    let transaction = radix_engine::manifest::compile(&manifest).unwrap();
+    assert_eq!(
+        radix_engine::manifest::decompile(&transaction).unwrap(),
+        manifest
+    );
+    let receipt6 = executor.run(transaction.sign([&sk]), false).unwrap();
+    assert!(receipt6.success);
+}
+
+#[test]
+fn test_take_all_and_return_to_context() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account1)
+        .call_method(
+            account1,
+            "withdraw",
+            vec!["3".to_owned(), RADIX_TOKEN.to_string()],
+            Some(account1),
+        )
+        .declare_bucket(|builder, bid| {
+            builder
+                .take_all_from_context(RADIX_TOKEN, bid)
+                .assert_context_does_not_contain(RADIX_TOKEN)
+                .return_to_context(bid)
+        })
+        .declare_bucket(|builder, bid| {
+            builder
+                .take_from_context(1.into(), RADIX_TOKEN, bid)
+                .add_instruction(Instruction::CallMethod {
+                    component_address: account2,
+                    method: "deposit".to_owned(),
+                    args: vec![SmartValue::from(bid)],
+                })
+        })
+        .assert_context_contains(2.into(), RADIX_TOKEN)
+        .deposit_all_buckets(account1)
+        .build(vec![key1])
+        .unwrap()
+        .sign([&sk1]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let changes2 = receipt
+        .balance_changes
+        .iter()
+        .find(|c| c.component_address == Some(account2))
+        .map(|c| c.changes.clone())
+        .unwrap();
+    assert_eq!(changes2[0].amount, Decimal::one());
+}
+
+#[test]
+fn test_take_nfts_from_context() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, _) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+
+    let nft = new_nft_resource(&mut executor, account1, key1, &sk1, vec![1, 2, 3]);
+
+    let mut run = |ids: Vec<u128>, take: Vec<u128>, resource_address: Address| {
+        let transaction = TransactionBuilder::new(&executor)
+            .set_nonce(executor.nonce())
+            .lock_fee(100.into(), account1)
+            .call_method_with_args(
+                account1,
+                "withdraw_nfts",
+                vec![
+                    SmartValue::from(ids.into_iter().collect::<BTreeSet<u128>>()),
+                    SmartValue::from(nft),
+                ],
+            )
+            .call_method(
+                account1,
+                "withdraw",
+                vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+                Some(account1),
+            )
+            .declare_bucket(|builder, bid| {
+                builder
+                    .take_nfts_from_context(take.into_iter().collect(), resource_address, bid)
+                    .add_instruction(Instruction::CallMethod {
+                        component_address: account2,
+                        method: "deposit".to_owned(),
+                        args: vec![SmartValue::from(bid)],
+                    })
+            })
+            .deposit_all_buckets(account1)
+            .build(vec![key1])
+            .unwrap()
+            .sign([&sk1]);
+        executor.run(transaction, false).unwrap()
+    };
+
+    // The selected NFTs are taken, the rest stays in context
+    let receipt1 = run(vec![1, 2], vec![1], nft);
+    assert!(receipt1.success);
+    let changes2 = receipt1
+        .balance_changes
+        .iter()
+        .find(|c| c.component_address == Some(account2))
+        .map(|c| c.changes.clone())
+        .unwrap();
+    assert_eq!(changes2[0].nfts_added, BTreeSet::from([1]));
+
+    // An NFT which is not in context can't be taken
+    let receipt2 = run(vec![2], vec![3], nft);
+    assert!(!receipt2.success);
+    assert!(matches!(
+        receipt2.results.last(),
+        Some(Err(RuntimeError::BucketError(BucketError::NftNotFound)))
+    ));
+
+    // Nor can a fungible resource be taken by ID
+    let receipt3 = run(vec![2], vec![1], RADIX_TOKEN);
+    assert!(!receipt3.success);
+    assert!(matches!(
+        receipt3.results.last(),
+        Some(Err(RuntimeError::BucketError(
+            BucketError::UnsupportedOperation
+        )))
+    ));
+}
+
+#[test]
+fn test_typed_args() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key, sk) = executor.new_key_pair();
+    let account = executor.new_account(key);
+
+    // Encoded arguments are checked against the ABI
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method_with_args(
+            account,
+            "withdraw",
+            vec![
+                SmartValue::from(Decimal::one()),
+                SmartValue::from(RADIX_TOKEN),
+            ],
+        )
+        .deposit_all_buckets(account)
+        .build(vec![key])
+        .unwrap()
+        .sign([&sk]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+
+    let result = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method_with_args(
+            account,
+            "withdraw",
+            vec![SmartValue::from(1u32), SmartValue::from(RADIX_TOKEN)],
+        )
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::MismatchedType(0, _)
+        ))
+    ));
+
+    let result = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method_with_values(
+            account,
+            "withdraw",
+            vec![
+                sbor::any::decode_any(&scrypto_encode(&Decimal::one())).unwrap(),
+                sbor::any::decode_any(&scrypto_encode(&RADIX_TOKEN)).unwrap(),
+                sbor::any::Value::Unit,
+            ],
+        )
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::UnexpectedArgument(2)
+        ))
+    ));
+
+    // Composite arguments can be passed as strings in the manifest value syntax
+    let result = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(
+            account,
+            "withdraw_nfts",
+            vec![
+                "TreeSet<u128>(1u128, 2u128)".to_owned(),
+                RADIX_TOKEN.to_string(),
+            ],
+            None,
+        )
+        .build(vec![key]);
+    assert!(result.is_ok());
+
+    let result = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), account)
+        .call_method(
+            account,
+            "withdraw_nfts",
+            vec!["TreeSet<u32>(1u32)".to_owned(), RADIX_TOKEN.to_string()],
+            None,
+        )
+        .build(vec![key]);
+    assert!(matches!(
+        result,
+        Err(BuildTransactionError::FailedToBuildArgs(
+            BuildArgsError::MismatchedType(0, _)
+        ))
+    ));
+}
+
+#[test]
+fn test_batch() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (key1, sk1) = executor.new_key_pair();
+    let account1 = executor.new_account(key1);
+    let (key2, sk2) = executor.new_key_pair();
+    let account2 = executor.new_account(key2);
+    let transfer = |executor: &TransactionExecutor<InMemoryLedger>, nonce: u64, amount: u32| {
+        TransactionBuilder::new(executor)
+            .set_nonce(nonce)
+            .lock_fee(1.into(), account2)
+            .call_method(
+                account1,
+                "withdraw",
+                vec![amount.to_string(), RADIX_TOKEN.to_string()],
+                Some(account1),
+            )
+            .deposit_all_buckets(account2)
+            .build(vec![key1, key2])
+            .unwrap()
+            .sign([&sk1, &sk2])
+    };
+
+    // Nothing is committed if any transaction fails, and no fee is charged
+    let nonce = executor.nonce();
+    let batch = vec![transfer(&executor, 1, 1), transfer(&executor, 2, 2_000_000)];
+    let receipts = executor.run_batch(batch, false).unwrap();
+    assert_eq!(receipts.len(), 2);
+    assert!(receipts[0].success);
+    assert!(!receipts[1].success);
+    assert!(receipts.iter().all(|r| r.fee.is_none()));
+    assert_eq!(executor.nonce(), nonce);
+    let receipt = executor.run(transfer(&executor, 1, 1), false).unwrap();
+    assert!(receipt.success);
+
+    // Nor if any transaction is rejected
+    let nonce = executor.nonce();
+    let batch = vec![
+        transfer(&executor, 3, 999_990),
+        transfer(&executor, 4, 8),
+        transfer(&executor, 4, 8),
+    ];
+    let result = executor.run_batch(batch, false);
+    assert!(matches!(
+        result,
+        Err((2, TransactionExecutionError::DuplicateTransaction(_)))
+    ));
+    assert_eq!(executor.nonce(), nonce);
+
+    // Otherwise all transactions are committed, each seeing the updates of the ones before
+    let batch = vec![transfer(&executor, 3, 999_990), transfer(&executor, 4, 8)];
+    let receipts = executor.run_batch(batch, false).unwrap();
+    assert!(receipts.iter().all(|r| r.success && r.fee.is_some()));
+    assert_ne!(receipts[0].transaction_hash, receipts[1].transaction_hash);
+    assert_eq!(executor.nonce(), nonce + 2);
+    let receipt = executor.run(transfer(&executor, 5, 2), false).unwrap();
+    assert!(!receipt.success);
+    let result = executor.run(transfer(&executor, 4, 8), false);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::DuplicateTransaction(_))
+    ));
+}
+
+#[test]
+fn test_partial_transactions() {
+    let mut ledger = InMemoryLedger::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, 0, 0);
+    let (alice_key, alice_sk) = executor.new_key_pair();
+    let alice = executor.new_account(alice_key);
+    let (bob_key, bob_sk) = executor.new_key_pair();
+    let bob = executor.new_account(bob_key);
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), bob)
+        .new_token_fixed(HashMap::new(), 100.into())
+        .deposit_all_buckets(bob)
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let token = executor
+        .run(transaction, false)
+        .unwrap()
+        .resource_def(0)
+        .unwrap();
+
+    // Alice offers 100 XRD for 10 tokens, without knowing where the tokens come from
+    let offer = |executor: &TransactionExecutor<InMemoryLedger>, nonce: u64| {
+        TransactionBuilder::new(executor)
+            .set_nonce(nonce)
+            .assert_context_contains(10.into(), token)
+            .call_method(
+                alice,
+                "withdraw",
+                vec!["100".to_owned(), RADIX_TOKEN.to_string()],
+                Some(alice),
+            )
+            .call_method(alice, "deposit", vec![format!("10,{}", token)], None)
+            .build_partial(vec![alice_key])
+            .unwrap()
+            .sign([&alice_sk])
+    };
+
+    // Bob combines the offer with his side of the swap, and only signs his own instructions
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), bob)
+        .call_method(
+            bob,
+            "withdraw",
+            vec!["10".to_owned(), token.to_string()],
+            Some(bob),
+        )
+        .add_partial_transaction(offer(&executor, 1))
+        .deposit_all_buckets(bob)
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    let changes = |account: Address| {
+        receipt
+            .balance_changes
+            .iter()
+            .find(|c| c.component_address == Some(account))
+            .map(|c| {
+                c.changes
+                    .iter()
+                    .map(|change| (change.resource_address, change.amount))
+                    .collect::<Vec<(Address, Decimal)>>()
+            })
+            .unwrap()
+    };
+    assert!(changes(alice).contains(&(RADIX_TOKEN, Decimal::from(-100))));
+    assert!(changes(alice).contains(&(token, Decimal::from(10))));
+    let fee = receipt.fee.as_ref().unwrap();
+    assert!(changes(bob).contains(&(RADIX_TOKEN, Decimal::from(100) - fee.charged)));
+    assert!(changes(bob).contains(&(token, Decimal::from(-10))));
+
+    // The outputs of the partial transaction are in the receipt, one for each instruction
+    let offer_len = offer(&executor, 1).partial.instructions.len();
+    match &receipt.results[2] {
+        Ok(Some(output)) => assert!(matches!(
+            sbor::any::decode_any(&output.encoded),
+            Ok(sbor::any::Value::Tuple(outputs)) if outputs.len() == offer_len
+        )),
+        _ => panic!("Unexpected result"),
+    }
+
+    // The offer can't be replayed
+    let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(2)
+        .lock_fee(100.into(), bob)
+        .add_partial_transaction(offer(&executor, 1))
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let result = executor.run(transaction, false);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::DuplicateTransaction(_))
+    ));
+
+    // Nor can it be tampered with
+    let mut tampered = offer(&executor, 2);
+    tampered.partial.instructions.pop();
+    let transaction = TransactionBuilder::new(&executor)
+        .lock_fee(100.into(), bob)
+        .add_partial_transaction(tampered)
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let result = executor.run(transaction, false);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::InvalidPartialSignature(1))
+    ));
+
+    // Alice's signature doesn't cover instructions outside her partial transaction
+    let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(3)
+        .lock_fee(100.into(), bob)
+        .call_method(
+            bob,
+            "withdraw",
+            vec!["10".to_owned(), token.to_string()],
+            Some(bob),
+        )
+        .add_partial_transaction(offer(&executor, 3))
+        .call_method(
+            alice,
+            "withdraw",
+            vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+            Some(alice),
+        )
+        .deposit_all_buckets(bob)
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.results[2].is_ok());
+    assert!(receipt.results[3].is_err());
+
+    // Nor can the partial transaction use buckets declared outside of it
+    let partial = TransactionBuilder::new(&executor)
+        .set_nonce(4)
+        .call_method_with_args(alice, "deposit", vec![SmartValue::from(Bid(0))])
+        .build_partial(vec![alice_key])
+        .unwrap()
+        .sign([&alice_sk]);
+    let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(4)
+        .lock_fee(100.into(), bob)
+        .declare_bucket(|builder, bid| {
+            builder
+                .call_method(
+                    bob,
+                    "withdraw",
+                    vec!["1".to_owned(), RADIX_TOKEN.to_string()],
+                    Some(bob),
+                )
+                .take_from_context(1.into(), RADIX_TOKEN, bid)
+        })
+        .add_partial_transaction(partial)
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let result = executor.run(transaction, false);
+    assert!(matches!(
+        result,
+        Err(TransactionExecutionError::InvalidTransaction(v))
+            if matches!(v.as_slice(), [
+                (4, TransactionValidationError::UndeclaredBucket(Bid(0))),
+            ])
+    ));
+
+    // Nor can the signer badge of Alice outlive the partial transaction
+    let package = executor.publish_package(&compile("badge"));
+    let badge = alice_key.signer_badge().unwrap();
+    let partial = TransactionBuilder::new(&executor)
+        .set_nonce(5)
+        .declare_bucket_ref(|builder, rid| {
+            builder
+                .borrow_from_context(1.into(), badge, rid)
+                .call_function_with_args(package, "SignerTest", "pass", vec![SmartValue::from(rid)])
+        })
+        .build_partial(vec![alice_key])
+        .unwrap()
+        .sign([&alice_sk]);
+    let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(5)
+        .lock_fee(100.into(), bob)
+        .add_partial_transaction(partial)
+        .drop_all_bucket_refs()
+        .deposit_all_buckets(bob)
+        .build(vec![bob_key])
+        .unwrap()
+        .sign([&bob_sk]);
+    let receipt = executor.run(transaction, false).unwrap();
+    assert!(receipt.success);
+    assert!(receipt.balance_changes.iter().all(|c| c
+        .changes
+        .iter()
+        .all(|change| change.resource_address != badge)));
+}
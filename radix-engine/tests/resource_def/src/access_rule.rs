@@ -0,0 +1,94 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct AccessRuleTest {
+        treasury: Vault,
+        badges: Vec<Vault>,
+        tokens: Vault,
+    }
+
+    impl AccessRuleTest {
+        /// Creates a treasury which allows withdrawal with either badge A and two of
+        /// badges B, C and D, or at least 100 tokens.
+        pub fn new() -> Component {
+            let badges: Vec<Bucket> = (0..4)
+                .map(|_| ResourceBuilder::new_fungible(DIVISIBILITY_NONE).initial_supply_fungible(1))
+                .collect();
+            let tokens = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM).initial_supply_fungible(200);
+            let rule = AccessRule::any_of(vec![
+                AccessRule::all_of(vec![
+                    AccessRule::require(badges[0].resource_def()),
+                    AccessRule::count_of(
+                        2,
+                        vec![
+                            AccessRule::require(badges[1].resource_def()),
+                            AccessRule::require(badges[2].resource_def()),
+                            AccessRule::require(badges[3].resource_def()),
+                        ],
+                    ),
+                ]),
+                AccessRule::require_amount(100, tokens.resource_def()),
+            ]);
+            let treasury = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .flags(RESTRICTED_TRANSFER)
+                .withdraw_rule(rule)
+                .initial_supply_fungible(1000);
+
+            Self {
+                treasury: Vault::with_bucket(treasury),
+                badges: badges.into_iter().map(Vault::with_bucket).collect(),
+                tokens: Vault::with_bucket(tokens),
+            }
+            .instantiate()
+        }
+
+        /// Withdraws from the treasury, presenting the badges at the given indices and
+        /// the given amount of tokens.
+        pub fn withdraw(&self, amount: Decimal, badges: Vec<u8>, tokens: Decimal) -> Bucket {
+            let buckets: Vec<(u8, Bucket)> =
+                badges.into_iter().map(|i| (i, self.badges[i as usize].take(1))).collect();
+            let tokens = self.tokens.take(tokens);
+
+            let mut auths: Vec<BucketRef> = buckets.iter().map(|(_, b)| b.present()).collect();
+            if !tokens.is_empty() {
+                auths.push(tokens.present());
+            }
+            let withdrawn = self.treasury.take_with_auths(amount, auths);
+
+            for (i, bucket) in buckets {
+                self.badges[i as usize].put(bucket);
+            }
+            self.tokens.put(tokens);
+            withdrawn
+        }
+
+        /// Withdraws from the treasury, presenting the given amount of tokens twice.
+        pub fn withdraw_with_duplicate_ref(&self, amount: Decimal, tokens: Decimal) -> Bucket {
+            let tokens = self.tokens.take(tokens);
+            let auth = tokens.present();
+            let withdrawn = self.treasury.take_with_auths(amount, vec![auth.clone(), auth]);
+            self.tokens.put(tokens);
+            withdrawn
+        }
+
+        /// Creates a resource with a withdraw rule which requires none of the rules.
+        pub fn create_with_empty_rule(all_of: bool) -> Bucket {
+            let rule = if all_of {
+                AccessRule::all_of(vec![])
+            } else {
+                AccessRule::count_of(0, vec![])
+            };
+            ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .flags(RESTRICTED_TRANSFER)
+                .withdraw_rule(rule)
+                .initial_supply_fungible(1000)
+        }
+
+        /// Creates a resource with a withdraw rule, but without `RESTRICTED_TRANSFER`.
+        pub fn create_with_unrestricted_transfer() -> Bucket {
+            ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .withdraw_rule(AccessRule::require(RADIX_TOKEN))
+                .initial_supply_fungible(1000)
+        }
+    }
+}
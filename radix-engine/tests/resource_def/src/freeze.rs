@@ -0,0 +1,36 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct FreezeTest {
+        token: ResourceDef,
+        badge: Vault,
+    }
+
+    impl FreezeTest {
+        pub fn new(freezable: bool) -> (Component, Bucket) {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE).initial_supply_fungible(1);
+            let tokens = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .flags(if freezable { FREEZABLE } else { 0 })
+                .badge(badge.resource_address(), MAY_FREEZE)
+                .initial_supply_fungible(100);
+
+            let component = Self {
+                token: tokens.resource_def(),
+                badge: Vault::with_bucket(badge),
+            }
+            .instantiate();
+            (component, tokens)
+        }
+
+        pub fn freeze(&self, vid: Vid, freeze_flags: u8) {
+            self.badge
+                .authorize(|auth| self.token.freeze_vault(vid, freeze_flags, auth));
+        }
+
+        pub fn unfreeze(&self, vid: Vid) {
+            self.badge
+                .authorize(|auth| self.token.unfreeze_vault(vid, auth));
+        }
+    }
+}
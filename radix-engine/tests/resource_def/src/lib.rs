@@ -1 +1,4 @@
 pub mod resource_def;
+pub mod recall;
+pub mod freeze;
+pub mod access_rule;
@@ -0,0 +1,31 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    struct RecallTest {
+        token: ResourceDef,
+        badge: Vault,
+    }
+
+    impl RecallTest {
+        pub fn new(recallable: bool) -> (Component, Bucket) {
+            let badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE).initial_supply_fungible(1);
+            let tokens = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "TestToken")
+                .flags(if recallable { RECALLABLE } else { 0 })
+                .badge(badge.resource_address(), MAY_RECALL)
+                .initial_supply_fungible(100);
+
+            let component = Self {
+                token: tokens.resource_def(),
+                badge: Vault::with_bucket(badge),
+            }
+            .instantiate();
+            (component, tokens)
+        }
+
+        pub fn recall(&self, vid: Vid, amount: Decimal) -> Bucket {
+            self.badge
+                .authorize(|auth| self.token.recall(vid, amount, auth))
+        }
+    }
+}
@@ -22,6 +22,8 @@ pub struct Blueprint {
     pub name: String,
     pub functions: Vec<Function>,
     pub methods: Vec<Method>,
+    #[cfg_attr(any(feature = "serde_std", feature = "serde_alloc"), serde(default))]
+    pub events: Vec<Event>,
 }
 
 /// Represents a function.
@@ -62,3 +64,14 @@ pub enum Mutability {
     /// A mutable method requires a mutable reference to component state.
     Mutable,
 }
+
+/// Represents an event.
+#[cfg_attr(
+    any(feature = "serde_std", feature = "serde_alloc"),
+    derive(Serialize, Deserialize)
+)]
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct Event {
+    pub name: String,
+    pub schema: Type,
+}
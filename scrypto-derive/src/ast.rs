@@ -1,7 +1,7 @@
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{ItemImpl, ItemStruct, Path, Result};
+use syn::{ItemImpl, ItemStruct, Path, Result, Type};
 
 /// Represents the AST of blueprint.
 pub struct Blueprint {
@@ -18,6 +18,19 @@ impl Parse for Blueprint {
     }
 }
 
+/// Represents the AST of events declared by a blueprint.
+pub struct Events {
+    pub types: Punctuated<Type, Comma>,
+}
+
+impl Parse for Events {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self {
+            types: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
 /// Represents the AST of allowed badges for authorization.
 pub struct Auth {
     pub allowed: Punctuated<Path, Comma>,
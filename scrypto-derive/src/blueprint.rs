@@ -96,6 +96,7 @@ pub fn handle_blueprint(input: TokenStream) -> Result<TokenStream> {
 
     let abi_ident = format_ident!("{}_abi", bp_ident);
     let (abi_functions, abi_methods) = generate_abi(bp_ident, bp_items)?;
+    let abi_events = generate_abi_events(&bp_strut.attrs)?;
     let output_abi = quote! {
         #[no_mangle]
         pub extern "C" fn #abi_ident() -> *mut u8 {
@@ -107,7 +108,8 @@ pub fn handle_blueprint(input: TokenStream) -> Result<TokenStream> {
 
             let functions: Vec<Function> = vec![ #(#abi_functions),* ];
             let methods: Vec<Method> = vec![ #(#abi_methods),* ];
-            let output = (functions, methods);
+            let events: Vec<::scrypto::abi::Event> = vec![ #(#abi_events),* ];
+            let output = (functions, methods, events);
 
             // serialize the output
             let output_bytes = ::scrypto::buffer::scrypto_encode_for_kernel(&output);
@@ -358,6 +360,28 @@ fn generate_abi(bp_ident: &Ident, items: &[ImplItem]) -> Result<(Vec<Expr>, Vec<
     Ok((functions, methods))
 }
 
+// Parses the `events` attribute of the blueprint struct and returns ABI of events.
+fn generate_abi_events(attrs: &[Attribute]) -> Result<Vec<Expr>> {
+    let mut events = Vec::<Expr>::new();
+
+    for attr in attrs {
+        if attr.path.get_ident().map(ToString::to_string) == Some("events".to_string()) {
+            let declared = attr.parse_args::<ast::Events>()?;
+            for ty in declared.types {
+                trace!("Processing event: {}", quote! { #ty });
+                events.push(parse_quote! {
+                    ::scrypto::abi::Event {
+                        name: <#ty as ::scrypto::core::Event>::name(),
+                        schema: <#ty>::describe(),
+                    }
+                });
+            }
+        }
+    }
+
+    Ok(events)
+}
+
 // Parses function items of an `Impl` and returns ABI of functions.
 fn generate_stubs(bp_ident: &Ident, items: &[ImplItem]) -> Result<TokenStream> {
     let bp_name = bp_ident.to_string();
@@ -607,7 +631,8 @@ mod tests {
                         ],
                         output: <u32>::describe(),
                     }];
-                    let output = (functions, methods);
+                    let events: Vec<::scrypto::abi::Event> = vec![];
+                    let output = (functions, methods, events);
                     let output_bytes = ::scrypto::buffer::scrypto_encode_for_kernel(&output);
                     ::scrypto::buffer::scrypto_wrap(output_bytes)
                 }
@@ -51,7 +51,7 @@ fn assert_json_eq<T: Serialize>(actual: T, expected: Value) {
 #[test]
 fn test_simple_auth() {
     let ptr = SimpleAuth_abi();
-    let abi: (Vec<abi::Function>, Vec<abi::Method>) =
+    let abi: (Vec<abi::Function>, Vec<abi::Method>, Vec<abi::Event>) =
         unsafe { scrypto_consume(ptr, |slice| scrypto_decode(slice).unwrap()) };
 
     assert_json_eq(
@@ -131,7 +131,8 @@ fn test_simple_auth() {
                         "generics": []
                     }
                 }
-            ]
+            ],
+            []
         ]),
     );
 }
@@ -47,7 +47,7 @@ fn assert_json_eq<T: Serialize>(actual: T, expected: Value) {
 #[test]
 fn test_simple_abi() {
     let ptr = Simple_abi();
-    let abi: (Vec<abi::Function>, Vec<abi::Method>) =
+    let abi: (Vec<abi::Function>, Vec<abi::Method>, Vec<abi::Event>) =
         unsafe { scrypto_consume(ptr, |slice| scrypto_decode(slice).unwrap()) };
 
     assert_json_eq(
@@ -189,7 +189,8 @@ fn test_simple_abi() {
                         "type": "Unit"
                     }
                 }
-            ]
+            ],
+            []
         ]),
     );
 }
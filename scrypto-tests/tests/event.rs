@@ -0,0 +1,81 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sbor::*;
+use scrypto::abi;
+use scrypto::buffer::*;
+use scrypto::prelude::*;
+use serde::Serialize;
+use serde_json::{json, to_value, Value};
+
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Swapped {
+    pub amount: Decimal,
+    pub price: u32,
+}
+
+impl Event for Swapped {}
+
+blueprint! {
+    #[events(Swapped)]
+    struct Exchange {
+        price: u32,
+    }
+
+    impl Exchange {
+        pub fn swap(&self, amount: Decimal) {
+            Swapped {
+                amount,
+                price: self.price,
+            }
+            .emit();
+        }
+    }
+}
+
+fn assert_json_eq<T: Serialize>(actual: T, expected: Value) {
+    assert_eq!(to_value(&actual).unwrap(), expected);
+}
+
+#[test]
+fn test_event_name() {
+    assert_eq!(Swapped::name(), "Swapped");
+}
+
+#[test]
+fn test_event_abi() {
+    let ptr = Exchange_abi();
+    let abi: (Vec<abi::Function>, Vec<abi::Method>, Vec<abi::Event>) =
+        unsafe { scrypto_consume(ptr, |slice| scrypto_decode(slice).unwrap()) };
+
+    assert_json_eq(
+        abi.2,
+        json!([
+            {
+                "name": "Swapped",
+                "schema": {
+                    "type": "Struct",
+                    "name": "Swapped",
+                    "fields": {
+                        "type": "Named",
+                        "named": [
+                            [
+                                "amount",
+                                {
+                                    "type": "Custom",
+                                    "name": "scrypto::types::Decimal",
+                                    "generics": []
+                                }
+                            ],
+                            [
+                                "price",
+                                {
+                                    "type": "U32"
+                                }
+                            ]
+                        ]
+                    }
+                }
+            }
+        ]),
+    );
+}
@@ -1,5 +1,6 @@
 use crate::kernel::*;
 use crate::rust::borrow::ToOwned;
+use crate::rust::string::String;
 use crate::rust::vec::Vec;
 use crate::types::*;
 
@@ -32,3 +33,22 @@ pub fn call_method(component_address: Address, method: &str, args: Vec<Vec<u8>>)
 
     output.rtn
 }
+
+/// Invokes a method on a component, returning an error instead of aborting if the call fails.
+///
+/// The state updates of a failed call are rolled back and the buckets passed to it are
+/// returned to the caller.
+pub fn call_method_try(
+    component_address: Address,
+    method: &str,
+    args: Vec<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    let input = CallMethodTryInput {
+        component_address,
+        method: method.to_owned(),
+        args,
+    };
+    let output: CallMethodTryOutput = call_kernel(CALL_METHOD_TRY, input);
+
+    output.rtn
+}
@@ -4,6 +4,7 @@ use crate::buffer::*;
 use crate::core::*;
 use crate::kernel::*;
 use crate::rust::borrow::ToOwned;
+use crate::rust::string::String;
 use crate::rust::vec;
 use crate::rust::vec::Vec;
 use crate::types::*;
@@ -59,6 +60,17 @@ impl Component {
         scrypto_unwrap(scrypto_decode(&output))
     }
 
+    /// Invokes a method on this component, returning an error instead of aborting if the
+    /// call fails.
+    ///
+    /// The state updates of a failed call are rolled back and the buckets passed to it are
+    /// returned to the caller.
+    pub fn call_try<T: Decode>(&self, method: &str, args: Vec<Vec<u8>>) -> Result<T, String> {
+        let output = call_method_try(self.address, method, args)?;
+
+        Ok(scrypto_unwrap(scrypto_decode(&output)))
+    }
+
     /// Returns the state of this component.
     pub fn get_state<T: ComponentState>(&self) -> T {
         let input = GetComponentStateInput {
@@ -78,6 +90,17 @@ impl Component {
         let _: PutComponentStateOutput = call_kernel(PUT_COMPONENT_STATE, input);
     }
 
+    /// Allows read-only methods of this component to be called while another of its
+    /// methods is running.
+    ///
+    /// Re-entrant calls to mutable methods are always rejected.
+    pub fn allow_reentrant_reads(&self) {
+        let input = AllowReentrantReadsInput {
+            component_address: self.address,
+        };
+        let _: AllowReentrantReadsOutput = call_kernel(ALLOW_REENTRANT_READS, input);
+    }
+
     /// Returns the blueprint that this component is instantiated from.
     pub fn blueprint(&self) -> Blueprint {
         let input = GetComponentInfoInput {
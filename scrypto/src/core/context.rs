@@ -34,4 +34,11 @@ impl Context {
         let output: GetTransactionSignersOutput = call_kernel(GET_TRANSACTION_SIGNERS, input);
         output.transaction_signers
     }
+
+    /// Returns the depth of the current call, starting from `1` for calls made by the transaction.
+    pub fn call_depth() -> u32 {
+        let input = GetCallDepthInput {};
+        let output: GetCallDepthOutput = call_kernel(GET_CALL_DEPTH, input);
+        output.call_depth
+    }
 }
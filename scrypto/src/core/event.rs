@@ -0,0 +1,41 @@
+use sbor::{describe::Type, *};
+
+use crate::buffer::*;
+use crate::kernel::*;
+use crate::rust::string::String;
+
+/// Represents a typed event which can be emitted by a blueprint.
+///
+/// Events are identified by the name of their type. An event must be listed in the
+/// `#[events(...)]` attribute of the blueprint struct, which makes it part of the blueprint ABI,
+/// and its data is checked against the schema in the ABI when emitted.
+///
+/// # Example
+/// ```ignore
+/// #[derive(TypeId, Encode, Decode, Describe)]
+/// pub struct Swapped {
+///     amount: Decimal,
+/// }
+///
+/// impl Event for Swapped {}
+///
+/// Swapped { amount: 1.into() }.emit();
+/// ```
+pub trait Event: Encode + Describe {
+    /// Returns the name of this event.
+    fn name() -> String {
+        match Self::describe() {
+            Type::Struct { name, .. } | Type::Enum { name, .. } => name,
+            _ => panic!("An event must be a struct or an enum"),
+        }
+    }
+
+    /// Emits this event.
+    fn emit(&self) {
+        let input = EmitEventInput {
+            name: Self::name(),
+            data: scrypto_encode(self),
+        };
+        let _: EmitEventOutput = call_kernel(EMIT_EVENT, input);
+    }
+}
@@ -3,6 +3,7 @@ mod blueprint;
 mod call;
 mod component;
 mod context;
+mod event;
 mod lazy_map;
 mod logger;
 mod package;
@@ -10,9 +11,10 @@ mod uuid;
 
 pub use account::Account;
 pub use blueprint::Blueprint;
-pub use call::{call_function, call_method};
+pub use call::{call_function, call_method, call_method_try};
 pub use component::{Component, ComponentState};
 pub use context::Context;
+pub use event::Event;
 pub use lazy_map::LazyMap;
 pub use logger::Logger;
 pub use package::Package;
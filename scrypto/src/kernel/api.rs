@@ -1,6 +1,7 @@
 use sbor::{Decode, Encode, TypeId};
 
 use crate::kernel::*;
+use crate::resource::AccessRule;
 use crate::rust::collections::HashMap;
 use crate::rust::string::String;
 use crate::rust::vec::Vec;
@@ -18,6 +19,8 @@ pub const PUBLISH_PACKAGE: u32 = 0x00;
 pub const CALL_FUNCTION: u32 = 0x01;
 /// Call a method
 pub const CALL_METHOD: u32 = 0x02;
+/// Call a method, rolling back its state updates if it fails
+pub const CALL_METHOD_TRY: u32 = 0x03;
 
 /// Create a component
 pub const CREATE_COMPONENT: u32 = 0x10;
@@ -27,6 +30,8 @@ pub const GET_COMPONENT_INFO: u32 = 0x11;
 pub const GET_COMPONENT_STATE: u32 = 0x12;
 /// Update component state
 pub const PUT_COMPONENT_STATE: u32 = 0x13;
+/// Allow re-entrant calls to read-only methods of a component
+pub const ALLOW_REENTRANT_READS: u32 = 0x14;
 
 /// Create a lazy map
 pub const CREATE_LAZY_MAP: u32 = 0x20;
@@ -61,6 +66,8 @@ pub const GET_NFT_DATA: u32 = 0x3a;
 pub const UPDATE_NFT_MUTABLE_DATA: u32 = 0x3b;
 /// Update resource metadata
 pub const UPDATE_RESOURCE_METADATA: u32 = 0x3c;
+/// Create a resource with a withdraw rule
+pub const CREATE_RESOURCE_WITH_WITHDRAW_RULE: u32 = 0x3d;
 
 /// Create an empty vault
 pub const CREATE_EMPTY_VAULT: u32 = 0x40;
@@ -76,6 +83,14 @@ pub const GET_VAULT_RESOURCE_ADDRESS: u32 = 0x44;
 pub const TAKE_NFT_FROM_VAULT: u32 = 0x45;
 /// Get the IDs of all NFTs in this vault
 pub const GET_NFT_IDS_IN_VAULT: u32 = 0x46;
+/// Recall resource from any vault
+pub const RECALL_FROM_VAULT: u32 = 0x47;
+/// Get the freeze flags of this vault
+pub const GET_VAULT_FREEZE_FLAGS: u32 = 0x48;
+/// Update the freeze flags of any vault
+pub const UPDATE_VAULT_FREEZE_FLAGS: u32 = 0x49;
+/// Take fungible resource from this vault, presenting multiple badges
+pub const TAKE_FROM_VAULT_WITH_AUTHS: u32 = 0x4a;
 
 /// Create an empty bucket
 pub const CREATE_EMPTY_BUCKET: u32 = 0x50;
@@ -119,6 +134,10 @@ pub const GET_TRANSACTION_HASH: u32 = 0xf4;
 pub const GET_TRANSACTION_SIGNERS: u32 = 0xf5;
 /// Generate an UUID
 pub const GENERATE_UUID: u32 = 0xf6;
+/// Retrieve current call depth
+pub const GET_CALL_DEPTH: u32 = 0xf7;
+/// Emit an event
+pub const EMIT_EVENT: u32 = 0xf8;
 
 //==========
 // blueprint
@@ -159,6 +178,18 @@ pub struct CallMethodOutput {
     pub rtn: Vec<u8>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CallMethodTryInput {
+    pub component_address: Address,
+    pub method: String,
+    pub args: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CallMethodTryOutput {
+    pub rtn: Result<Vec<u8>, String>,
+}
+
 //==========
 // component
 //==========
@@ -204,6 +235,14 @@ pub struct PutComponentStateInput {
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct PutComponentStateOutput {}
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct AllowReentrantReadsInput {
+    pub component_address: Address,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct AllowReentrantReadsOutput {}
+
 //==========
 // LazyMap
 //==========
@@ -257,6 +296,12 @@ pub struct CreateResourceOutput {
     pub bucket: Option<Bid>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct CreateResourceWithWithdrawRuleInput {
+    pub resource: CreateResourceInput,
+    pub withdraw_rule: AccessRule,
+}
+
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct MintResourceInput {
     pub resource_address: Address,
@@ -458,6 +503,52 @@ pub struct GetNftIdsInVaultOutput {
     pub ids: Vec<u128>,
 }
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct RecallFromVaultInput {
+    pub resource_address: Address,
+    pub vid: Vid,
+    pub amount: Decimal,
+    pub auth: Rid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct RecallFromVaultOutput {
+    pub bid: Bid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetVaultFreezeFlagsInput {
+    pub vid: Vid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetVaultFreezeFlagsOutput {
+    pub freeze_flags: u8,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct UpdateVaultFreezeFlagsInput {
+    pub resource_address: Address,
+    pub vid: Vid,
+    pub new_freeze_flags: u8,
+    pub auth: Rid,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct UpdateVaultFreezeFlagsOutput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct TakeFromVaultWithAuthsInput {
+    pub vid: Vid,
+    pub amount: Decimal,
+    pub auths: Vec<Rid>,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct TakeFromVaultWithAuthsOutput {
+    pub bid: Bid,
+}
+
 //==========
 // bucket
 //==========
@@ -608,6 +699,15 @@ pub struct EmitLogInput {
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct EmitLogOutput {}
 
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct EmitEventInput {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct EmitEventOutput {}
+
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct GetPackageAddressInput {}
 
@@ -656,3 +756,11 @@ pub struct GenerateUuidInput {}
 pub struct GenerateUuidOutput {
     pub uuid: u128,
 }
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetCallDepthInput {}
+
+#[derive(Debug, Clone, TypeId, Encode, Decode)]
+pub struct GetCallDepthOutput {
+    pub call_depth: u32,
+}
@@ -0,0 +1,70 @@
+use sbor::*;
+
+use crate::resource::*;
+use crate::rust::vec::Vec;
+use crate::types::*;
+
+/// A rule which authorizes an operation, based on the resources presented.
+///
+/// Each bucket counts once, however many references to it are presented.
+///
+/// # Example
+/// ```ignore
+/// // Requires badge A, and two of badges B, C and D
+/// let rule = AccessRule::all_of(vec![
+///     AccessRule::require(a),
+///     AccessRule::count_of(2, vec![
+///         AccessRule::require(b),
+///         AccessRule::require(c),
+///         AccessRule::require(d),
+///     ]),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, TypeId, Encode, Decode)]
+pub enum AccessRule {
+    /// Requires at least the given amount of a resource.
+    Require {
+        resource_address: Address,
+        amount: Decimal,
+    },
+
+    /// Requires all of the rules, which must not be empty.
+    AllOf(Vec<AccessRule>),
+
+    /// Requires at least the given number of rules, which must be between one and the
+    /// number of rules.
+    CountOf(u8, Vec<AccessRule>),
+}
+
+impl AccessRule {
+    /// Requires one unit of a badge.
+    pub fn require<A: Into<ResourceDef>>(badge: A) -> Self {
+        Self::require_amount(1, badge)
+    }
+
+    /// Requires at least the given amount of a resource.
+    pub fn require_amount<T: Into<Decimal>, A: Into<ResourceDef>>(
+        amount: T,
+        resource_def: A,
+    ) -> Self {
+        Self::Require {
+            resource_address: resource_def.into().address(),
+            amount: amount.into(),
+        }
+    }
+
+    /// Requires all of the rules.
+    pub fn all_of(rules: Vec<AccessRule>) -> Self {
+        Self::AllOf(rules)
+    }
+
+    /// Requires any of the rules.
+    pub fn any_of(rules: Vec<AccessRule>) -> Self {
+        Self::CountOf(1, rules)
+    }
+
+    /// Requires at least `count` of the rules.
+    pub fn count_of(count: u8, rules: Vec<AccessRule>) -> Self {
+        Self::CountOf(count, rules)
+    }
+}
@@ -1,3 +1,4 @@
+mod access_rule;
 mod bucket;
 mod bucket_ref;
 mod nft;
@@ -12,6 +13,10 @@ pub mod resource_flags;
 /// Various resource permissions.
 pub mod resource_permissions;
 
+/// Vault freeze flags.
+pub mod vault_freeze_flags;
+
+pub use access_rule::AccessRule;
 pub use bucket::Bucket;
 pub use bucket_ref::BucketRef;
 pub use nft::Nft;
@@ -21,3 +26,4 @@ pub use resource_def::ResourceDef;
 pub use resource_flags::*;
 pub use resource_permissions::*;
 pub use vault::Vault;
+pub use vault_freeze_flags::*;
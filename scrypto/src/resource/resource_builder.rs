@@ -17,6 +17,7 @@ pub struct ResourceBuilder {
     flags: u16,
     mutable_flags: u16,
     authorities: HashMap<Address, u16>,
+    withdraw_rule: Option<AccessRule>,
 }
 
 impl ResourceBuilder {
@@ -28,6 +29,7 @@ impl ResourceBuilder {
             flags: 0,
             mutable_flags: 0,
             authorities: HashMap::new(),
+            withdraw_rule: None,
         }
     }
 
@@ -72,6 +74,16 @@ impl ResourceBuilder {
         self
     }
 
+    /// Sets the rule for withdrawing resource from vaults.
+    ///
+    /// Withdrawal is allowed if the badges presented satisfy this rule, in addition to
+    /// badges with `MAY_TRANSFER` permission. The resource must have `RESTRICTED_TRANSFER` on,
+    /// or its creation fails.
+    pub fn withdraw_rule(&mut self, rule: AccessRule) -> &mut Self {
+        self.withdraw_rule = Some(rule);
+        self
+    }
+
     /// Creates resource with the given initial supply.
     pub fn initial_supply(&self, supply: NewSupply) -> Bucket {
         self.build(Some(supply)).1.unwrap()
@@ -116,6 +128,27 @@ impl ResourceBuilder {
     }
 
     fn build(&self, supply: Option<NewSupply>) -> (ResourceDef, Option<Bucket>) {
+        if let Some(withdraw_rule) = &self.withdraw_rule {
+            let input = CreateResourceWithWithdrawRuleInput {
+                resource: CreateResourceInput {
+                    resource_type: self.resource_type,
+                    metadata: self.metadata.clone(),
+                    flags: self.flags,
+                    mutable_flags: self.mutable_flags,
+                    authorities: self.authorities.clone(),
+                    initial_supply: supply,
+                },
+                withdraw_rule: withdraw_rule.clone(),
+            };
+            let output: CreateResourceOutput =
+                call_kernel(CREATE_RESOURCE_WITH_WITHDRAW_RULE, input);
+
+            return (
+                output.resource_address.into(),
+                output.bucket.map(Into::into),
+            );
+        }
+
         ResourceDef::new(
             self.resource_type,
             self.metadata.clone(),
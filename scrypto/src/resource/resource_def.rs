@@ -107,6 +107,39 @@ impl ResourceDef {
         let _output: BurnResourceOutput = call_kernel(BURN_RESOURCE, input);
     }
 
+    /// Recalls some amount of resource from any vault, provided the resource is recallable.
+    ///
+    /// A frozen vault can still be recalled from, as recall ignores its freeze flags.
+    pub fn recall<A: Into<Decimal>>(&self, vid: Vid, amount: A, auth: BucketRef) -> Bucket {
+        let input = RecallFromVaultInput {
+            resource_address: self.address,
+            vid,
+            amount: amount.into(),
+            auth: auth.into(),
+        };
+        let output: RecallFromVaultOutput = call_kernel(RECALL_FROM_VAULT, input);
+
+        output.bid.into()
+    }
+
+    /// Freezes a vault holding this resource, provided the resource is freezable.
+    ///
+    /// The new freeze flags replace the existing ones.
+    pub fn freeze_vault(&self, vid: Vid, freeze_flags: u8, auth: BucketRef) {
+        let input = UpdateVaultFreezeFlagsInput {
+            resource_address: self.address,
+            vid,
+            new_freeze_flags: freeze_flags,
+            auth: auth.into(),
+        };
+        let _output: UpdateVaultFreezeFlagsOutput = call_kernel(UPDATE_VAULT_FREEZE_FLAGS, input);
+    }
+
+    /// Unfreezes a vault holding this resource.
+    pub fn unfreeze_vault(&self, vid: Vid, auth: BucketRef) {
+        self.freeze_vault(vid, 0, auth)
+    }
+
     /// Returns the resource type.
     pub fn resource_type(&self) -> ResourceType {
         let input = GetResourceTypeInput {
@@ -10,7 +10,7 @@ pub const FREELY_BURNABLE: u16 = 1u16 << 2;
 /// New supply can be minted.
 pub const MINTABLE: u16 = 1u16 << 3;
 
-/// (Not implemented) Resource can be seized from any vault if proper authority is presented.
+/// Resource can be seized from any vault if proper authority is presented.
 pub const RECALLABLE: u16 = 1u16 << 4;
 
 /// Top-level resource metadata can be changed.
@@ -19,5 +19,8 @@ pub const SHARED_METADATA_MUTABLE: u16 = 1u16 << 5;
 /// The mutable data part of an individual NFT can be modified.
 pub const INDIVIDUAL_METADATA_MUTABLE: u16 = 1u16 << 6;
 
+/// Individual vaults can be frozen if proper authority is presented.
+pub const FREEZABLE: u16 = 1u16 << 7;
+
 /// All resources flags.
 pub const ALL_FLAGS: u16 = !0u16;
@@ -7,7 +7,7 @@ pub const MAY_BURN: u16 = 1u16 << 2;
 /// May create new supply.
 pub const MAY_MINT: u16 = 1u16 << 4;
 
-/// (Not implemented) May seize from any vault.
+/// May seize from any vault, if the resource is recallable.
 pub const MAY_RECALL: u16 = 1u16 << 5;
 
 /// May change top-level resource metadata, e.g. name and symbol.
@@ -19,5 +19,8 @@ pub const MAY_CHANGE_INDIVIDUAL_METADATA: u16 = 1u16 << 7;
 /// May change mutable flags.
 pub const MAY_MANAGE_RESOURCE_FLAGS: u16 = 1u16 << 7;
 
+/// May freeze or unfreeze vaults.
+pub const MAY_FREEZE: u16 = 1u16 << 8;
+
 /// All permissions.
 pub const ALL_PERMISSIONS: u16 = !0u16;
@@ -80,6 +80,21 @@ impl Vault {
         output.bid.into()
     }
 
+    /// Takes some amount of resource from this vault into a bucket, presenting multiple badges.
+    ///
+    /// This is required when the resource's withdraw rule can only be satisfied by badges
+    /// presented together.
+    pub fn take_with_auths<A: Into<Decimal>>(&self, amount: A, auths: Vec<BucketRef>) -> Bucket {
+        let input = TakeFromVaultWithAuthsInput {
+            vid: self.vid,
+            amount: amount.into(),
+            auths: auths.into_iter().map(Into::into).collect(),
+        };
+        let output: TakeFromVaultWithAuthsOutput = call_kernel(TAKE_FROM_VAULT_WITH_AUTHS, input);
+
+        output.bid.into()
+    }
+
     /// Takes all resource stored in this vault.
     pub fn take_all(&self) -> Bucket {
         self.take(self.amount())
@@ -180,6 +195,16 @@ impl Vault {
         self.resource_def().address()
     }
 
+    /// Returns the freeze flags of this vault.
+    ///
+    /// Only the package which created this vault can read its freeze flags.
+    pub fn freeze_flags(&self) -> u8 {
+        let input = GetVaultFreezeFlagsInput { vid: self.vid };
+        let output: GetVaultFreezeFlagsOutput = call_kernel(GET_VAULT_FREEZE_FLAGS, input);
+
+        output.freeze_flags
+    }
+
     /// Checks if this vault is empty.
     pub fn is_empty(&self) -> bool {
         self.amount() == 0.into()
@@ -0,0 +1,8 @@
+/// Resource can not be taken from the vault.
+pub const FREEZE_WITHDRAW: u8 = 1u8 << 0;
+
+/// Resource can not be put into the vault.
+pub const FREEZE_DEPOSIT: u8 = 1u8 << 1;
+
+/// Resource can neither be taken from nor put into the vault.
+pub const FREEZE_ALL: u8 = FREEZE_WITHDRAW | FREEZE_DEPOSIT;
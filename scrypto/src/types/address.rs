@@ -8,6 +8,7 @@ use crate::rust::str::FromStr;
 use crate::rust::vec;
 use crate::rust::vec::Vec;
 use crate::types::*;
+use crate::utils::*;
 
 /// The package which defines the `System` blueprint.
 pub const SYSTEM_PACKAGE: Address = Address::Package([
@@ -78,6 +79,18 @@ impl Address {
     pub fn is_public_key(&self) -> bool {
         matches!(self, Address::PublicKey(_))
     }
+
+    /// Returns the virtual badge of a public key, which is held by transactions signed with the key.
+    ///
+    /// The badge can be borrowed from transaction context, but never withdrawn or deposited.
+    pub fn signer_badge(&self) -> Option<Address> {
+        match self {
+            Self::PublicKey(_) => Some(Address::ResourceDef(
+                sha256_twice(self.to_vec()).lower_26_bytes(),
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl FromStr for Address {
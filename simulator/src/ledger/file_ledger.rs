@@ -19,12 +19,21 @@ const LAZY_MAPS: &str = "lazy_maps";
 const RESOURCE_DEFS: &str = "resource_defs";
 const VAULTS: &str = "vaults";
 const NFTS: &str = "nfts";
+const TRANSACTIONS: &str = "transactions";
 
 const FILE_EXT: &str = "sbor";
 
 impl FileBasedLedger {
     pub fn new(root: PathBuf) -> Self {
-        for folder in [PACKAGES, COMPONENTS, LAZY_MAPS, RESOURCE_DEFS, VAULTS, NFTS] {
+        for folder in [
+            PACKAGES,
+            COMPONENTS,
+            LAZY_MAPS,
+            RESOURCE_DEFS,
+            VAULTS,
+            NFTS,
+            TRANSACTIONS,
+        ] {
             let mut path = root.clone();
             path.push(folder);
             if !path.exists() {
@@ -176,4 +185,16 @@ impl Ledger for FileBasedLedger {
             Self::encode(&nft),
         )
     }
+
+    fn has_transaction(&self, transaction_hash: H256) -> bool {
+        self.get_path(TRANSACTIONS, transaction_hash.to_string(), FILE_EXT)
+            .exists()
+    }
+
+    fn put_transaction(&mut self, transaction_hash: H256) {
+        Self::write(
+            self.get_path(TRANSACTIONS, transaction_hash.to_string(), FILE_EXT),
+            [],
+        )
+    }
 }
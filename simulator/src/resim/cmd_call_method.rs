@@ -9,6 +9,7 @@ const ARG_METHOD: &str = "METHOD";
 const ARG_ARGS: &str = "ARGS";
 
 const ARG_TRACE: &str = "TRACE";
+const ARG_PREVIEW: &str = "PREVIEW";
 const ARG_SIGNERS: &str = "SIGNERS";
 
 /// Constructs a `call-method` subcommand.
@@ -28,7 +29,7 @@ pub fn make_call_method<'a, 'b>() -> App<'a, 'b> {
         )
         .arg(
             Arg::with_name(ARG_ARGS)
-            .help("Specify the arguments, e.g. \"5\", \"hello\", \"amount,resource_address\" for Bucket, or \"#id1,#id2,..,resource_address\" for NFT Bucket.")
+            .help("Specify the arguments, e.g. \"5\", \"hello\", \"amount,resource_address\" for Bucket, \"#id1,#id2,..,resource_address\" for NFT Bucket, or \"Struct { 1u8, \\\"a\\\" }\" for composite types in the manifest value syntax.")
                 .multiple(true),
         )
         // options
@@ -37,11 +38,16 @@ pub fn make_call_method<'a, 'b>() -> App<'a, 'b> {
                 .long("trace")
                 .help("Turn on tracing."),
         )
+        .arg(
+            Arg::with_name(ARG_PREVIEW)
+                .long("preview")
+                .help("Preview the transaction without committing it."),
+        )
         .arg(
             Arg::with_name(ARG_SIGNERS)
                 .long("signers")
                 .takes_value(true)
-                .help("Specify the transaction signers, separated by comma."),
+                .help("Specify the private keys of the transaction signers, separated by comma."),
         )
 }
 
@@ -51,6 +57,7 @@ pub fn handle_call_method(matches: &ArgMatches) -> Result<(), Error> {
     let method = match_string(matches, ARG_METHOD)?;
     let args = match_args(matches, ARG_ARGS)?;
     let trace = matches.is_present(ARG_TRACE);
+    let preview = matches.is_present(ARG_PREVIEW);
     let signers = match_signers(matches, ARG_SIGNERS)?;
 
     let mut configs = get_configs()?;
@@ -58,17 +65,27 @@ pub fn handle_call_method(matches: &ArgMatches) -> Result<(), Error> {
     let mut ledger = FileBasedLedger::with_bootstrap(get_data_dir()?);
     let mut executor = TransactionExecutor::new(&mut ledger, configs.current_epoch, configs.nonce);
     let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(executor.nonce())
+        .lock_fee(FEE_LOCK_AMOUNT.into(), account.0)
         .call_method(component, &method, args, Some(account.0))
         .drop_all_bucket_refs()
         .deposit_all_buckets(account.0)
-        .build(signers)
+        .build(signers.iter().map(EcdsaPrivateKey::public_key).collect())
         .map_err(Error::TransactionConstructionError)?;
-    let receipt = executor.run(transaction, trace).unwrap();
+    let signed = transaction.sign(&signers);
+    let receipt = if preview {
+        executor.preview(signed, true, trace)
+    } else {
+        executor.run(signed, trace)
+    }
+    .map_err(Error::TransactionRejected)?;
 
     println!("{:?}", receipt);
-    if receipt.success {
+    if receipt.committed() {
         configs.nonce = executor.nonce();
         set_configs(configs)?;
+    }
+    if receipt.success {
         Ok(())
     } else {
         Err(Error::TransactionFailed)
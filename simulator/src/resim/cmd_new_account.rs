@@ -24,7 +24,7 @@ pub fn make_new_account<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name(ARG_SIGNERS)
                 .long("signers")
                 .takes_value(true)
-                .help("Specify the transaction signers, separated by comma."),
+                .help("Specify the private keys of the transaction signers, separated by comma."),
         )
 }
 
@@ -36,8 +36,10 @@ pub fn handle_new_account(matches: &ArgMatches) -> Result<(), Error> {
     let mut configs = get_configs()?;
     let mut ledger = FileBasedLedger::with_bootstrap(get_data_dir()?);
     let mut executor = TransactionExecutor::new(&mut ledger, configs.current_epoch, configs.nonce);
-    let key = executor.new_public_key();
+    let private_key = new_private_key()?;
+    let key = private_key.public_key();
     let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(executor.nonce())
         .call_method(
             SYSTEM_COMPONENT,
             "free_xrd",
@@ -45,9 +47,12 @@ pub fn handle_new_account(matches: &ArgMatches) -> Result<(), Error> {
             None,
         )
         .new_account_with_resource(key, 1000000.into(), RADIX_TOKEN)
-        .build(signers)
+        .build(signers.iter().map(EcdsaPrivateKey::public_key).collect())
         .map_err(Error::TransactionConstructionError)?;
-    let receipt = executor.run(transaction, trace).unwrap();
+    // funding a new account from the faucet is a system transaction, which locks no fee
+    let receipt = executor
+        .run_system(transaction.sign(&signers), trace)
+        .map_err(Error::TransactionRejected)?;
     println!("{:?}", receipt);
 
     if receipt.success {
@@ -55,10 +60,18 @@ pub fn handle_new_account(matches: &ArgMatches) -> Result<(), Error> {
         println!("{}", "=".repeat(80));
         println!("A new account has been created!");
         println!("Public key: {}", key.to_string().green());
+        println!(
+            "Private key: {}",
+            hex::encode(private_key.to_bytes()).green()
+        );
+        println!(
+            "Signer badge: {}",
+            key.signer_badge().unwrap().to_string().green()
+        );
         println!("Account address: {}", account.to_string().green());
         if configs.default_account.is_none() {
             println!("As this is the first account, it has been set as your default account.");
-            configs.default_account = Some((receipt.component(0).unwrap(), key));
+            configs.default_account = Some((account, key, private_key.to_bytes()));
         }
         println!("{}", "=".repeat(80));
 
@@ -66,6 +79,10 @@ pub fn handle_new_account(matches: &ArgMatches) -> Result<(), Error> {
         set_configs(configs)?;
         Ok(())
     } else {
+        if receipt.committed() {
+            configs.nonce = executor.nonce();
+            set_configs(configs)?;
+        }
         Err(Error::TransactionFailed)
     }
 }
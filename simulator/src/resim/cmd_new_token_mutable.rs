@@ -35,7 +35,7 @@ pub fn make_new_token_mutable<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name(ARG_SIGNERS)
                 .long("signers")
                 .takes_value(true)
-                .help("Specify the transaction signers, separated by comma."),
+                .help("Specify the private keys of the transaction signers, separated by comma."),
         )
         .arg(
             Arg::with_name(ARG_SYMBOL)
@@ -97,19 +97,26 @@ pub fn handle_new_token_mutable(matches: &ArgMatches) -> Result<(), Error> {
         .and_then(|v| metadata.insert("icon_url".to_owned(), v.to_owned()));
 
     let mut configs = get_configs()?;
+    let account = configs.default_account.ok_or(Error::NoDefaultAccount)?;
     let mut ledger = FileBasedLedger::with_bootstrap(get_data_dir()?);
     let mut executor = TransactionExecutor::new(&mut ledger, configs.current_epoch, configs.nonce);
     let transaction = TransactionBuilder::new(&executor)
+        .set_nonce(executor.nonce())
+        .lock_fee(FEE_LOCK_AMOUNT.into(), account.0)
         .new_token_mutable(metadata, mint_badge_addr)
-        .build(signers)
+        .build(signers.iter().map(EcdsaPrivateKey::public_key).collect())
         .map_err(Error::TransactionConstructionError)?;
 
-    let receipt = executor.run(transaction, trace).unwrap();
+    let receipt = executor
+        .run(transaction.sign(&signers), trace)
+        .map_err(Error::TransactionRejected)?;
 
     println!("{:?}", receipt);
-    if receipt.success {
+    if receipt.committed() {
         configs.nonce = executor.nonce();
         set_configs(configs)?;
+    }
+    if receipt.success {
         Ok(())
     } else {
         Err(Error::TransactionFailed)
@@ -0,0 +1,66 @@
+use std::fs;
+
+use clap::{crate_version, App, Arg, ArgMatches, SubCommand};
+use radix_engine::manifest::compile;
+use radix_engine::transaction::*;
+
+use crate::ledger::*;
+use crate::resim::*;
+
+const ARG_PATH: &str = "PATH";
+
+const ARG_TRACE: &str = "TRACE";
+const ARG_SIGNERS: &str = "SIGNERS";
+
+/// Constructs a `run` subcommand.
+pub fn make_run<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_RUN)
+        .about("Compiles and runs a transaction manifest")
+        .version(crate_version!())
+        .arg(
+            Arg::with_name(ARG_PATH)
+                .help("Specify the path to a transaction manifest file, which must lock a fee.")
+                .required(true),
+        )
+        // options
+        .arg(
+            Arg::with_name(ARG_TRACE)
+                .long("trace")
+                .help("Turn on tracing."),
+        )
+        .arg(
+            Arg::with_name(ARG_SIGNERS)
+                .long("signers")
+                .takes_value(true)
+                .help("Specify the private keys of the transaction signers, separated by comma."),
+        )
+}
+
+/// Handles a `run` request.
+pub fn handle_run(matches: &ArgMatches) -> Result<(), Error> {
+    let path = match_path(matches, ARG_PATH)?;
+    let trace = matches.is_present(ARG_TRACE);
+    let signers = match_signers(matches, ARG_SIGNERS)?;
+
+    let manifest = fs::read_to_string(path).map_err(Error::IOError)?;
+    let mut transaction = compile(&manifest).map_err(Error::CompileError)?;
+
+    let mut configs = get_configs()?;
+    let mut ledger = FileBasedLedger::with_bootstrap(get_data_dir()?);
+    let mut executor = TransactionExecutor::new(&mut ledger, configs.current_epoch, configs.nonce);
+    transaction.header.nonce = executor.nonce();
+    let receipt = executor
+        .run(transaction.sign(&signers), trace)
+        .map_err(Error::TransactionRejected)?;
+
+    println!("{:?}", receipt);
+    if receipt.committed() {
+        configs.nonce = executor.nonce();
+        set_configs(configs)?;
+    }
+    if receipt.success {
+        Ok(())
+    } else {
+        Err(Error::TransactionFailed)
+    }
+}
@@ -3,7 +3,7 @@ use clap::{crate_version, App, Arg, ArgMatches, SubCommand};
 use crate::resim::*;
 
 const ARG_ADDRESS: &str = "ADDRESS";
-const ARG_PUBLIC_KEY: &str = "PUBLIC_KEY";
+const ARG_PRIVATE_KEY: &str = "PRIVATE_KEY";
 
 /// Constructs a `set-default-account` subcommand.
 pub fn make_set_default_account<'a, 'b>() -> App<'a, 'b> {
@@ -16,8 +16,8 @@ pub fn make_set_default_account<'a, 'b>() -> App<'a, 'b> {
                 .required(true),
         )
         .arg(
-            Arg::with_name(ARG_PUBLIC_KEY)
-                .help("Specify the account public key.")
+            Arg::with_name(ARG_PRIVATE_KEY)
+                .help("Specify the account private key.")
                 .required(true),
         )
 }
@@ -25,13 +25,10 @@ pub fn make_set_default_account<'a, 'b>() -> App<'a, 'b> {
 /// Handles a `set-default-account` request.
 pub fn handle_set_default_account(matches: &ArgMatches) -> Result<(), Error> {
     let address = match_address(matches, ARG_ADDRESS)?;
-    let public_key = match_address(matches, ARG_PUBLIC_KEY)?;
-    if !public_key.is_public_key() {
-        return Err(Error::InvalidSignerPublicKey);
-    }
+    let private_key = parse_private_key(&match_string(matches, ARG_PRIVATE_KEY)?)?;
 
     let mut configs = get_configs()?;
-    configs.default_account = Some((address, public_key));
+    configs.default_account = Some((address, private_key.public_key(), private_key.to_bytes()));
     set_configs(configs)?;
 
     println!("Default account set!");
@@ -10,7 +10,8 @@ use crate::resim::*;
 /// Radix Engine configurations.
 #[derive(Debug, Clone, TypeId, Encode, Decode)]
 pub struct Configs {
-    pub default_account: Option<(Address, Address)>,
+    /// The default account address, public key and private key.
+    pub default_account: Option<(Address, Address, [u8; 32])>,
     pub current_epoch: u64,
     pub nonce: u64,
 }
@@ -2,6 +2,7 @@ use std::io;
 use std::num::ParseIntError;
 
 use radix_engine::engine::*;
+use radix_engine::manifest::CompileError;
 use radix_engine::transaction::*;
 use sbor::*;
 use scrypto::types::*;
@@ -30,7 +31,9 @@ pub enum Error {
 
     InvalidConfig(sbor::DecodeError),
 
-    InvalidSignerPublicKey,
+    InvalidSignerPrivateKey,
+
+    RandomnessError(getrandom::Error),
 
     IOError(io::Error),
 
@@ -40,10 +43,14 @@ pub enum Error {
 
     CargoError(CargoExecutionError),
 
+    CompileError(CompileError),
+
     TransactionConstructionError(BuildTransactionError),
 
     TransactionExecutionError(RuntimeError),
 
+    TransactionRejected(TransactionExecutionError),
+
     LedgerDumpError(DisplayError),
 
     TransactionFailed,
@@ -1,5 +1,5 @@
 use clap::ArgMatches;
-use radix_engine::transaction::ResourceAmount;
+use radix_engine::transaction::{EcdsaPrivateKey, ResourceAmount};
 use scrypto::types::*;
 use std::path::PathBuf;
 
@@ -67,23 +67,38 @@ pub fn match_args(matches: &ArgMatches, name: &str) -> Result<Vec<String>, Error
     Ok(v)
 }
 
-/// Match signers
-pub fn match_signers(matches: &ArgMatches, name: &str) -> Result<Vec<Address>, Error> {
-    let mut v = Vec::<Address>::new();
+/// Generates a private key from the randomness of the operating system.
+pub fn new_private_key() -> Result<EcdsaPrivateKey, Error> {
+    let mut bytes = [0u8; 32];
+    loop {
+        getrandom::getrandom(&mut bytes).map_err(Error::RandomnessError)?;
+        if let Some(private_key) = EcdsaPrivateKey::from_bytes(&bytes) {
+            return Ok(private_key);
+        }
+    }
+}
+
+/// Match signers, specified by private keys.
+pub fn match_signers(matches: &ArgMatches, name: &str) -> Result<Vec<EcdsaPrivateKey>, Error> {
+    let mut v = Vec::<EcdsaPrivateKey>::new();
     if let Some(x) = matches.value_of(name) {
         for a in x.split(',') {
-            let address: Address = a.trim().parse().map_err(Error::InvalidAddress)?;
-            if address.is_public_key() {
-                v.push(address);
-            } else {
-                return Err(Error::InvalidSignerPublicKey);
-            }
+            v.push(parse_private_key(a.trim())?);
         }
     }
     if let Some(account) = get_configs()?.default_account {
-        if !v.contains(&account.1) {
-            v.push(account.1);
+        let private_key = EcdsaPrivateKey::from_bytes(&account.2).unwrap();
+        if !v.contains(&private_key) {
+            v.push(private_key);
         }
     }
     Ok(v)
 }
+
+/// Parses a hex-encoded private key.
+pub fn parse_private_key(s: &str) -> Result<EcdsaPrivateKey, Error> {
+    hex::decode(s)
+        .ok()
+        .and_then(|bytes| EcdsaPrivateKey::from_bytes(&bytes))
+        .ok_or(Error::InvalidSignerPrivateKey)
+}
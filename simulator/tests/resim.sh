@@ -9,7 +9,7 @@ resim="cargo run --bin resim $@ --"
 
 # Set up environment
 $resim reset
-temp=`$resim new-account | tee /dev/tty | awk '/Component:|Public key:/ {print $NF}'`
+temp=`$resim new-account | tee /dev/tty | awk '/Component:|Private key:/ {print $NF}'`
 account=`echo $temp | cut -d " " -f1`
 account_key=`echo $temp | cut -d " " -f2`
 account2=`$resim new-account | tee /dev/tty | awk '/Component:/ {print $NF}'`